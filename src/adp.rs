@@ -0,0 +1,251 @@
+use std::f64::consts::PI;
+
+use cgmath::{Matrix, Matrix3, SquareMatrix, Zero};
+
+use crate::{AdpType, Atom, Cell};
+
+/// Factor `8π²` between `B` and `U`.
+pub const U_TO_B: f64 = 8.0 * PI * PI;
+
+/// Converts a displacement parameter `U` in Å² into `B = 8π² U`.
+pub fn u_to_b(u: f64) -> f64 {
+    u * U_TO_B
+}
+
+/// Converts a displacement parameter `B` in Å² into `U = B / 8π²`.
+pub fn b_to_u(b: f64) -> f64 {
+    b / U_TO_B
+}
+
+impl Cell {
+    /// Converts `U` in the CIF convention into the dimensionless `β` with
+    /// `β_ij = 2π² a*_i a*_j U_ij`.
+    pub fn u_cif_to_beta(&self, u: Matrix3<f64>) -> Matrix3<f64> {
+        let n = Matrix3::from_diagonal(self.reciprocal_lengths());
+
+        n * u * n * (2.0 * PI * PI)
+    }
+
+    /// Converts the dimensionless `β` into `U` in the CIF convention.
+    pub fn beta_to_u_cif(&self, beta: Matrix3<f64>) -> Matrix3<f64> {
+        let n_inv = Matrix3::from_diagonal(self.reciprocal_lengths().map(|x| 1.0 / x));
+
+        n_inv * beta * n_inv / (2.0 * PI * PI)
+    }
+
+    /// Converts `U` in the CIF convention into Cartesian `U` in the frame of
+    /// [`Cell::orthogonalization_matrix`].
+    pub fn u_cif_to_u_cart(&self, u: Matrix3<f64>) -> Matrix3<f64> {
+        let a = self.orthogonalization_matrix() * Matrix3::from_diagonal(self.reciprocal_lengths());
+
+        a * u * a.transpose()
+    }
+
+    /// Converts Cartesian `U` into `U` in the CIF convention.
+    pub fn u_cart_to_u_cif(&self, u: Matrix3<f64>) -> Matrix3<f64> {
+        let a = self.orthogonalization_matrix() * Matrix3::from_diagonal(self.reciprocal_lengths());
+        let a_inv = a.invert().expect("Unit cell is degenerate");
+
+        a_inv * u * a_inv.transpose()
+    }
+
+    /// Returns the equivalent isotropic displacement parameter `U_eq`, a third of the trace of
+    /// the Cartesian `U`.
+    pub fn u_equiv(&self, u: Matrix3<f64>) -> f64 {
+        let u_cart = self.u_cif_to_u_cart(u);
+
+        (u_cart.x.x + u_cart.y.y + u_cart.z.z) / 3.0
+    }
+
+    /// Converts an isotropic `U` into the equivalent `U` in the CIF convention.
+    pub fn u_iso_to_u_cif(&self, u_iso: f64) -> Matrix3<f64> {
+        self.u_cart_to_u_cif(Matrix3::from_value(u_iso))
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AdpConversionError(String);
+
+impl AdpConversionError {
+    pub fn new(from: &AdpType, to: &AdpType) -> Self {
+        Self(format!("Cannot convert ADP type {} into {}", from, to))
+    }
+}
+
+impl std::fmt::Display for AdpConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for AdpConversionError {}
+
+impl Atom {
+    /// Returns `U` in the CIF convention, expanding isotropic parameters into a tensor.
+    ///
+    /// Returns `None` for multipole expansions.
+    pub fn u_cif(&self, cell: &Cell) -> Option<Matrix3<f64>> {
+        match self.adp_type {
+            AdpType::Uiso | AdpType::Uovl => Some(cell.u_iso_to_u_cif(self.u_iso_or_equiv)),
+            AdpType::Biso | AdpType::Bovl => Some(cell.u_iso_to_u_cif(b_to_u(self.u_iso_or_equiv))),
+            AdpType::Uani => Some(self.u_ani()),
+            AdpType::Bani => Some(self.u_ani() / U_TO_B),
+            AdpType::Umpe => None,
+        }
+    }
+
+    /// Returns the isotropic or equivalent isotropic `U`, derived from the anisotropic
+    /// components if present.
+    ///
+    /// Returns `None` for multipole expansions.
+    pub fn u_equiv(&self, cell: &Cell) -> Option<f64> {
+        match self.adp_type {
+            AdpType::Uiso | AdpType::Uovl => Some(self.u_iso_or_equiv),
+            AdpType::Biso | AdpType::Bovl => Some(b_to_u(self.u_iso_or_equiv)),
+            AdpType::Uani | AdpType::Bani => self.u_cif(cell).map(|u| cell.u_equiv(u)),
+            AdpType::Umpe => None,
+        }
+    }
+
+    /// Rewrites the displacement parameters in the representation of `target`.
+    ///
+    /// `B` types store `B` in `u_iso_or_equiv` and the tensor components. `u_iso_or_equiv`
+    /// always holds the (equivalent) isotropic value, the tensor components are set to zero for
+    /// isotropic and overall types.
+    pub fn convert_adp(&mut self, target: AdpType, cell: &Cell) -> Result<(), AdpConversionError> {
+        let error = || AdpConversionError::new(&self.adp_type, &target);
+
+        let u = self.u_cif(cell).ok_or_else(error)?;
+        let u_equiv = cell.u_equiv(u);
+
+        let (u_iso_or_equiv, u) = match target {
+            AdpType::Uiso | AdpType::Uovl => (u_equiv, Matrix3::zero()),
+            AdpType::Biso | AdpType::Bovl => (u_to_b(u_equiv), Matrix3::zero()),
+            AdpType::Uani => (u_equiv, u),
+            AdpType::Bani => (u_to_b(u_equiv), u * U_TO_B),
+            AdpType::Umpe => return Err(error()),
+        };
+
+        self.u_iso_or_equiv = u_iso_or_equiv;
+        self.set_u_ani(u);
+        self.adp_type = target;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_adp {
+    use cgmath::{assert_abs_diff_eq, Matrix3, SquareMatrix};
+
+    use crate::{AdpType, Atom, Cell};
+
+    fn triclinic() -> Cell {
+        Cell {
+            a: 5.1,
+            b: 6.2,
+            c: 7.3,
+            alpha: 81.0,
+            beta: 104.0,
+            gamma: 95.0,
+            ..Default::default()
+        }
+    }
+
+    fn u() -> Matrix3<f64> {
+        Matrix3::new(
+            0.02, 0.003, -0.001, 0.003, 0.015, 0.002, -0.001, 0.002, 0.03,
+        )
+    }
+
+    #[test]
+    fn test_u_to_b() {
+        assert_abs_diff_eq!(super::u_to_b(0.01), 0.789568352, epsilon = 1e-8);
+        assert_abs_diff_eq!(
+            super::b_to_u(super::u_to_b(0.0123)),
+            0.0123,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let cell = triclinic();
+
+        assert_abs_diff_eq!(
+            cell.beta_to_u_cif(cell.u_cif_to_beta(u())),
+            u(),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            cell.u_cart_to_u_cif(cell.u_cif_to_u_cart(u())),
+            u(),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_isotropic() {
+        let cell = triclinic();
+        let u = cell.u_iso_to_u_cif(0.01);
+
+        assert_abs_diff_eq!(
+            cell.u_cif_to_u_cart(u),
+            Matrix3::from_value(0.01),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(cell.u_equiv(u), 0.01, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_orthogonal() {
+        let cell = Cell {
+            a: 4.0,
+            b: 5.0,
+            c: 6.0,
+            alpha: 90.0,
+            beta: 90.0,
+            gamma: 90.0,
+            ..Default::default()
+        };
+
+        let u = Matrix3::from_diagonal(cgmath::vec3(0.01, 0.02, 0.03));
+
+        assert_abs_diff_eq!(cell.u_cif_to_u_cart(u), u, epsilon = 1e-12);
+        assert_abs_diff_eq!(cell.u_equiv(u), 0.02, epsilon = 1e-12);
+        assert_abs_diff_eq!(
+            cell.u_cif_to_beta(u).x.x,
+            2.0 * std::f64::consts::PI.powi(2) * 0.01 / 16.0,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_convert_adp() {
+        let cell = triclinic();
+
+        let mut atom = Atom {
+            adp_type: AdpType::Uani,
+            ..Default::default()
+        };
+        atom.set_u_ani(u());
+
+        let u_equiv = atom.u_equiv(&cell).unwrap();
+
+        atom.convert_adp(AdpType::Bani, &cell).unwrap();
+
+        assert_eq!(atom.adp_type, AdpType::Bani);
+        assert_abs_diff_eq!(atom.u11, super::u_to_b(0.02), epsilon = 1e-12);
+        assert_abs_diff_eq!(atom.u_iso_or_equiv, super::u_to_b(u_equiv), epsilon = 1e-12);
+
+        atom.convert_adp(AdpType::Uiso, &cell).unwrap();
+
+        assert_abs_diff_eq!(atom.u_iso_or_equiv, u_equiv, epsilon = 1e-12);
+        assert_eq!(atom.u11, 0.0);
+
+        atom.convert_adp(AdpType::Uani, &cell).unwrap();
+
+        assert_abs_diff_eq!(atom.u_equiv(&cell).unwrap(), u_equiv, epsilon = 1e-12);
+        assert!(atom.convert_adp(AdpType::Umpe, &cell).is_err());
+    }
+}
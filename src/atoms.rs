@@ -126,3 +126,23 @@ pub struct Atom {
     #[cfg_attr(feature = "serde", serde(rename = "U23"))]
     pub u23: f64,
 }
+
+impl Atom {
+    /// Returns the anisotropic displacement parameters as a symmetric matrix.
+    pub fn u_ani(&self) -> cgmath::Matrix3<f64> {
+        cgmath::Matrix3::new(
+            self.u11, self.u12, self.u13, self.u12, self.u22, self.u23, self.u13, self.u23,
+            self.u33,
+        )
+    }
+
+    /// Sets the anisotropic displacement parameters from a symmetric matrix.
+    pub fn set_u_ani(&mut self, u: cgmath::Matrix3<f64>) {
+        self.u11 = u.x.x;
+        self.u22 = u.y.y;
+        self.u33 = u.z.z;
+        self.u12 = u.x.y;
+        self.u13 = u.x.z;
+        self.u23 = u.y.z;
+    }
+}
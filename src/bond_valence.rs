@@ -0,0 +1,360 @@
+use crate::{
+    CoordinationEnvironment, Element, NeighborCriterion, Phase, BOND_VALENCE_PARAMETERS,
+    ELECTRONEGATIVITIES,
+};
+
+/// The default distance in Å up to which bonds contribute to bond valence sums.
+pub const BOND_VALENCE_CUTOFF: f64 = 3.5;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct BondValenceError(String);
+
+impl BondValenceError {
+    pub fn new(s: impl AsRef<str>) -> Self {
+        Self(s.as_ref().to_string())
+    }
+}
+
+impl std::fmt::Display for BondValenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for BondValenceError {}
+
+/// The bond valence sum of a site.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BondValenceSite {
+    /// The index of the site in the atoms of the phase.
+    pub site: usize,
+    pub element: Element,
+    /// The oxidation state of the atom type, or the suggested one if the type has none.
+    pub oxidation_state: Option<i8>,
+    /// The sum of the valences of the bonds to counter-ions weighted by their occupancies,
+    /// always positive. `None` if a bond lacks bond valence parameters.
+    pub bond_valence_sum: Option<f64>,
+    /// The cation oxidation state whose bond valence sum matches it best, `None` for anions
+    /// and cations without bond valence parameters.
+    pub suggested_oxidation_state: Option<i8>,
+    /// The number of positions of the site in the unit cell.
+    pub multiplicity: usize,
+    pub occupancy: f64,
+}
+
+impl BondValenceSite {
+    /// Returns the difference of the bond valence sum and the magnitude of the oxidation state.
+    pub fn deviation(&self) -> Option<f64> {
+        Some(self.bond_valence_sum? - f64::from(self.oxidation_state?.abs()))
+    }
+}
+
+/// The bond valence sums of all sites of a phase.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct BondValenceAnalysis {
+    pub sites: Vec<BondValenceSite>,
+}
+
+impl BondValenceAnalysis {
+    /// Returns the global instability index `GII = sqrt(Σ d_i² / N)` of Salinas-Sanchez et al.
+    /// (1992) over all atoms of the unit cell, i.e. with the sites weighted by multiplicity
+    /// and occupancy. Sites without bond valence sum are left out.
+    pub fn global_instability_index(&self) -> f64 {
+        let (sum, count) = self
+            .sites
+            .iter()
+            .filter_map(|site| Some((site.multiplicity as f64 * site.occupancy, site.deviation()?)))
+            .fold((0.0, 0.0), |(sum, count), (weight, deviation)| {
+                (sum + weight * deviation.powi(2), count + weight)
+            });
+
+        match count > 0.0 {
+            true => (sum / count).sqrt(),
+            false => 0.0,
+        }
+    }
+}
+
+impl Phase {
+    /// Returns the bond valence sums of all sites with bonds up to `cutoff` in Å, see
+    /// [`BOND_VALENCE_CUTOFF`].
+    ///
+    /// Sites are cations or anions by the sign of the oxidation state of their atom type.
+    /// Without one, elements that are anions in [`crate::BOND_VALENCE_PARAMETERS`] are
+    /// anions in their usual oxidation state, unless their nearest neighbor is a more
+    /// electronegative anion as for S in sulfates. Cations without oxidation state are
+    /// assigned the suggested one, i.e. the tabulated oxidation state closest to its bond
+    /// valence sum. Only cation-anion bonds contribute, each weighted by the occupancy of the
+    /// counter-ion.
+    pub fn bond_valence_sums(&self, cutoff: f64) -> Result<BondValenceAnalysis, BondValenceError> {
+        let bonding = Bonding::new(self, cutoff)?;
+        let number_of_sites = self.atoms.len();
+
+        let suggested_oxidation_states = (0..number_of_sites)
+            .map(|site| match bonding.is_anion[site] {
+                true => None,
+                false => bonding.suggest_oxidation_state(site),
+            })
+            .collect::<Vec<_>>();
+
+        // anion sums depend on the oxidation states of the cations
+        let oxidation_states = (0..number_of_sites)
+            .map(|site| match bonding.is_anion[site] {
+                true => Some(bonding.anion_oxidation_state(site)),
+                false => bonding.oxidation_states[site].or(suggested_oxidation_states[site]),
+            })
+            .collect::<Vec<_>>();
+
+        let sites = (0..number_of_sites)
+            .map(|site| BondValenceSite {
+                site,
+                element: bonding.elements[site],
+                oxidation_state: oxidation_states[site],
+                bond_valence_sum: oxidation_states[site]
+                    .and_then(|state| bonding.bond_valence_sum(site, state, &oxidation_states)),
+                suggested_oxidation_state: suggested_oxidation_states[site],
+                multiplicity: bonding.multiplicities[site],
+                occupancy: self.atoms[site].occupancy,
+            })
+            .collect();
+
+        Ok(BondValenceAnalysis { sites })
+    }
+}
+
+/// The counter-ions around every site of a phase.
+struct Bonding {
+    environments: Vec<CoordinationEnvironment>,
+    /// The site and occupancy of each atom of the phase expanded to P1.
+    site_of: Vec<usize>,
+    occupancies: Vec<f64>,
+    multiplicities: Vec<usize>,
+    elements: Vec<Element>,
+    /// The oxidation states of the atom types.
+    oxidation_states: Vec<Option<i8>>,
+    is_anion: Vec<bool>,
+}
+
+impl Bonding {
+    fn new(phase: &Phase, cutoff: f64) -> Result<Self, BondValenceError> {
+        let environments = phase
+            .coordination_environments(NeighborCriterion::Cutoff(cutoff))
+            .map_err(|e| BondValenceError::new(e.to_string()))?;
+
+        let (p1, site_of) = phase.expand_to_p1_with_sites();
+        let mut multiplicities = vec![0; phase.atoms.len()];
+
+        for site in &site_of {
+            multiplicities[*site] += 1;
+        }
+
+        let atom_types = phase
+            .atoms
+            .iter()
+            .map(|atom| {
+                atom.atom_type().ok_or_else(|| {
+                    BondValenceError::new(format!("Unknown element of atom {}", atom.label))
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let elements = atom_types.iter().map(|t| t.element).collect::<Vec<_>>();
+        let electronegativity = |e: Element| ELECTRONEGATIVITIES.get(e).copied();
+
+        let is_anion = environments
+            .iter()
+            .zip(&atom_types)
+            .map(|(environment, atom_type)| match atom_type.oxidation_state {
+                Some(state) => state < 0,
+                None => {
+                    BOND_VALENCE_PARAMETERS.is_anion(atom_type.element)
+                        && !environment.neighbors.first().is_some_and(|nearest| {
+                            let other = elements[site_of[nearest.index]];
+
+                            BOND_VALENCE_PARAMETERS.is_anion(other)
+                                && electronegativity(other) > electronegativity(atom_type.element)
+                        })
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            environments,
+            site_of,
+            occupancies: p1.atoms.iter().map(|atom| atom.occupancy).collect(),
+            multiplicities,
+            elements,
+            oxidation_states: atom_types.iter().map(|t| t.oxidation_state).collect(),
+            is_anion,
+        })
+    }
+
+    /// Returns the oxidation state of an anion, by default that of the noble gas
+    /// configuration like `-2` for O.
+    fn anion_oxidation_state(&self, site: usize) -> i8 {
+        self.oxidation_states[site]
+            .unwrap_or_else(|| self.elements[site].group().map_or(-1, |g| g as i8 - 18))
+    }
+
+    /// Returns the sum of the valences of the bonds to counter-ions weighted by their
+    /// occupancies, with the site in the given oxidation state and the other sites in
+    /// `oxidation_states`. Returns `None` if a bond lacks parameters.
+    fn bond_valence_sum(
+        &self,
+        site: usize,
+        oxidation_state: i8,
+        oxidation_states: &[Option<i8>],
+    ) -> Option<f64> {
+        self.environments[site]
+            .neighbors
+            .iter()
+            .map(|neighbor| (self.site_of[neighbor.index], neighbor))
+            .filter(|(other, _)| self.is_anion[*other] != self.is_anion[site])
+            .map(|(other, neighbor)| {
+                let (cation, state, anion) = match self.is_anion[site] {
+                    true => (other, oxidation_states[other]?, site),
+                    false => (site, oxidation_state, other),
+                };
+
+                BOND_VALENCE_PARAMETERS
+                    .get(self.elements[cation], state, self.elements[anion])
+                    .map(|parameter| {
+                        self.occupancies[neighbor.index] * parameter.bond_valence(neighbor.distance)
+                    })
+            })
+            .sum()
+    }
+
+    /// Returns the tabulated oxidation state of a cation closest to its bond valence sum.
+    fn suggest_oxidation_state(&self, site: usize) -> Option<i8> {
+        let anions = self.environments[site]
+            .neighbors
+            .iter()
+            .map(|neighbor| self.site_of[neighbor.index])
+            .filter(|other| self.is_anion[*other])
+            .collect::<Vec<_>>();
+
+        let mut states = anions
+            .iter()
+            .flat_map(|anion| {
+                BOND_VALENCE_PARAMETERS.oxidation_states(self.elements[site], self.elements[*anion])
+            })
+            .collect::<Vec<_>>();
+        states.sort();
+        states.dedup();
+
+        // the oxidation states of the anions do not enter the sum of a cation
+        let unused = vec![None; self.elements.len()];
+
+        states
+            .into_iter()
+            .filter_map(|state| {
+                let sum = self.bond_valence_sum(site, state, &unused)?;
+                Some((state, (sum - f64::from(state)).abs()))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(state, _)| state)
+    }
+}
+
+#[cfg(test)]
+mod test_bond_valence {
+    use cgmath::assert_abs_diff_eq;
+
+    use crate::{test_util::phase, Element, BOND_VALENCE_CUTOFF};
+
+    #[test]
+    fn test_rock_salt() {
+        let rock_salt = phase(5.64, "Fm-3m", 225, &[("Na", [0.0; 3]), ("Cl", [0.5; 3])]);
+        let analysis = rock_salt.bond_valence_sums(BOND_VALENCE_CUTOFF).unwrap();
+
+        let [na, cl] = [&analysis.sites[0], &analysis.sites[1]];
+        let expected = 6.0 * ((f64::from(2.15f32) - 2.82) / 0.37).exp();
+
+        assert_eq!(na.oxidation_state, Some(1));
+        assert_eq!(na.suggested_oxidation_state, Some(1));
+        assert_abs_diff_eq!(na.bond_valence_sum.unwrap(), expected, epsilon = 1e-8);
+        assert_eq!(cl.element, Element::Cl);
+        assert_eq!(cl.oxidation_state, Some(-1));
+        assert_eq!(cl.suggested_oxidation_state, None);
+        assert_abs_diff_eq!(cl.bond_valence_sum.unwrap(), expected, epsilon = 1e-8);
+        assert_eq!(na.multiplicity, 4);
+        assert_abs_diff_eq!(
+            analysis.global_instability_index(),
+            1.0 - expected,
+            epsilon = 1e-8
+        );
+    }
+
+    #[test]
+    fn test_oxidation_states() {
+        let manganosite = phase(4.445, "Fm-3m", 225, &[("Mn", [0.0; 3]), ("O", [0.5; 3])]);
+        let analysis = manganosite.bond_valence_sums(BOND_VALENCE_CUTOFF).unwrap();
+
+        assert_eq!(analysis.sites[0].suggested_oxidation_state, Some(2));
+        assert_eq!(analysis.sites[1].oxidation_state, Some(-2));
+
+        // the given oxidation state is kept, but the suggestion follows the distances
+        let wrong = phase(
+            4.445,
+            "Fm-3m",
+            225,
+            &[("Mn3+", [0.0; 3]), ("O2-", [0.5; 3])],
+        );
+        let site = &wrong.bond_valence_sums(BOND_VALENCE_CUTOFF).unwrap().sites[0];
+
+        assert_eq!(site.oxidation_state, Some(3));
+        assert_eq!(site.suggested_oxidation_state, Some(2));
+        assert!(site.deviation().unwrap() < -1.0);
+
+        // sites without parameters have no sum but do not fail the analysis
+        let unknown = phase(
+            4.445,
+            "P1",
+            1,
+            &[
+                ("Ne", [0.5, 0.5, 0.0]),
+                ("Mn", [0.5, 0.0, 0.0]),
+                ("O", [0.5; 3]),
+            ],
+        );
+        let analysis = unknown.bond_valence_sums(BOND_VALENCE_CUTOFF).unwrap();
+
+        assert_eq!(analysis.sites[0].oxidation_state, None);
+        assert_eq!(analysis.sites[0].bond_valence_sum, None);
+        assert!(analysis.sites[1].bond_valence_sum.is_some());
+        assert_eq!(analysis.sites[2].bond_valence_sum, None);
+    }
+
+    #[test]
+    fn test_partial_occupancy() {
+        let mut disordered = phase(
+            4.38,
+            "Fm-3m",
+            225,
+            &[("Fe2+", [0.0; 3]), ("Mn2+", [0.0; 3]), ("O2-", [0.5; 3])],
+        );
+        disordered.atoms[0].occupancy = 0.5;
+        disordered.atoms[1].occupancy = 0.5;
+
+        let analysis = disordered.bond_valence_sums(BOND_VALENCE_CUTOFF).unwrap();
+        let [fe, mn, o] = [0, 1, 2].map(|i| analysis.sites[i].bond_valence_sum.unwrap());
+
+        // each oxygen has six neighbors, half Fe and half Mn
+        assert_abs_diff_eq!(o, (fe + mn) / 2.0, epsilon = 1e-8);
+        assert!((o - 2.0).abs() < 0.3);
+
+        let deviations = [0, 1, 2].map(|i| analysis.sites[i].deviation().unwrap());
+        let expected =
+            ((0.5 * deviations[0].powi(2) + 0.5 * deviations[1].powi(2) + deviations[2].powi(2))
+                / 2.0)
+                .sqrt();
+
+        assert_abs_diff_eq!(
+            analysis.global_instability_index(),
+            expected,
+            epsilon = 1e-8
+        );
+    }
+}
@@ -1,4 +1,4 @@
-use cgmath::{vec3, Matrix3, Matrix4};
+use cgmath::{vec3, Matrix, Matrix3, Matrix4, SquareMatrix, Vector3};
 
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(default))]
@@ -13,6 +13,10 @@ pub struct Cell {
     pub volume: f64,
     pub space_group: String,
     pub space_group_number: u8,
+    /// The symmetry operations of a setting of the space group that is not tabulated, e.g.
+    /// `P2_1/n` for number 14, in fractional coordinates. Empty for the tabulated setting.
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Vec::is_empty"))]
+    pub setting_operations: Vec<Matrix4<f64>>,
 }
 
 impl Cell {
@@ -49,4 +53,188 @@ impl Cell {
 
         Matrix4::from(m)
     }
+
+    /// Returns the unit cell volume calculated from the lattice parameters.
+    pub fn calculate_volume(&self) -> f64 {
+        let (cos_alpha, cos_beta, cos_gamma) = self.cos_angles();
+
+        self.a
+            * self.b
+            * self.c
+            * (1.0 - cos_alpha.powi(2) - cos_beta.powi(2) - cos_gamma.powi(2)
+                + 2.0 * cos_alpha * cos_beta * cos_gamma)
+                .sqrt()
+    }
+
+    /// Returns the matrix converting fractional into Cartesian coordinates.
+    ///
+    /// `a` points along x and `b` lies in the xy-plane.
+    pub fn orthogonalization_matrix(&self) -> Matrix3<f64> {
+        let (cos_alpha, cos_beta, cos_gamma) = self.cos_angles();
+        let sin_gamma = self.gamma.to_radians().sin();
+
+        let c0 = vec3(self.a, 0.0, 0.0);
+        let c1 = vec3(self.b * cos_gamma, self.b * sin_gamma, 0.0);
+        let c2 = vec3(
+            self.c * cos_beta,
+            self.c * (cos_alpha - cos_beta * cos_gamma) / sin_gamma,
+            self.calculate_volume() / (self.a * self.b * sin_gamma),
+        );
+
+        Matrix3::from_cols(c0, c1, c2)
+    }
+
+    /// Returns the matrix converting Cartesian into fractional coordinates, or `None` if the
+    /// cell is degenerate.
+    pub fn fractionalization_matrix(&self) -> Option<Matrix3<f64>> {
+        self.orthogonalization_matrix()
+            .invert()
+            .filter(|m| m.determinant().is_finite())
+    }
+
+    /// Returns the metric tensor `G` with `G_ij = a_i · a_j`.
+    pub fn metric_tensor(&self) -> Matrix3<f64> {
+        let m = self.orthogonalization_matrix();
+
+        m.transpose() * m
+    }
+
+    /// Returns the reciprocal lattice parameters `a*`, `b*` and `c*`.
+    pub fn reciprocal_lengths(&self) -> Vector3<f64> {
+        let g_inv = self
+            .metric_tensor()
+            .invert()
+            .expect("Unit cell is degenerate");
+
+        vec3(g_inv.x.x.sqrt(), g_inv.y.y.sqrt(), g_inv.z.z.sqrt())
+    }
+
+    /// Creates a P1 cell from the three lattice vectors in Cartesian coordinates.
+    pub fn from_lattice_vectors(vectors: [Vector3<f64>; 3]) -> Self {
+        use cgmath::InnerSpace;
+
+        let [a, b, c] = vectors;
+        let angle = |u: Vector3<f64>, v: Vector3<f64>| u.angle(v).0.to_degrees();
+
+        let mut cell = Self {
+            a: a.magnitude(),
+            b: b.magnitude(),
+            c: c.magnitude(),
+            alpha: angle(b, c),
+            beta: angle(a, c),
+            gamma: angle(a, b),
+            space_group: "P1".to_string(),
+            space_group_number: 1,
+            ..Default::default()
+        };
+        cell.volume = cell.calculate_volume();
+
+        cell
+    }
+
+    fn cos_angles(&self) -> (f64, f64, f64) {
+        (
+            self.alpha.to_radians().cos(),
+            self.beta.to_radians().cos(),
+            self.gamma.to_radians().cos(),
+        )
+    }
+}
+
+#[cfg(feature = "symmetry")]
+impl Cell {
+    /// Returns the symmetry operations of the space group of the cell.
+    ///
+    /// `setting_operations` take precedence if present, then `space_group_number` unless it
+    /// is `0`, then `space_group`.
+    pub fn symmetry_operations(&self) -> Option<&Vec<cgmath::Matrix4<f64>>> {
+        use crate::SpaceGroupSymmetryOperations;
+
+        if !self.setting_operations.is_empty() {
+            return Some(&self.setting_operations);
+        }
+
+        match self.space_group_number {
+            0 => SpaceGroupSymmetryOperations::get(self.space_group.as_str()),
+            number => SpaceGroupSymmetryOperations::get(number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_cell {
+    use cgmath::{assert_abs_diff_eq, vec3};
+
+    use super::Cell;
+
+    fn monoclinic() -> Cell {
+        Cell {
+            a: 5.0,
+            b: 6.0,
+            c: 7.0,
+            alpha: 90.0,
+            beta: 110.0,
+            gamma: 90.0,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_volume() {
+        let cell = monoclinic();
+
+        assert_abs_diff_eq!(
+            cell.calculate_volume(),
+            5.0 * 6.0 * 7.0 * 110f64.to_radians().sin(),
+            epsilon = 1e-10
+        );
+    }
+
+    #[test]
+    fn test_orthogonalization_matrix() {
+        let cell = monoclinic();
+        let m = cell.orthogonalization_matrix();
+
+        assert_abs_diff_eq!(
+            m * vec3(1.0, 0.0, 0.0),
+            vec3(5.0, 0.0, 0.0),
+            epsilon = 1e-10
+        );
+
+        let g = cell.metric_tensor();
+
+        assert_abs_diff_eq!(g.x.x, 25.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(g.y.y, 36.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(g.z.z, 49.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(g.x.z, 35.0 * 110f64.to_radians().cos(), epsilon = 1e-10);
+
+        let f = cell.fractionalization_matrix().unwrap();
+
+        assert_abs_diff_eq!(f * m, cgmath::SquareMatrix::identity(), epsilon = 1e-10);
+
+        let flat = Cell {
+            gamma: 180.0,
+            ..monoclinic()
+        };
+
+        assert_eq!(flat.fractionalization_matrix(), None);
+
+        let reciprocal = cell.reciprocal_lengths();
+
+        assert_abs_diff_eq!(reciprocal.y, 1.0 / 6.0, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_from_lattice_vectors() {
+        let cell = monoclinic();
+        let m = cell.orthogonalization_matrix();
+        let new_cell = Cell::from_lattice_vectors([m.x, m.y, m.z]);
+
+        assert_abs_diff_eq!(new_cell.a, 5.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(new_cell.c, 7.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(new_cell.alpha, 90.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(new_cell.beta, 110.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(new_cell.volume, cell.calculate_volume(), epsilon = 1e-10);
+        assert_eq!(new_cell.space_group_number, 1);
+    }
 }
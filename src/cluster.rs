@@ -0,0 +1,286 @@
+use cgmath::{EuclideanSpace, InnerSpace, Matrix, SquareMatrix};
+
+use crate::{phase::rotation_part, Phase};
+
+/// A lattice plane `(hkl)` at a distance in Å from the center of a cluster.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Facet {
+    pub hkl: [i32; 3],
+    pub distance: f64,
+}
+
+/// Shape of a finite particle cut from a crystal.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClusterShape {
+    /// Sphere with the radius in Å.
+    Sphere { radius: f64 },
+    /// Convex polyhedron bounded by the given facets.
+    Polyhedron { facets: Vec<Facet> },
+}
+
+impl ClusterShape {
+    /// Returns a polyhedron bounded by the crystal forms `{hkl}`.
+    ///
+    /// Every facet is expanded to all symmetry equivalent planes using the point group of the
+    /// space group of the phase.
+    pub fn from_forms(phase: &Phase, forms: &[Facet]) -> Self {
+        let identity = vec![cgmath::Matrix4::identity()];
+        let symmetry_operations = phase.cell.symmetry_operations().unwrap_or(&identity);
+
+        let mut facets: Vec<Facet> = Vec::new();
+
+        for form in forms {
+            let hkl = cgmath::vec3(form.hkl[0] as f64, form.hkl[1] as f64, form.hkl[2] as f64);
+
+            for operation in symmetry_operations {
+                let new_hkl = rotation_part(operation).transpose() * hkl;
+                let new_hkl = [new_hkl.x, new_hkl.y, new_hkl.z].map(|x| x.round() as i32);
+
+                if !facets.iter().any(|f| f.hkl == new_hkl) {
+                    facets.push(Facet {
+                        hkl: new_hkl,
+                        distance: form.distance,
+                    });
+                }
+            }
+        }
+
+        ClusterShape::Polyhedron { facets }
+    }
+
+    /// Returns the radius of the smallest sphere around the center enclosing the shape or
+    /// `None` if the polyhedron is not closed.
+    fn bounding_radius(&self, planes: &[(cgmath::Vector3<f64>, f64)]) -> Option<f64> {
+        match self {
+            ClusterShape::Sphere { radius } => Some(*radius),
+            ClusterShape::Polyhedron { .. } => {
+                let mut radius: Option<f64> = None;
+
+                for (i, a) in planes.iter().enumerate() {
+                    for (j, b) in planes.iter().enumerate().skip(i + 1) {
+                        for c in planes.iter().skip(j + 1) {
+                            let normals = cgmath::Matrix3::from_cols(a.0, b.0, c.0).transpose();
+                            let Some(inverse) = normals.invert() else {
+                                continue;
+                            };
+
+                            let vertex = inverse * cgmath::vec3(a.1, b.1, c.1);
+
+                            if is_inside_planes(planes, vertex) {
+                                radius = Some(radius.unwrap_or(0.0).max(vertex.magnitude()));
+                            }
+                        }
+                    }
+                }
+
+                // the polyhedron is closed if no edge direction of the recession cone
+                // `n · d <= 0` remains
+                let is_open = planes.iter().any(|a| {
+                    planes.iter().any(|b| {
+                        let direction = a.0.cross(b.0);
+
+                        direction.magnitude() > 1e-8
+                            && (planes.iter().all(|c| c.0.dot(direction) <= 1e-8)
+                                || planes.iter().all(|c| c.0.dot(direction) >= -1e-8))
+                    })
+                });
+
+                match is_open {
+                    true => None,
+                    false => radius,
+                }
+            }
+        }
+    }
+}
+
+fn is_inside_planes(
+    planes: &[(cgmath::Vector3<f64>, f64)],
+    position: cgmath::Vector3<f64>,
+) -> bool {
+    planes
+        .iter()
+        .all(|(normal, distance)| normal.dot(position) <= distance + 1e-8)
+}
+
+/// An atom of a finite cluster in Cartesian coordinates.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClusterAtom {
+    pub label: String,
+    pub type_: String,
+    pub position: cgmath::Point3<f64>,
+    pub occupancy: f64,
+}
+
+/// A finite particle cut from a crystal.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Cluster {
+    pub atoms: Vec<ClusterAtom>,
+}
+
+impl Cluster {
+    /// Cuts a finite particle from the phase.
+    ///
+    /// The phase is expanded to P1 and all atoms of the lattice whose positions lie inside the
+    /// shape around `center`, given in fractional coordinates, are kept. The positions of the
+    /// atoms are Cartesian and relative to `center`.
+    /// Returns `None` if the shape is not closed or the cell is degenerate.
+    pub fn cut(phase: &Phase, shape: &ClusterShape, center: cgmath::Point3<f64>) -> Option<Self> {
+        let p1 = phase.expand_to_p1();
+        let orthogonalization_matrix = p1.cell.orthogonalization_matrix();
+        let fractionalization_matrix = p1.cell.fractionalization_matrix()?;
+
+        let planes = match shape {
+            ClusterShape::Sphere { .. } => Vec::new(),
+            ClusterShape::Polyhedron { facets } => facets
+                .iter()
+                .map(|f| {
+                    let hkl = cgmath::vec3(f.hkl[0] as f64, f.hkl[1] as f64, f.hkl[2] as f64);
+                    let normal = (fractionalization_matrix.transpose() * hkl).normalize();
+
+                    (normal, f.distance)
+                })
+                .collect::<Vec<_>>(),
+        };
+
+        let radius = shape.bounding_radius(&planes)?;
+
+        let reciprocal_lengths = p1.cell.reciprocal_lengths();
+        let ranges = [
+            reciprocal_lengths.x,
+            reciprocal_lengths.y,
+            reciprocal_lengths.z,
+        ]
+        .map(|l| (radius * l).ceil() as i32 + 1);
+        let origin = center.map(|x| x.floor());
+
+        let mut atoms = Vec::new();
+
+        for u in -ranges[0]..=ranges[0] {
+            for v in -ranges[1]..=ranges[1] {
+                for w in -ranges[2]..=ranges[2] {
+                    let translation = origin.to_vec() + cgmath::vec3(u as f64, v as f64, w as f64);
+
+                    for atom in p1.atoms.iter() {
+                        let fractional = cgmath::vec3(atom.x, atom.y, atom.z) + translation;
+                        let position = orthogonalization_matrix * (fractional - center.to_vec());
+
+                        let inside = match shape {
+                            ClusterShape::Sphere { radius } => position.magnitude() <= *radius,
+                            ClusterShape::Polyhedron { .. } => is_inside_planes(&planes, position),
+                        };
+
+                        if inside {
+                            atoms.push(ClusterAtom {
+                                label: atom.label.clone(),
+                                type_: atom.type_.clone(),
+                                position: cgmath::Point3::from_vec(position),
+                                occupancy: atom.occupancy,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        Some(Self { atoms })
+    }
+}
+
+#[cfg(test)]
+mod test_cluster {
+    use super::{Cluster, ClusterShape, Facet};
+    use crate::{Atom, Atoms, Cell, Phase};
+
+    fn simple_cubic() -> Phase {
+        Phase {
+            cell: Cell {
+                a: 2.0,
+                b: 2.0,
+                c: 2.0,
+                alpha: 90.0,
+                beta: 90.0,
+                gamma: 90.0,
+                space_group_number: 221,
+                ..Default::default()
+            },
+            atoms: Atoms(vec![Atom {
+                label: "Po1".to_string(),
+                type_: "Po".to_string(),
+                occupancy: 1.0,
+                ..Default::default()
+            }]),
+        }
+    }
+
+    #[test]
+    fn test_sphere() {
+        let center = cgmath::Point3::new(0.0, 0.0, 0.0);
+
+        let cluster = Cluster::cut(
+            &simple_cubic(),
+            &ClusterShape::Sphere { radius: 2.1 },
+            center,
+        )
+        .unwrap();
+
+        // center, 6 face neighbours
+        assert_eq!(cluster.atoms.len(), 7);
+
+        let cluster = Cluster::cut(
+            &simple_cubic(),
+            &ClusterShape::Sphere { radius: 2.9 },
+            center,
+        )
+        .unwrap();
+
+        // and 12 edge neighbours
+        assert_eq!(cluster.atoms.len(), 19);
+    }
+
+    #[test]
+    fn test_cube() {
+        let phase = simple_cubic();
+        let shape = ClusterShape::from_forms(
+            &phase,
+            &[Facet {
+                hkl: [1, 0, 0],
+                distance: 3.0,
+            }],
+        );
+
+        let ClusterShape::Polyhedron { facets } = &shape else {
+            unreachable!()
+        };
+
+        assert_eq!(facets.len(), 6);
+
+        let cluster = Cluster::cut(&phase, &shape, cgmath::Point3::new(0.0, 0.0, 0.0)).unwrap();
+
+        assert_eq!(cluster.atoms.len(), 27);
+    }
+
+    #[test]
+    fn test_open_polyhedron() {
+        let shape = ClusterShape::Polyhedron {
+            facets: vec![
+                Facet {
+                    hkl: [1, 0, 0],
+                    distance: 3.0,
+                },
+                Facet {
+                    hkl: [-1, 0, 0],
+                    distance: 3.0,
+                },
+            ],
+        };
+
+        assert!(
+            Cluster::cut(&simple_cubic(), &shape, cgmath::Point3::new(0.0, 0.0, 0.0)).is_none()
+        );
+    }
+}
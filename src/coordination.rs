@@ -0,0 +1,729 @@
+use std::f64::consts::PI;
+
+use cgmath::{vec3, InnerSpace, Matrix3, Point3, Vector3};
+
+use crate::{DataTableError, MissingRadius, NeighborList, Phase, COVALENT_RADII};
+
+/// How the neighbors of a site are selected.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NeighborCriterion {
+    /// All atoms within the distance in Å.
+    Cutoff(f64),
+    /// Atoms closer than the sum of their covalent radii plus the tolerance in Å.
+    RadiusSum { tolerance: f64 },
+    /// Atoms sharing a face of the Voronoi cell of the site whose solid angle is at least
+    /// `min_weight` times that of the largest face.
+    SolidAngle { min_weight: f64 },
+}
+
+/// A neighbor of a site, given as an atom of the phase expanded to P1.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Neighbor {
+    /// The index of the atom in [`Phase::expand_to_p1`].
+    pub index: usize,
+    pub label: String,
+    pub type_: String,
+    /// The lattice translation from the atom in the unit cell to the neighbor.
+    pub offset: [i32; 3],
+    /// The Cartesian vector from the site to the neighbor in Å.
+    pub vector: Vector3<f64>,
+    pub distance: f64,
+    /// The solid angle of the shared Voronoi face relative to the largest face, or `1.0` for
+    /// criteria other than [`NeighborCriterion::SolidAngle`].
+    pub weight: f64,
+}
+
+/// An ideal coordination polyhedron.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Polyhedron {
+    Linear,
+    Bent,
+    TrigonalPlanar,
+    TrigonalPyramid,
+    TShaped,
+    Tetrahedron,
+    SquarePlanar,
+    Seesaw,
+    TrigonalBipyramid,
+    SquarePyramid,
+    Octahedron,
+    TrigonalPrism,
+    PentagonalBipyramid,
+    Cube,
+    SquareAntiprism,
+    Cuboctahedron,
+    Anticuboctahedron,
+    Icosahedron,
+}
+
+impl Polyhedron {
+    pub fn all() -> &'static [Polyhedron] {
+        use Polyhedron::*;
+
+        &[
+            Linear,
+            Bent,
+            TrigonalPlanar,
+            TrigonalPyramid,
+            TShaped,
+            Tetrahedron,
+            SquarePlanar,
+            Seesaw,
+            TrigonalBipyramid,
+            SquarePyramid,
+            Octahedron,
+            TrigonalPrism,
+            PentagonalBipyramid,
+            Cube,
+            SquareAntiprism,
+            Cuboctahedron,
+            Anticuboctahedron,
+            Icosahedron,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Polyhedron::Linear => "linear",
+            Polyhedron::Bent => "bent",
+            Polyhedron::TrigonalPlanar => "trigonal planar",
+            Polyhedron::TrigonalPyramid => "trigonal pyramid",
+            Polyhedron::TShaped => "T-shaped",
+            Polyhedron::Tetrahedron => "tetrahedron",
+            Polyhedron::SquarePlanar => "square planar",
+            Polyhedron::Seesaw => "seesaw",
+            Polyhedron::TrigonalBipyramid => "trigonal bipyramid",
+            Polyhedron::SquarePyramid => "square pyramid",
+            Polyhedron::Octahedron => "octahedron",
+            Polyhedron::TrigonalPrism => "trigonal prism",
+            Polyhedron::PentagonalBipyramid => "pentagonal bipyramid",
+            Polyhedron::Cube => "cube",
+            Polyhedron::SquareAntiprism => "square antiprism",
+            Polyhedron::Cuboctahedron => "cuboctahedron",
+            Polyhedron::Anticuboctahedron => "anticuboctahedron",
+            Polyhedron::Icosahedron => "icosahedron",
+        }
+    }
+
+    /// Returns the unit vectors from the center to the vertices of the ideal polyhedron.
+    pub fn vertices(&self) -> Vec<Vector3<f64>> {
+        let polygon = |n: usize, radius: f64, z: f64, phase: f64| {
+            (0..n)
+                .map(|k| {
+                    let angle = phase + 2.0 * PI * k as f64 / n as f64;
+                    vec3(radius * angle.cos(), radius * angle.sin(), z)
+                })
+                .collect::<Vec<_>>()
+        };
+        let axial = vec![vec3(0.0, 0.0, 1.0), vec3(0.0, 0.0, -1.0)];
+        let octahedron = [
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            vec3(-1.0, 0.0, 0.0),
+            vec3(0.0, -1.0, 0.0),
+            vec3(0.0, 0.0, 1.0),
+            vec3(0.0, 0.0, -1.0),
+        ];
+        let tetrahedron = [
+            vec3(1.0, 1.0, 1.0),
+            vec3(1.0, -1.0, -1.0),
+            vec3(-1.0, 1.0, -1.0),
+            vec3(-1.0, -1.0, 1.0),
+        ];
+        // the bicupolas of fcc and hcp: a hexagon between two triangles
+        let bicupola = |phase: f64| {
+            let h = (2.0f64 / 3.0).sqrt();
+            let r = (1.0f64 / 3.0).sqrt();
+
+            [
+                polygon(6, 1.0, 0.0, 0.0),
+                polygon(3, r, h, PI / 6.0),
+                polygon(3, r, -h, PI / 6.0 + phase),
+            ]
+            .concat()
+        };
+
+        let vertices = match self {
+            Polyhedron::Linear => axial,
+            Polyhedron::Bent => polygon(3, 1.0, 0.0, 0.0)[..2].to_vec(),
+            Polyhedron::TrigonalPlanar => polygon(3, 1.0, 0.0, 0.0),
+            Polyhedron::TrigonalPyramid => tetrahedron[..3].to_vec(),
+            Polyhedron::TShaped => octahedron[..3].to_vec(),
+            Polyhedron::Tetrahedron => tetrahedron.to_vec(),
+            Polyhedron::SquarePlanar => octahedron[..4].to_vec(),
+            Polyhedron::Seesaw => [axial, polygon(3, 1.0, 0.0, 0.0)[..2].to_vec()].concat(),
+            Polyhedron::TrigonalBipyramid => [axial, polygon(3, 1.0, 0.0, 0.0)].concat(),
+            Polyhedron::SquarePyramid => octahedron[..5].to_vec(),
+            Polyhedron::Octahedron => octahedron.to_vec(),
+            Polyhedron::TrigonalPrism => {
+                // all edges of equal length
+                let h = 3f64.sqrt() / 2.0;
+                [polygon(3, 1.0, h, 0.0), polygon(3, 1.0, -h, 0.0)].concat()
+            }
+            Polyhedron::PentagonalBipyramid => [axial, polygon(5, 1.0, 0.0, 0.0)].concat(),
+            Polyhedron::Cube => [polygon(4, 1.0, 1.0, 0.0), polygon(4, 1.0, -1.0, 0.0)].concat(),
+            Polyhedron::SquareAntiprism => {
+                // all edges of equal length
+                let h = 2f64.powf(0.25) / 2.0;
+                [polygon(4, 1.0, h, 0.0), polygon(4, 1.0, -h, PI / 4.0)].concat()
+            }
+            Polyhedron::Cuboctahedron => bicupola(PI / 3.0),
+            Polyhedron::Anticuboctahedron => bicupola(0.0),
+            Polyhedron::Icosahedron => {
+                let phi = (1.0 + 5f64.sqrt()) / 2.0;
+
+                [1.0, -1.0]
+                    .into_iter()
+                    .flat_map(|s| [1.0, -1.0].map(|t| (s, t * phi)))
+                    .flat_map(|(s, t)| [vec3(0.0, s, t), vec3(s, t, 0.0), vec3(t, 0.0, s)])
+                    .collect()
+            }
+        };
+
+        vertices.into_iter().map(|v| v.normalize()).collect()
+    }
+}
+
+impl std::fmt::Display for Polyhedron {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The neighbors of a site of a phase.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoordinationEnvironment {
+    /// The index of the site in the atoms of the phase.
+    pub site: usize,
+    /// The neighbors sorted by distance.
+    pub neighbors: Vec<Neighbor>,
+}
+
+impl CoordinationEnvironment {
+    pub fn coordination_number(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    /// Returns the mean distance to the neighbors in Å.
+    pub fn mean_bond_length(&self) -> f64 {
+        self.neighbors.iter().map(|n| n.distance).sum::<f64>() / self.neighbors.len() as f64
+    }
+
+    /// Returns the ideal polyhedron with the same number of vertices whose bond angles are
+    /// closest to the observed ones, or `None` if no polyhedron has that many vertices.
+    pub fn polyhedron(&self) -> Option<Polyhedron> {
+        let angles = sorted_angles(&self.directions());
+
+        Polyhedron::all()
+            .iter()
+            .map(|polyhedron| (polyhedron, sorted_angles(&polyhedron.vertices())))
+            .filter(|(_, ideal)| ideal.len() == angles.len() && !ideal.is_empty())
+            .map(|(polyhedron, ideal)| {
+                let deviation: f64 = ideal
+                    .iter()
+                    .zip(&angles)
+                    .map(|(a, b)| (a - b).powi(2))
+                    .sum();
+
+                (*polyhedron, deviation)
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(polyhedron, _)| polyhedron)
+    }
+
+    /// Returns the quadratic elongation `<λ> = Σ (l_i / l_0)² / n` of Robinson et al. (1971),
+    /// where `l_0` is the center-to-vertex distance of the regular polyhedron of the same
+    /// volume. Only defined for tetrahedra and octahedra.
+    pub fn quadratic_elongation(&self) -> Option<f64> {
+        let vectors = self.vectors();
+
+        let l0 = match self.polyhedron()? {
+            Polyhedron::Tetrahedron => {
+                let volume = tetrahedron_volume(vectors[0], vectors[1], vectors[2], vectors[3]);
+                (volume * 9.0 * 3f64.sqrt() / 8.0).cbrt()
+            }
+            Polyhedron::Octahedron => (octahedron_volume(&vectors) * 3.0 / 4.0).cbrt(),
+            _ => return None,
+        };
+
+        Some(
+            self.neighbors
+                .iter()
+                .map(|n| (n.distance / l0).powi(2))
+                .sum::<f64>()
+                / self.neighbors.len() as f64,
+        )
+    }
+
+    /// Returns the bond angle variance `σ² = Σ (θ_i - θ_0)² / (m - 1)` in deg² of Robinson
+    /// et al. (1971) over the `m` angles between neighbors sharing an edge. Only defined for
+    /// tetrahedra and octahedra.
+    pub fn bond_angle_variance(&self) -> Option<f64> {
+        let directions = self.directions();
+
+        let (ideal_angle, angles) = match self.polyhedron()? {
+            Polyhedron::Tetrahedron => (109.4712206, sorted_angles(&directions)),
+            // the 12 smallest angles skip the three trans angles
+            Polyhedron::Octahedron => (90.0, sorted_angles(&directions)[..12].to_vec()),
+            _ => return None,
+        };
+
+        Some(
+            angles
+                .iter()
+                .map(|angle| (angle - ideal_angle).powi(2))
+                .sum::<f64>()
+                / (angles.len() - 1) as f64,
+        )
+    }
+
+    /// Returns the distortion index `D = Σ |l_i - l_av| / (n l_av)` of Baur (1974).
+    pub fn distortion_index(&self) -> f64 {
+        let mean = self.mean_bond_length();
+
+        self.neighbors
+            .iter()
+            .map(|n| (n.distance - mean).abs())
+            .sum::<f64>()
+            / (self.neighbors.len() as f64 * mean)
+    }
+
+    fn vectors(&self) -> Vec<Vector3<f64>> {
+        self.neighbors.iter().map(|n| n.vector).collect()
+    }
+
+    fn directions(&self) -> Vec<Vector3<f64>> {
+        self.neighbors
+            .iter()
+            .map(|n| n.vector.normalize())
+            .collect()
+    }
+}
+
+impl Phase {
+    /// Returns the coordination environment of every atom of the phase. Neighbors are searched
+    /// among the atoms of the phase expanded to P1 and all their periodic images.
+    ///
+    /// Fails for a missing covalent radius or a search cutoff rejected by
+    /// [`NeighborList::new`].
+    pub fn coordination_environments(
+        &self,
+        criterion: NeighborCriterion,
+    ) -> Result<Vec<CoordinationEnvironment>, DataTableError> {
+        let search = NeighborSearch::new(self, criterion)?;
+
+        (0..self.atoms.len())
+            .map(|site| search.environment(self, site))
+            .collect()
+    }
+
+    /// Returns the coordination environment of the atom with the index `site`, see
+    /// [`Self::coordination_environments`].
+    pub fn coordination_environment(
+        &self,
+        site: usize,
+        criterion: NeighborCriterion,
+    ) -> Result<CoordinationEnvironment, DataTableError> {
+        NeighborSearch::new(self, criterion)?.environment(self, site)
+    }
+}
+
+/// Distances below this in Å are considered to be the site itself.
+const SAME_SITE_DISTANCE: f64 = 1e-4;
+
+/// The atoms of a phase expanded to P1 with the neighbor list for a criterion.
+struct NeighborSearch {
+    p1: Phase,
+    list: NeighborList,
+    criterion: NeighborCriterion,
+    /// The covalent radii of the atoms for [`NeighborCriterion::RadiusSum`].
+    radii: Vec<f64>,
+}
+
+impl NeighborSearch {
+    fn new(phase: &Phase, criterion: NeighborCriterion) -> Result<Self, DataTableError> {
+        let p1 = phase.expand_to_p1();
+
+        let radii = match criterion {
+            NeighborCriterion::RadiusSum { .. } => p1
+                .atoms
+                .iter()
+                .map(|atom| covalent_radius(&atom.type_))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => Vec::new(),
+        };
+        let max_radius = radii.iter().copied().fold(0.0, f64::max);
+
+        let cutoff = match criterion {
+            NeighborCriterion::Cutoff(cutoff) => cutoff,
+            NeighborCriterion::RadiusSum { tolerance } => 2.0 * max_radius + tolerance,
+            // bins of about the mean distance of the atoms
+            NeighborCriterion::SolidAngle { .. } => {
+                (p1.cell.calculate_volume() / p1.atoms.len().max(1) as f64).cbrt()
+            }
+        };
+
+        Ok(Self {
+            list: NeighborList::new(&p1, cutoff).map_err(|e| DataTableError::new(e.to_string()))?,
+            p1,
+            criterion,
+            radii,
+        })
+    }
+
+    fn environment(
+        &self,
+        phase: &Phase,
+        site: usize,
+    ) -> Result<CoordinationEnvironment, DataTableError> {
+        let atom = &phase.atoms[site];
+        let center = Point3::new(atom.x, atom.y, atom.z);
+
+        let neighbors = match self.criterion {
+            NeighborCriterion::Cutoff(cutoff) => self.neighbors_within(center, cutoff),
+            NeighborCriterion::RadiusSum { tolerance } => {
+                let site_radius = covalent_radius(&atom.type_)?;
+                let max_radius = self.radii.iter().copied().fold(0.0, f64::max);
+
+                self.neighbors_within(center, site_radius + max_radius + tolerance)
+                    .into_iter()
+                    .filter(|n| n.distance <= site_radius + self.radii[n.index] + tolerance)
+                    .collect()
+            }
+            NeighborCriterion::SolidAngle { min_weight } => self
+                .voronoi_neighbors(center)
+                .into_iter()
+                .filter(|n| n.weight >= min_weight)
+                .collect(),
+        };
+
+        Ok(CoordinationEnvironment { site, neighbors })
+    }
+
+    /// Returns all atoms within `cutoff` of a fractional position sorted by distance.
+    fn neighbors_within(&self, center: Point3<f64>, cutoff: f64) -> Vec<Neighbor> {
+        // the cutoff of the list is valid, so radius sums only fail by being negative
+        self.list
+            .neighbors_of_position(center, cutoff.max(0.0))
+            .unwrap_or_default()
+            .into_iter()
+            .map(|neighbor| {
+                let atom = &self.p1.atoms[neighbor.index];
+
+                Neighbor {
+                    index: neighbor.index,
+                    label: atom.label.clone(),
+                    type_: atom.type_.clone(),
+                    offset: neighbor.offset,
+                    vector: neighbor.vector,
+                    distance: neighbor.distance,
+                    weight: 1.0,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the atoms sharing a face with the Voronoi cell of a fractional position,
+    /// weighted by the solid angle of the face relative to the largest one.
+    fn voronoi_neighbors(&self, center: Point3<f64>) -> Vec<Neighbor> {
+        // the search ends at the latest with an image of the atom at the center
+        let mut radius = self.list.cutoff().max(1.0);
+        let nearest = loop {
+            if let Some(nearest) = self.neighbors_within(center, radius).first() {
+                break nearest.distance;
+            }
+
+            radius *= 2.0;
+        };
+        let mut cutoff = 2.5 * nearest;
+
+        loop {
+            let candidates = self.neighbors_within(center, cutoff);
+            let faces = candidates
+                .iter()
+                .map(|candidate| voronoi_face(candidate.vector, &candidates, cutoff))
+                .collect::<Vec<_>>();
+
+            // faces are complete if no vertex is closer to a plane beyond the cutoff
+            let max_vertex_distance = faces
+                .iter()
+                .flatten()
+                .map(|vertex| vertex.magnitude())
+                .fold(0.0, f64::max);
+
+            if 2.0 * max_vertex_distance > cutoff {
+                cutoff = 2.0 * max_vertex_distance * 1.01;
+                continue;
+            }
+
+            let solid_angles = faces
+                .iter()
+                .map(|face| polygon_solid_angle(face))
+                .collect::<Vec<_>>();
+            let max_solid_angle = solid_angles.iter().copied().fold(0.0, f64::max);
+
+            return candidates
+                .into_iter()
+                .zip(solid_angles)
+                .filter(|(_, solid_angle)| *solid_angle > 0.0)
+                .map(|(neighbor, solid_angle)| Neighbor {
+                    weight: solid_angle / max_solid_angle,
+                    ..neighbor
+                })
+                .collect();
+        }
+    }
+}
+
+fn covalent_radius(type_: &str) -> Result<f64, DataTableError> {
+    COVALENT_RADII
+        .get_or(type_, MissingRadius::Error)
+        .map(f64::from)
+}
+
+/// Returns the vertices of the face of the Voronoi cell around the origin belonging to the
+/// neighbor at `vector`, or an empty polygon if the neighbor shares no face.
+fn voronoi_face(vector: Vector3<f64>, candidates: &[Neighbor], size: f64) -> Vec<Vector3<f64>> {
+    let normal = vector.normalize();
+    let helper = match normal.x.abs() < 0.9 {
+        true => vec3(1.0, 0.0, 0.0),
+        false => vec3(0.0, 1.0, 0.0),
+    };
+    let u = normal.cross(helper).normalize() * size;
+    let v = normal.cross(u);
+    let middle = vector / 2.0;
+
+    let mut polygon = vec![
+        middle + u + v,
+        middle - u + v,
+        middle - u - v,
+        middle + u - v,
+    ];
+
+    for other in candidates {
+        if (other.vector - vector).magnitude() < SAME_SITE_DISTANCE {
+            continue;
+        }
+
+        polygon = clip_polygon(&polygon, other.vector, other.vector.magnitude2() / 2.0);
+
+        if polygon.is_empty() {
+            break;
+        }
+    }
+
+    polygon
+}
+
+/// Clips a convex polygon to the half-space `normal · x <= d`.
+fn clip_polygon(polygon: &[Vector3<f64>], normal: Vector3<f64>, d: f64) -> Vec<Vector3<f64>> {
+    let mut clipped = Vec::with_capacity(polygon.len() + 1);
+
+    for (i, p) in polygon.iter().enumerate() {
+        let q = polygon[(i + 1) % polygon.len()];
+        let sp = normal.dot(*p) - d;
+        let sq = normal.dot(q) - d;
+
+        if sp <= 0.0 {
+            clipped.push(*p);
+        }
+
+        if (sp < 0.0 && sq > 0.0) || (sp > 0.0 && sq < 0.0) {
+            clipped.push(p + (q - p) * (sp / (sp - sq)));
+        }
+    }
+
+    clipped
+}
+
+/// Returns the solid angle of a planar polygon seen from the origin using the formula of
+/// Van Oosterom & Strackee (1983) for each triangle of a fan.
+fn polygon_solid_angle(polygon: &[Vector3<f64>]) -> f64 {
+    if polygon.len() < 3 {
+        return 0.0;
+    }
+
+    let a = polygon[0];
+
+    polygon[1..]
+        .windows(2)
+        .map(|edge| {
+            let (b, c) = (edge[0], edge[1]);
+            let numerator = a.dot(b.cross(c)).abs();
+            let denominator = a.magnitude() * b.magnitude() * c.magnitude()
+                + a.dot(b) * c.magnitude()
+                + a.dot(c) * b.magnitude()
+                + b.dot(c) * a.magnitude();
+
+            2.0 * numerator.atan2(denominator)
+        })
+        .sum()
+}
+
+/// Returns the angles in degrees between all pairs of directions in ascending order.
+fn sorted_angles(directions: &[Vector3<f64>]) -> Vec<f64> {
+    let mut angles = Vec::new();
+
+    for (i, a) in directions.iter().enumerate() {
+        for b in &directions[i + 1..] {
+            angles.push(a.dot(*b).clamp(-1.0, 1.0).acos().to_degrees());
+        }
+    }
+
+    angles.sort_by(f64::total_cmp);
+
+    angles
+}
+
+fn tetrahedron_volume(a: Vector3<f64>, b: Vector3<f64>, c: Vector3<f64>, d: Vector3<f64>) -> f64 {
+    use cgmath::SquareMatrix;
+
+    Matrix3::from_cols(b - a, c - a, d - a).determinant().abs() / 6.0
+}
+
+/// Returns the volume of an octahedron from its six vertices. The faces are the eight
+/// triangles taking one vertex of each pair of opposite vertices.
+fn octahedron_volume(vertices: &[Vector3<f64>]) -> f64 {
+    let mut pairs: Vec<(Vector3<f64>, Vector3<f64>)> = Vec::new();
+    let mut used = [false; 6];
+
+    for i in 0..6 {
+        if used[i] {
+            continue;
+        }
+
+        let opposite = (0..6)
+            .filter(|&j| j != i && !used[j])
+            .max_by(|&j, &k| {
+                let distance = |l: usize| (vertices[l] - vertices[i]).magnitude();
+                distance(j).total_cmp(&distance(k))
+            })
+            .expect("Octahedron has six vertices");
+
+        used[i] = true;
+        used[opposite] = true;
+        pairs.push((vertices[i], vertices[opposite]));
+    }
+
+    let centroid = vertices
+        .iter()
+        .fold(Vector3::new(0.0, 0.0, 0.0), |s, v| s + v)
+        / 6.0;
+
+    (0..8)
+        .map(|k: usize| {
+            let [a, b, c] = [0, 1, 2].map(|p| match (k >> p) & 1 {
+                0 => pairs[p].0,
+                _ => pairs[p].1,
+            });
+
+            tetrahedron_volume(centroid, a, b, c)
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod test_coordination {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::{NeighborCriterion, Polyhedron};
+    use crate::test_util::phase;
+
+    #[test]
+    fn test_ideal_polyhedra() {
+        for polyhedron in Polyhedron::all() {
+            let vertices = polyhedron.vertices();
+            let angles = super::sorted_angles(&vertices);
+
+            // every vertex is distinct and no ideal shape is mistaken for another
+            assert!(angles[0] > 40.0, "{polyhedron}");
+            assert!(Polyhedron::all()
+                .iter()
+                .filter(|other| other != &polyhedron)
+                .all(|other| super::sorted_angles(&other.vertices()) != angles));
+        }
+    }
+
+    #[test]
+    fn test_rock_salt() {
+        let rock_salt = phase(5.64, "Fm-3m", 225, &[("Na", [0.0; 3]), ("Cl", [0.5; 3])]);
+
+        for criterion in [
+            NeighborCriterion::Cutoff(3.0),
+            NeighborCriterion::RadiusSum { tolerance: 0.5 },
+            NeighborCriterion::SolidAngle { min_weight: 0.5 },
+        ] {
+            let environments = rock_salt.coordination_environments(criterion).unwrap();
+
+            for environment in environments {
+                assert_eq!(environment.coordination_number(), 6, "{criterion:?}");
+                assert_eq!(environment.polyhedron(), Some(Polyhedron::Octahedron));
+                assert_abs_diff_eq!(environment.mean_bond_length(), 2.82, epsilon = 1e-8);
+                assert_abs_diff_eq!(
+                    environment.quadratic_elongation().unwrap(),
+                    1.0,
+                    epsilon = 1e-8
+                );
+                assert_abs_diff_eq!(
+                    environment.bond_angle_variance().unwrap(),
+                    0.0,
+                    epsilon = 1e-8
+                );
+                assert_abs_diff_eq!(environment.distortion_index(), 0.0, epsilon = 1e-8);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zinc_blende() {
+        let zinc_blende = phase(5.41, "F-43m", 216, &[("Zn", [0.0; 3]), ("S", [0.25; 3])]);
+        let environment = zinc_blende
+            .coordination_environment(0, NeighborCriterion::SolidAngle { min_weight: 0.5 })
+            .unwrap();
+
+        assert_eq!(environment.coordination_number(), 4);
+        assert_eq!(environment.polyhedron(), Some(Polyhedron::Tetrahedron));
+        assert!(environment.neighbors.iter().all(|n| n.type_ == "S"));
+        assert_abs_diff_eq!(
+            environment.quadratic_elongation().unwrap(),
+            1.0,
+            epsilon = 1e-8
+        );
+    }
+
+    #[test]
+    fn test_distorted_octahedron() {
+        let mut phase = phase(
+            4.0,
+            "P1",
+            1,
+            &[
+                ("Ti", [0.0; 3]),
+                ("O", [0.5, 0.0, 0.0]),
+                ("O", [0.0, 0.5, 0.0]),
+                ("O", [0.0, 0.0, 0.55]),
+            ],
+        );
+        phase.cell.c = 4.4;
+
+        let environment = phase
+            .coordination_environment(0, NeighborCriterion::Cutoff(2.5))
+            .unwrap();
+
+        assert_eq!(environment.coordination_number(), 6);
+        assert_eq!(environment.polyhedron(), Some(Polyhedron::Octahedron));
+        assert!(environment.quadratic_elongation().unwrap() > 1.0);
+        assert!(environment.bond_angle_variance().unwrap() > 0.0);
+
+        // bonds of 2.0 Å in a and b, 2.42 Å and 1.98 Å along c
+        let lengths = [2.0, 2.0, 2.0, 2.0, 2.42, 1.98];
+        let mean = lengths.iter().sum::<f64>() / 6.0;
+        let expected = lengths.iter().map(|l| (l - mean).abs()).sum::<f64>() / (6.0 * mean);
+
+        assert_abs_diff_eq!(environment.distortion_index(), expected, epsilon = 1e-8);
+    }
+}
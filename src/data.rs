@@ -1,27 +1,29 @@
-use std::{collections::HashMap, sync::LazyLock};
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex, OnceLock, RwLock},
+};
+
+use crate::{AtomType, Element, ToElement};
 
 pub const VAN_DER_WAALS_RADII_RAW: &[u8] =
     include_bytes!("../assets/data/van-der-Waals-radii.toml");
 
-pub struct VanDerWaalsRadii(LazyLock<HashMap<String, f32>>);
+pub struct VanDerWaalsRadii(RadiusTable);
 
 impl VanDerWaalsRadii {
     pub const fn load() -> Self {
-        Self(LazyLock::new(|| {
-            let data = std::str::from_utf8(VAN_DER_WAALS_RADII_RAW)
-                .expect("Failed to convert van_der_Waals data to str");
-            toml::from_str(data).expect("Failed to parse van_der_Waals data form toml")
-        }))
+        Self(RadiusTable::new(
+            "van der Waals radii",
+            VAN_DER_WAALS_RADII_RAW,
+        ))
     }
+}
 
-    pub fn get(&self, atom_type: &str) -> &f32 {
-        self.0.get(atom_type).unwrap_or_else(|| {
-            log::warn!(
-                "Unknown atom type: {}. Using default radius of 1.0.",
-                atom_type
-            );
-            &1.0
-        })
+impl std::ops::Deref for VanDerWaalsRadii {
+    type Target = RadiusTable;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
     }
 }
 
@@ -31,36 +33,935 @@ pub static VAN_DER_WAALS_RADII: VanDerWaalsRadii = VanDerWaalsRadii::load();
 #[test]
 fn test_van_der_waals_radii() {
     assert_eq!(VAN_DER_WAALS_RADII.get("H"), &1.2);
+    assert_eq!(
+        VAN_DER_WAALS_RADII.get("Ow"),
+        VAN_DER_WAALS_RADII.get(Element::O)
+    );
+    assert_eq!(VAN_DER_WAALS_RADII.get("Xx"), &1.0);
+    assert_eq!(VAN_DER_WAALS_RADII.get("La"), &2.4);
+    assert_eq!(VAN_DER_WAALS_RADII.get("Ce"), &2.35);
+    assert_eq!(VAN_DER_WAALS_RADII.get_value("La"), 2.4);
 }
 
 pub const COVALENT_RADII_RAW: &[u8] = include_bytes!("../assets/data/covalent-radii.toml");
 
-pub struct CovalentRadii(LazyLock<HashMap<String, f32>>);
+pub struct CovalentRadii(RadiusTable);
 
 impl CovalentRadii {
     pub const fn load() -> Self {
-        Self(LazyLock::new(|| {
-            let data = std::str::from_utf8(COVALENT_RADII_RAW)
-                .expect("Failed to convert covalent radii data to str");
-            toml::from_str(data).expect("Failed to parse covalent radii data form toml")
-        }))
+        Self(RadiusTable::new("covalent radii", COVALENT_RADII_RAW))
     }
+}
+
+impl std::ops::Deref for CovalentRadii {
+    type Target = RadiusTable;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+pub static COVALENT_RADII: CovalentRadii = CovalentRadii::load();
 
-    pub fn get(&self, atom_type: &str) -> &f32 {
-        self.0.get(atom_type).unwrap_or_else(|| {
+#[cfg(test)]
+#[test]
+fn test_covalent_radii() {
+    assert_eq!(COVALENT_RADII.get("H"), &0.31);
+    assert_eq!(COVALENT_RADII.get("Fe2+"), COVALENT_RADII.get("Fe"));
+    assert_eq!(COVALENT_RADII.get("D"), &0.31);
+}
+
+/// What a radius lookup returns for atom types without a radius.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MissingRadius {
+    /// Use the value without notice.
+    Default(f32),
+    /// Use the value and log a warning.
+    Warn(f32),
+    /// Return an error.
+    Error,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DataTableError(String);
+
+impl DataTableError {
+    pub fn new(s: impl AsRef<str>) -> Self {
+        Self(s.as_ref().to_string())
+    }
+}
+
+impl std::fmt::Display for DataTableError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DataTableError {}
+
+/// A table of radii by element, initialized from the bundled data and extensible at runtime.
+///
+/// Overrides apply to all later lookups in the program.
+pub struct RadiusTable {
+    name: &'static str,
+    raw: &'static [u8],
+    radii: OnceLock<RwLock<HashMap<Element, f32>>>,
+}
+
+impl RadiusTable {
+    pub const fn new(name: &'static str, raw: &'static [u8]) -> Self {
+        Self {
+            name,
+            raw,
+            radii: OnceLock::new(),
+        }
+    }
+
+    /// Returns the radius of an element or atom type like `Fe2+` or `Ow`, or `1.0` with a
+    /// warning if it is unknown.
+    pub fn get(&self, atom_type: impl ToElement + std::fmt::Debug) -> &f32 {
+        intern(self.get_value(atom_type))
+    }
+
+    /// Returns the radius like [`Self::get`], but by value.
+    pub fn get_value(&self, atom_type: impl ToElement + std::fmt::Debug) -> f32 {
+        self.lookup(atom_type.to_element()).unwrap_or_else(|| {
             log::warn!(
-                "Unknown atom type: {}. Using default radius of 1.0.",
+                "Unknown atom type: {:?}. Using default radius of 1.0.",
                 atom_type
             );
-            &1.0
+            1.0
         })
     }
+
+    /// Returns the radius of an element or atom type, or `None` if it is unknown.
+    pub fn try_get(&self, atom_type: impl ToElement) -> Option<f32> {
+        self.lookup(atom_type.to_element())
+    }
+
+    fn lookup(&self, element: Option<Element>) -> Option<f32> {
+        let element = element?;
+
+        self.radii()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&element)
+            .copied()
+    }
+
+    /// Returns the radius of an element or atom type, handling unknown ones as given.
+    pub fn get_or(
+        &self,
+        atom_type: impl ToElement + std::fmt::Debug,
+        missing: MissingRadius,
+    ) -> Result<f32, DataTableError> {
+        if let Some(radius) = self.lookup(atom_type.to_element()) {
+            return Ok(radius);
+        }
+
+        match missing {
+            MissingRadius::Default(radius) => Ok(radius),
+            MissingRadius::Warn(radius) => {
+                log::warn!(
+                    "Unknown atom type: {:?}. Using default radius of {}.",
+                    atom_type,
+                    radius
+                );
+                Ok(radius)
+            }
+            MissingRadius::Error => Err(DataTableError::new(format!(
+                "No {} for atom type: {:?}",
+                self.name, atom_type
+            ))),
+        }
+    }
+
+    /// Sets the radius of an element, returning the previous one.
+    pub fn insert(&self, element: Element, radius: f32) -> Option<f32> {
+        self.radii()
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(element, radius)
+    }
+
+    /// Merges radii in the TOML format of the bundled data, e.g. `Fe = 1.3`, into the table.
+    /// Elements not in `content` keep their radius.
+    pub fn merge_toml(&self, content: &str) -> Result<(), DataTableError> {
+        let table = toml::from_str(content)
+            .map_err(|e| DataTableError::new(format!("Invalid {} table: {}", self.name, e)))?;
+
+        for (element, radius) in try_element_table(table)? {
+            self.insert(element, radius);
+        }
+
+        Ok(())
+    }
+
+    /// Merges radii from a TOML file, see [`Self::merge_toml`].
+    pub fn merge_file(&self, path: impl AsRef<std::path::Path>) -> Result<(), DataTableError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| DataTableError::new(format!("{}: {}", path.display(), e)))?;
+
+        self.merge_toml(&content)
+    }
+
+    /// Restores the bundled radii.
+    pub fn reset(&self) {
+        *self.radii().write().unwrap_or_else(|e| e.into_inner()) = self.bundled();
+    }
+
+    fn radii(&self) -> &RwLock<HashMap<Element, f32>> {
+        self.radii.get_or_init(|| RwLock::new(self.bundled()))
+    }
+
+    fn bundled(&self) -> HashMap<Element, f32> {
+        let data = std::str::from_utf8(self.raw)
+            .unwrap_or_else(|_| panic!("Failed to convert {} data to str", self.name));
+        let table: HashMap<String, f32> = toml::from_str(data)
+            .unwrap_or_else(|_| panic!("Failed to parse {} data form toml", self.name));
+
+        element_table(table)
+    }
 }
 
-pub static COVALENT_RADII: CovalentRadii = CovalentRadii::load();
+/// Returns a reference to a radius that lives as long as the program. Each distinct value is
+/// allocated only once, so overriding radii repeatedly does not grow the memory use.
+fn intern(radius: f32) -> &'static f32 {
+    static INTERNED: LazyLock<Mutex<HashMap<u32, &'static f32>>> = LazyLock::new(Default::default);
+
+    INTERNED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(radius.to_bits())
+        .or_insert_with(|| Box::leak(Box::new(radius)))
+}
+
+/// Converts a table keyed by element symbol. `Ln` and `An` give the value of lanthanides and
+/// actinides without an own entry.
+fn element_table<T: Clone>(table: HashMap<String, T>) -> HashMap<Element, T> {
+    try_element_table(table).unwrap_or_else(|e| panic!("{e}"))
+}
+
+fn try_element_table<T: Clone>(
+    mut table: HashMap<String, T>,
+) -> Result<HashMap<Element, T>, DataTableError> {
+    let groups = [("Ln", 57..=71), ("An", 89..=103)];
+    let mut elements = HashMap::new();
+
+    for (symbol, atomic_numbers) in groups {
+        if let Some(value) = table.remove(symbol) {
+            elements.extend(
+                atomic_numbers
+                    .filter_map(Element::from_atomic_number)
+                    .map(|element| (element, value.clone())),
+            );
+        }
+    }
+
+    for (symbol, value) in table {
+        let element = Element::from_symbol(&symbol).ok_or_else(|| {
+            DataTableError::new(format!("Unknown element in data table: {symbol}"))
+        })?;
+
+        elements.insert(element, value);
+    }
+
+    Ok(elements)
+}
 
 #[cfg(test)]
 #[test]
-fn test_covalent_radii() {
-    assert_eq!(COVALENT_RADII.get("H"), &0.31);
+fn test_radius_table() {
+    let table = RadiusTable::new("test radii", b"H = 1.0\nO = 1.5\n");
+
+    assert_eq!(table.try_get("O2-"), Some(1.5));
+    assert_eq!(table.try_get("Fe"), None);
+    assert_eq!(table.get("O"), &1.5);
+    assert!(std::ptr::eq(table.get("O"), table.get("O2-")));
+    assert_eq!(table.get_or("Fe", MissingRadius::Default(2.0)), Ok(2.0));
+    assert_eq!(table.get_or("Fe", MissingRadius::Warn(2.0)), Ok(2.0));
+    assert!(table.get_or("Fe", MissingRadius::Error).is_err());
+    assert_eq!(table.get_or("H", MissingRadius::Error), Ok(1.0));
+
+    table.merge_toml("Fe = 1.25\nO = 1.4").unwrap();
+    assert_eq!(table.try_get("Fe"), Some(1.25));
+    assert_eq!(table.try_get("O"), Some(1.4));
+    assert_eq!(table.try_get("H"), Some(1.0));
+
+    assert!(table.merge_toml("Xx = 1.0").is_err());
+    assert!(table.merge_toml("Fe = ").is_err());
+
+    assert_eq!(table.insert(Element::Fe, 1.3), Some(1.25));
+
+    table.reset();
+    assert_eq!(table.try_get("Fe"), None);
+    assert_eq!(table.try_get("O"), Some(1.5));
+}
+
+/// An element table with additional entries for isotopes, e.g. `D` or `13C`.
+struct IsotopeTable<T> {
+    elements: HashMap<Element, T>,
+    isotopes: HashMap<(Element, u16), T>,
+}
+
+impl<T: Clone> IsotopeTable<T> {
+    /// Splits a table keyed by atom type symbol into elements and isotopes.
+    fn new(name: &str, table: HashMap<String, T>) -> Self {
+        let mut isotopes = HashMap::new();
+        let mut elements = HashMap::new();
+
+        for (symbol, value) in table {
+            match symbol.parse::<AtomType>() {
+                Ok(AtomType {
+                    element,
+                    mass_number: Some(mass_number),
+                    ..
+                }) => {
+                    isotopes.insert((element, mass_number), value);
+                }
+                _ => {
+                    elements.insert(symbol, value);
+                }
+            }
+        }
+
+        Self {
+            elements: try_element_table(elements)
+                .unwrap_or_else(|e| panic!("Invalid {name} data: {e}")),
+            isotopes,
+        }
+    }
+
+    /// Returns the value of the isotope of the atom type, or of the element if no isotope is
+    /// given. Isotopes without an entry give `None`.
+    fn get(&self, atom_type: impl ToElement) -> Option<&T> {
+        let atom_type = atom_type.to_atom_type()?;
+
+        match atom_type.mass_number {
+            Some(mass_number) => self.isotopes.get(&(atom_type.element, mass_number)),
+            None => self.elements.get(&atom_type.element),
+        }
+    }
+}
+
+pub const ATOMIC_NUMBERS_RAW: &[u8] = include_bytes!("../assets/data/atomic-numbers.toml");
+
+pub struct AtomicNumbers(LazyLock<HashMap<Element, u8>>);
+
+impl AtomicNumbers {
+    pub const fn load() -> Self {
+        Self(LazyLock::new(|| {
+            let data = std::str::from_utf8(ATOMIC_NUMBERS_RAW)
+                .expect("Failed to convert atomic number data to str");
+            element_table(
+                toml::from_str(data).expect("Failed to parse atomic number data form toml"),
+            )
+        }))
+    }
+
+    /// Returns the atomic number of an atom type, parsing types like `Fe2+` or `Ow`.
+    pub fn get(&self, atom_type: impl ToElement) -> Option<&u8> {
+        self.0.get(&atom_type.to_element()?)
+    }
+}
+
+pub static ATOMIC_NUMBERS: AtomicNumbers = AtomicNumbers::load();
+
+#[cfg(test)]
+#[test]
+fn test_atomic_numbers() {
+    assert_eq!(ATOMIC_NUMBERS.get("H"), Some(&1));
+    assert_eq!(ATOMIC_NUMBERS.get("Og"), Some(&118));
+    assert_eq!(ATOMIC_NUMBERS.get("Xx"), None);
+    assert_eq!(ATOMIC_NUMBERS.get("Fe3+"), Some(&26));
+    assert_eq!(ATOMIC_NUMBERS.get("D"), Some(&1));
+    assert_eq!(ATOMIC_NUMBERS.get(Element::Na), Some(&11));
+}
+
+pub const NEUTRON_SCATTERING_LENGTHS_RAW: &[u8] =
+    include_bytes!("../assets/data/neutron-scattering-lengths.toml");
+
+pub struct NeutronScatteringLengths(LazyLock<IsotopeTable<f32>>);
+
+impl NeutronScatteringLengths {
+    pub const fn load() -> Self {
+        Self(LazyLock::new(|| {
+            let data = std::str::from_utf8(NEUTRON_SCATTERING_LENGTHS_RAW)
+                .expect("Failed to convert neutron scattering length data to str");
+            IsotopeTable::new(
+                "neutron scattering length",
+                toml::from_str(data)
+                    .expect("Failed to parse neutron scattering length data form toml"),
+            )
+        }))
+    }
+
+    /// Returns the bound coherent scattering length in fm. Isotopes are only known as `D` and
+    /// `T`, other atom types like `Fe2+` give the length of the natural isotope mixture.
+    pub fn get(&self, atom_type: impl ToElement) -> Option<&f32> {
+        self.0.get(atom_type)
+    }
+}
+
+pub static NEUTRON_SCATTERING_LENGTHS: NeutronScatteringLengths = NeutronScatteringLengths::load();
+
+#[cfg(test)]
+#[test]
+fn test_neutron_scattering_lengths() {
+    assert_eq!(NEUTRON_SCATTERING_LENGTHS.get("H"), Some(&-3.739));
+    assert_eq!(NEUTRON_SCATTERING_LENGTHS.get("D"), Some(&6.671));
+    assert_eq!(NEUTRON_SCATTERING_LENGTHS.get("2H"), Some(&6.671));
+    assert_eq!(NEUTRON_SCATTERING_LENGTHS.get("O1"), Some(&5.803));
+    assert_eq!(NEUTRON_SCATTERING_LENGTHS.get(Element::O), Some(&5.803));
+    assert_eq!(NEUTRON_SCATTERING_LENGTHS.get("13C"), None);
+}
+
+pub const ATOMIC_MASSES_RAW: &[u8] = include_bytes!("../assets/data/atomic-masses.toml");
+
+pub struct AtomicMasses(LazyLock<IsotopeTable<f32>>);
+
+impl AtomicMasses {
+    pub const fn load() -> Self {
+        Self(LazyLock::new(|| {
+            let data = std::str::from_utf8(ATOMIC_MASSES_RAW)
+                .expect("Failed to convert atomic mass data to str");
+            IsotopeTable::new(
+                "atomic mass",
+                toml::from_str(data).expect("Failed to parse atomic mass data form toml"),
+            )
+        }))
+    }
+
+    /// Returns the standard atomic weight in u. Isotopes are only known as `D` and `T`, other
+    /// atom types like `Fe2+` give the weight of the element.
+    pub fn get(&self, atom_type: impl ToElement) -> Option<&f32> {
+        self.0.get(atom_type)
+    }
+}
+
+pub static ATOMIC_MASSES: AtomicMasses = AtomicMasses::load();
+
+#[cfg(test)]
+#[test]
+fn test_atomic_masses() {
+    assert_eq!(ATOMIC_MASSES.get("C"), Some(&12.011));
+    assert_eq!(ATOMIC_MASSES.get("Tc"), Some(&98.0));
+    assert_eq!(ATOMIC_MASSES.get("Xx"), None);
+    assert_eq!(ATOMIC_MASSES.get("D"), Some(&2.0141));
+    assert_eq!(ATOMIC_MASSES.get("D1"), Some(&2.0141));
+    assert_eq!(ATOMIC_MASSES.get("H1"), Some(&1.008));
+    assert_eq!(ATOMIC_MASSES.get("O2-"), Some(&15.999));
+    assert_eq!(ATOMIC_MASSES.get(Element::Fe), Some(&55.845));
+}
+
+pub const ELECTRONEGATIVITIES_RAW: &[u8] =
+    include_bytes!("../assets/data/electronegativities.toml");
+
+pub struct Electronegativities(LazyLock<HashMap<Element, f32>>);
+
+impl Electronegativities {
+    pub const fn load() -> Self {
+        Self(LazyLock::new(|| {
+            let data = std::str::from_utf8(ELECTRONEGATIVITIES_RAW)
+                .expect("Failed to convert electronegativity data to str");
+            element_table(
+                toml::from_str(data).expect("Failed to parse electronegativity data form toml"),
+            )
+        }))
+    }
+
+    /// Returns the Pauling electronegativity.
+    pub fn get(&self, atom_type: impl ToElement) -> Option<&f32> {
+        self.0.get(&atom_type.to_element()?)
+    }
+}
+
+pub static ELECTRONEGATIVITIES: Electronegativities = Electronegativities::load();
+
+#[cfg(test)]
+#[test]
+fn test_electronegativities() {
+    assert_eq!(ELECTRONEGATIVITIES.get("F"), Some(&3.98));
+    assert_eq!(ELECTRONEGATIVITIES.get(Element::Na), Some(&0.93));
+    assert_eq!(ELECTRONEGATIVITIES.get("Ne"), None);
+}
+
+pub const JMOL_COLORS_RAW: &[u8] = include_bytes!("../assets/data/jmol-colors.toml");
+
+pub struct JmolColors(LazyLock<HashMap<Element, [u8; 3]>>);
+
+impl JmolColors {
+    pub const fn load() -> Self {
+        Self(LazyLock::new(|| {
+            let data =
+                std::str::from_utf8(JMOL_COLORS_RAW).expect("Failed to convert color data to str");
+            let colors: HashMap<String, String> =
+                toml::from_str(data).expect("Failed to parse color data form toml");
+
+            element_table(
+                colors
+                    .into_iter()
+                    .map(|(symbol, color)| {
+                        let color = parse_hex_color(&color)
+                            .unwrap_or_else(|| panic!("Invalid color in color data: {color}"));
+
+                        (symbol, color)
+                    })
+                    .collect(),
+            )
+        }))
+    }
+
+    /// Returns the display color as RGB.
+    pub fn get(&self, atom_type: impl ToElement) -> Option<&[u8; 3]> {
+        self.0.get(&atom_type.to_element()?)
+    }
+
+    /// Returns the display color as RGB with components in `[0, 1]`.
+    pub fn get_f32(&self, atom_type: impl ToElement) -> Option<[f32; 3]> {
+        self.get(atom_type)
+            .map(|color| color.map(|c| f32::from(c) / 255.0))
+    }
+}
+
+pub static JMOL_COLORS: JmolColors = JmolColors::load();
+
+/// Parses a color like `#E06633`.
+fn parse_hex_color(color: &str) -> Option<[u8; 3]> {
+    let hex = color.strip_prefix('#')?;
+
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let component = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+
+    Some([component(0)?, component(2)?, component(4)?])
+}
+
+#[cfg(test)]
+#[test]
+fn test_jmol_colors() {
+    assert_eq!(JMOL_COLORS.get("Fe"), Some(&[0xE0, 0x66, 0x33]));
+    assert_eq!(JMOL_COLORS.get("H"), Some(&[255, 255, 255]));
+    assert_eq!(
+        JMOL_COLORS.get_f32("O2-"),
+        Some([1.0, 13.0 / 255.0, 13.0 / 255.0])
+    );
+    assert_eq!(JMOL_COLORS.get(Element::Og), None);
+}
+
+pub const SHANNON_RADII_RAW: &[u8] = include_bytes!("../assets/data/shannon-radii.toml");
+
+/// The spin state of a transition metal ion.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpinState {
+    High,
+    Low,
+}
+
+/// A coordination shape that Shannon (1976) tabulates separately from the regular polyhedron
+/// of the same coordination number.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoordinationShape {
+    SquarePlanar,
+    Pyramidal,
+}
+
+/// The coordination of an ion, e.g. `IV` or `IVSQ`.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CoordinationGeometry {
+    pub coordination_number: u8,
+    /// `None` for the regular polyhedron.
+    pub shape: Option<CoordinationShape>,
+}
+
+impl CoordinationGeometry {
+    pub fn new(coordination_number: u8, shape: Option<CoordinationShape>) -> Self {
+        Self {
+            coordination_number,
+            shape,
+        }
+    }
+}
+
+impl From<u8> for CoordinationGeometry {
+    fn from(coordination_number: u8) -> Self {
+        Self::new(coordination_number, None)
+    }
+}
+
+/// An effective ionic radius of Shannon (1976).
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IonicRadius {
+    pub oxidation_state: i8,
+    pub geometry: CoordinationGeometry,
+    /// The spin state for ions whose radius depends on it.
+    pub spin: Option<SpinState>,
+    /// The radius in Å.
+    pub radius: f32,
+}
+
+pub struct ShannonRadii(LazyLock<HashMap<Element, Vec<IonicRadius>>>);
+
+impl ShannonRadii {
+    pub const fn load() -> Self {
+        Self(LazyLock::new(|| {
+            let data = std::str::from_utf8(SHANNON_RADII_RAW)
+                .expect("Failed to convert Shannon radii data to str");
+            let table: HashMap<String, HashMap<String, f32>> =
+                toml::from_str(data).expect("Failed to parse Shannon radii data form toml");
+
+            element_table(
+                table
+                    .into_iter()
+                    .map(|(symbol, radii)| {
+                        let mut radii = radii
+                            .into_iter()
+                            .map(|(key, radius)| {
+                                parse_ionic_radius(&key, radius).unwrap_or_else(|| {
+                                    panic!("Invalid entry in Shannon radii data: {symbol} {key}")
+                                })
+                            })
+                            .collect::<Vec<_>>();
+
+                        radii.sort_by_key(|r| {
+                            (
+                                r.oxidation_state,
+                                r.geometry.coordination_number,
+                                r.geometry.shape.is_some(),
+                                r.spin == Some(SpinState::Low),
+                            )
+                        });
+
+                        (symbol, radii)
+                    })
+                    .collect(),
+            )
+        }))
+    }
+
+    /// Returns all tabulated radii of an element, sorted by oxidation state and coordination
+    /// number with the regular polyhedron first.
+    pub fn get_all(&self, element: impl ToElement) -> &[IonicRadius] {
+        element
+            .to_element()
+            .and_then(|element| self.0.get(&element))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the radius for exactly the given coordination number.
+    ///
+    /// A coordination number without shape, e.g. `4`, prefers the regular polyhedron but gives
+    /// the square planar radius if only that is tabulated. Without a spin state the high-spin
+    /// radius is used for ions that have both.
+    pub fn get(
+        &self,
+        element: impl ToElement,
+        oxidation_state: i8,
+        geometry: impl Into<CoordinationGeometry>,
+        spin: Option<SpinState>,
+    ) -> Option<&IonicRadius> {
+        let geometry = geometry.into();
+
+        self.get_nearest(element, oxidation_state, geometry, spin)
+            .filter(|radius| {
+                radius.geometry.coordination_number == geometry.coordination_number
+                    && (geometry.shape.is_none() || radius.geometry.shape == geometry.shape)
+            })
+    }
+
+    /// Returns the radius for the tabulated coordination number closest to the given one,
+    /// preferring the requested shape and then the lower coordination number on ties.
+    ///
+    /// Entries of the requested spin state, or without spin dependence, are preferred over
+    /// those of the other spin state. Without a spin state high spin is preferred.
+    pub fn get_nearest(
+        &self,
+        element: impl ToElement,
+        oxidation_state: i8,
+        geometry: impl Into<CoordinationGeometry>,
+        spin: Option<SpinState>,
+    ) -> Option<&IonicRadius> {
+        let geometry = geometry.into();
+        let spin = spin.unwrap_or(SpinState::High);
+
+        self.get_all(element)
+            .iter()
+            .filter(|radius| radius.oxidation_state == oxidation_state)
+            .min_by_key(|radius| {
+                (
+                    radius.spin.is_some_and(|s| s != spin),
+                    radius
+                        .geometry
+                        .coordination_number
+                        .abs_diff(geometry.coordination_number),
+                    radius.geometry.shape != geometry.shape,
+                    radius.geometry.coordination_number,
+                )
+            })
+    }
+}
+
+pub static SHANNON_RADII: ShannonRadii = ShannonRadii::load();
+
+/// Parses a key like `2+ VI HS` or `2+ IV SQ` of the Shannon radii data.
+fn parse_ionic_radius(key: &str, radius: f32) -> Option<IonicRadius> {
+    let mut tokens = key.split_whitespace();
+
+    let charge = tokens.next()?;
+    let oxidation_state = match charge.split_at(charge.len().checked_sub(1)?) {
+        (magnitude, "+") => magnitude.parse::<i8>().ok()?,
+        (magnitude, "-") => -magnitude.parse::<i8>().ok()?,
+        _ => return None,
+    };
+
+    let coordination_number = parse_roman_numeral(tokens.next()?)?;
+    let mut token = tokens.next();
+
+    let shape = match token {
+        Some("SQ") => Some(CoordinationShape::SquarePlanar),
+        Some("PY") => Some(CoordinationShape::Pyramidal),
+        _ => None,
+    };
+
+    if shape.is_some() {
+        token = tokens.next();
+    }
+
+    let spin = match token {
+        Some("HS") => Some(SpinState::High),
+        Some("LS") => Some(SpinState::Low),
+        Some(_) => return None,
+        None => None,
+    };
+
+    if tokens.next().is_some() {
+        return None;
+    }
+
+    Some(IonicRadius {
+        oxidation_state,
+        geometry: CoordinationGeometry::new(coordination_number, shape),
+        spin,
+        radius,
+    })
+}
+
+fn parse_roman_numeral(s: &str) -> Option<u8> {
+    let values = s
+        .chars()
+        .map(|c| match c {
+            'I' => Some(1),
+            'V' => Some(5),
+            'X' => Some(10),
+            _ => None,
+        })
+        .collect::<Option<Vec<u8>>>()?;
+
+    let total = values.iter().enumerate().fold(0i16, |total, (i, &value)| {
+        match values.get(i + 1).is_some_and(|&next| next > value) {
+            true => total - i16::from(value),
+            false => total + i16::from(value),
+        }
+    });
+
+    u8::try_from(total).ok().filter(|&n| n > 0)
+}
+
+#[cfg(test)]
+#[test]
+fn test_shannon_radii() {
+    let radius = |element, charge, cn, spin| {
+        SHANNON_RADII
+            .get(element, charge, cn, spin)
+            .map(|r| r.radius)
+    };
+    let nearest = |element, charge, cn| {
+        SHANNON_RADII
+            .get_nearest(element, charge, cn, None)
+            .map(|r| (r.geometry.coordination_number, r.radius))
+    };
+
+    assert_eq!(radius("O", -2, 6, None), Some(1.40));
+    assert_eq!(radius("Fe", 2, 6, None), Some(0.78));
+    assert_eq!(radius("Fe", 2, 6, Some(SpinState::High)), Some(0.78));
+    assert_eq!(radius("Fe", 2, 6, Some(SpinState::Low)), Some(0.61));
+    assert_eq!(radius("Fe", 3, 5, Some(SpinState::Low)), Some(0.58));
+    assert_eq!(radius("Rb", 1, 14, None), Some(1.83));
+    assert_eq!(radius("Fe", 2, 7, None), None);
+    assert_eq!(radius("Xx", 2, 6, None), None);
+
+    assert_eq!(nearest("Fe", 2, 7), Some((6, 0.78)));
+    assert_eq!(nearest("Mg", 2, 7), Some((6, 0.72)));
+    assert_eq!(nearest("Ca", 2, 4), Some((6, 1.00)));
+    assert_eq!(nearest("Ca", 5, 6), None);
+
+    // square planar and pyramidal coordinations are kept apart from the regular ones
+    let square_planar = CoordinationGeometry::new(4, Some(CoordinationShape::SquarePlanar));
+
+    assert_eq!(radius("Ni", 2, 4, None), Some(0.55));
+    assert_eq!(
+        SHANNON_RADII
+            .get("Ni", 2, square_planar, None)
+            .map(|r| r.radius),
+        Some(0.49)
+    );
+    assert_eq!(radius("Pd", 2, 4, None), Some(0.64));
+    assert_eq!(SHANNON_RADII.get("Zn", 2, square_planar, None), None);
+    assert_eq!(
+        SHANNON_RADII
+            .get("Pb", 2, 4, None)
+            .and_then(|r| r.geometry.shape),
+        Some(CoordinationShape::Pyramidal)
+    );
+    assert_eq!(
+        SHANNON_RADII
+            .get("Fe", 2, square_planar, Some(SpinState::High))
+            .map(|r| r.radius),
+        Some(0.64)
+    );
+
+    // the table extends beyond Pu
+    assert_eq!(radius("Cf", 4, 8, None), Some(0.92));
+    assert_eq!(radius("Xe", 8, 6, None), Some(0.48));
+    assert_eq!(
+        parse_ionic_radius("5+ III PY", 0.1).map(|r| r.geometry),
+        Some(CoordinationGeometry::new(
+            3,
+            Some(CoordinationShape::Pyramidal)
+        ))
+    );
+    assert_eq!(parse_ionic_radius("2+ IV SQ HS XX", 0.1), None);
+
+    assert!(SHANNON_RADII
+        .get_all(Element::Mn)
+        .windows(2)
+        .all(|w| w[0].oxidation_state <= w[1].oxidation_state));
+    assert_eq!(parse_roman_numeral("XIV"), Some(14));
+    assert_eq!(parse_roman_numeral("IX"), Some(9));
+}
+
+pub const BOND_VALENCE_PARAMETERS_RAW: &[u8] =
+    include_bytes!("../assets/data/bond-valence-parameters.toml");
+
+/// The parameters of the bond valence `s = exp((R0 - R) / b)` of a cation-anion bond.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BondValenceParameter {
+    /// The bond length of unit valence in Å.
+    pub r0: f64,
+    /// The softness in Å.
+    pub b: f64,
+}
+
+impl BondValenceParameter {
+    /// Returns the valence of a bond of the given length in Å.
+    pub fn bond_valence(&self, distance: f64) -> f64 {
+        ((self.r0 - distance) / self.b).exp()
+    }
+}
+
+/// The `b` of all tabulated bond valence parameters.
+const BOND_VALENCE_SOFTNESS: f64 = 0.37;
+
+/// Bond valence parameters of Brown & Altermatt (1985) and Brese & O'Keeffe (1991) by cation
+/// element, cation oxidation state and anion element.
+pub struct BondValenceParameters(LazyLock<HashMap<(Element, i8, Element), f32>>);
+
+impl BondValenceParameters {
+    pub const fn load() -> Self {
+        Self(LazyLock::new(|| {
+            let data = std::str::from_utf8(BOND_VALENCE_PARAMETERS_RAW)
+                .expect("Failed to convert bond valence data to str");
+            let table: HashMap<String, HashMap<String, f32>> =
+                toml::from_str(data).expect("Failed to parse bond valence data form toml");
+
+            let mut parameters = HashMap::new();
+
+            for (anion, cations) in element_table(table) {
+                for (cation, r0) in cations {
+                    let cation = cation
+                        .parse::<AtomType>()
+                        .ok()
+                        .filter(|cation| cation.oxidation_state.is_some_and(|s| s > 0))
+                        .unwrap_or_else(|| {
+                            panic!("Invalid entry in bond valence data: {anion} {cation}")
+                        });
+
+                    parameters.insert(
+                        (cation.element, cation.oxidation_state.unwrap_or(0), anion),
+                        r0,
+                    );
+                }
+            }
+
+            parameters
+        }))
+    }
+
+    /// Returns the parameters of a bond between a cation in the given oxidation state and an
+    /// anion.
+    pub fn get(
+        &self,
+        cation: impl ToElement,
+        oxidation_state: i8,
+        anion: impl ToElement,
+    ) -> Option<BondValenceParameter> {
+        let key = (cation.to_element()?, oxidation_state, anion.to_element()?);
+
+        self.0.get(&key).map(|r0| BondValenceParameter {
+            r0: f64::from(*r0),
+            b: BOND_VALENCE_SOFTNESS,
+        })
+    }
+
+    /// Returns the oxidation states of a cation with parameters for bonds to the anion in
+    /// ascending order.
+    pub fn oxidation_states(&self, cation: impl ToElement, anion: impl ToElement) -> Vec<i8> {
+        let (Some(cation), Some(anion)) = (cation.to_element(), anion.to_element()) else {
+            return Vec::new();
+        };
+
+        let mut states = self
+            .0
+            .keys()
+            .filter(|(element, _, other)| *element == cation && *other == anion)
+            .map(|(_, state, _)| *state)
+            .collect::<Vec<_>>();
+        states.sort();
+
+        states
+    }
+
+    /// Returns whether the element appears as an anion in the table.
+    pub fn is_anion(&self, element: impl ToElement) -> bool {
+        element
+            .to_element()
+            .is_some_and(|element| self.0.keys().any(|(_, _, anion)| *anion == element))
+    }
+}
+
+pub static BOND_VALENCE_PARAMETERS: BondValenceParameters = BondValenceParameters::load();
+
+#[cfg(test)]
+#[test]
+fn test_bond_valence_parameters() {
+    let fe_o = BOND_VALENCE_PARAMETERS.get("Fe", 3, "O").unwrap();
+
+    assert_eq!(fe_o.r0, f64::from(1.759f32));
+    assert_eq!(fe_o.b, 0.37);
+    assert_eq!(fe_o.bond_valence(fe_o.r0), 1.0);
+    assert_eq!(BOND_VALENCE_PARAMETERS.get("Fe", 4, "O"), None);
+    assert_eq!(
+        BOND_VALENCE_PARAMETERS.oxidation_states("Mn", "O"),
+        [2, 3, 4]
+    );
+    assert!(BOND_VALENCE_PARAMETERS.is_anion("Cl-"));
+    assert!(!BOND_VALENCE_PARAMETERS.is_anion("Na"));
 }
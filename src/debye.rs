@@ -0,0 +1,298 @@
+use cgmath::MetricSpace;
+
+use crate::{cluster::Cluster, scattering::Radiation};
+
+/// Parameters of a Debye scattering equation calculation.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebyeParameters {
+    /// Lower end of the calculated range in Å⁻¹.
+    pub q_min: f64,
+    /// Upper end of the calculated range in Å⁻¹.
+    pub q_max: f64,
+    /// Grid spacing in Å⁻¹.
+    pub q_step: f64,
+    /// Width in Å of the bins the interatomic distances are sorted into.
+    pub bin_width: f64,
+    pub radiation: Radiation,
+}
+
+impl Default for DebyeParameters {
+    fn default() -> Self {
+        Self {
+            q_min: 0.5,
+            q_max: 20.0,
+            q_step: 0.01,
+            bin_width: 1e-3,
+            radiation: Radiation::XRay,
+        }
+    }
+}
+
+impl DebyeParameters {
+    /// Checks that the grid is non-empty and that the bin width is finite and positive.
+    pub fn validate(&self) -> Result<(), DebyeError> {
+        if !(self.q_step.is_finite() && self.q_step > 0.0) {
+            return Err(DebyeError::new(format!(
+                "Grid spacing must be positive: {}",
+                self.q_step
+            )));
+        }
+
+        if !(self.q_min.is_finite() && self.q_max.is_finite() && 0.0 <= self.q_min) {
+            return Err(DebyeError::new(format!(
+                "Range must be finite and non-negative: {} to {}",
+                self.q_min, self.q_max
+            )));
+        }
+
+        if self.q_min > self.q_max {
+            return Err(DebyeError::new(format!(
+                "Lower end of the range exceeds the upper end: {} > {}",
+                self.q_min, self.q_max
+            )));
+        }
+
+        if !(self.bin_width.is_finite() && self.bin_width > 0.0) {
+            return Err(DebyeError::new(format!(
+                "Bin width must be positive: {}",
+                self.bin_width
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct DebyeError(String);
+
+impl DebyeError {
+    pub fn new(message: impl AsRef<str>) -> Self {
+        Self(message.as_ref().to_string())
+    }
+}
+
+impl std::fmt::Display for DebyeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for DebyeError {}
+
+/// Scattered intensity of a finite cluster.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebyeScattering {
+    pub q: Vec<f64>,
+    pub intensity: Vec<f64>,
+}
+
+impl DebyeScattering {
+    /// Calculates the scattered intensity with the Q-independent scattering powers of the
+    /// radiation.
+    ///
+    /// Fails for invalid parameters, see [`DebyeParameters::validate`], or atom types without a
+    /// scattering power.
+    pub fn calculate(cluster: &Cluster, parameters: &DebyeParameters) -> Result<Self, DebyeError> {
+        for atom in &cluster.atoms {
+            parameters
+                .radiation
+                .scattering_power(&atom.type_)
+                .map_err(|e| DebyeError::new(e.to_string()))?;
+        }
+
+        Self::calculate_with_form_factors(cluster, parameters, |atom_type, _| {
+            parameters
+                .radiation
+                .scattering_power(atom_type)
+                .unwrap_or_default()
+        })
+    }
+
+    /// Calculates the scattered intensity
+    /// `I(Q) = Σᵢ Σⱼ fᵢ(Q) fⱼ(Q) sin(Q rᵢⱼ) / (Q rᵢⱼ)`.
+    ///
+    /// `form_factor` returns the scattering factor of an atom type at `Q`. The interatomic
+    /// distances are histogrammed per pair of atom types first, so the cost of evaluating the
+    /// sum scales with the number of bins instead of the number of pairs.
+    ///
+    /// Fails for invalid parameters, see [`DebyeParameters::validate`].
+    pub fn calculate_with_form_factors(
+        cluster: &Cluster,
+        parameters: &DebyeParameters,
+        form_factor: impl Fn(&str, f64) -> f64,
+    ) -> Result<Self, DebyeError> {
+        parameters.validate()?;
+
+        let mut species: Vec<&str> = Vec::new();
+        let atom_species = cluster
+            .atoms
+            .iter()
+            .map(|atom| match species.iter().position(|s| *s == atom.type_) {
+                Some(index) => index,
+                None => {
+                    species.push(&atom.type_);
+                    species.len() - 1
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut self_terms = vec![0.0; species.len()];
+        let mut histograms: Vec<Vec<f64>> = vec![Vec::new(); species.len() * species.len()];
+
+        for (i, a) in cluster.atoms.iter().enumerate() {
+            self_terms[atom_species[i]] += a.occupancy;
+
+            for (j, b) in cluster.atoms.iter().enumerate().skip(i + 1) {
+                let (s, t) = match atom_species[i] <= atom_species[j] {
+                    true => (atom_species[i], atom_species[j]),
+                    false => (atom_species[j], atom_species[i]),
+                };
+
+                let bin = (a.position.distance(b.position) / parameters.bin_width).round() as usize;
+                let histogram = &mut histograms[s * species.len() + t];
+
+                if histogram.len() <= bin {
+                    histogram.resize(bin + 1, 0.0);
+                }
+
+                histogram[bin] += a.occupancy * b.occupancy;
+            }
+        }
+
+        let number_of_points =
+            ((parameters.q_max - parameters.q_min) / parameters.q_step + 1e-9).floor() as usize + 1;
+        let q = (0..number_of_points)
+            .map(|i| parameters.q_min + i as f64 * parameters.q_step)
+            .collect::<Vec<_>>();
+
+        let intensity = q
+            .iter()
+            .map(|&q| {
+                let f = species
+                    .iter()
+                    .map(|s| form_factor(s, q))
+                    .collect::<Vec<_>>();
+
+                let mut intensity: f64 = self_terms.iter().zip(&f).map(|(n, f)| n * f * f).sum();
+
+                for s in 0..species.len() {
+                    for t in s..species.len() {
+                        let sum: f64 = histograms[s * species.len() + t]
+                            .iter()
+                            .enumerate()
+                            .filter(|(_, n)| **n != 0.0)
+                            .map(|(bin, n)| n * sinc(q * bin as f64 * parameters.bin_width))
+                            .sum();
+
+                        intensity += 2.0 * f[s] * f[t] * sum;
+                    }
+                }
+
+                intensity
+            })
+            .collect();
+
+        Ok(Self { q, intensity })
+    }
+}
+
+fn sinc(x: f64) -> f64 {
+    match x.abs() < 1e-12 {
+        true => 1.0,
+        false => x.sin() / x,
+    }
+}
+
+#[cfg(test)]
+mod test_debye {
+    use super::{DebyeParameters, DebyeScattering};
+    use crate::cluster::{Cluster, ClusterAtom};
+
+    fn dimer(distance: f64) -> Cluster {
+        let atom = |z: f64| ClusterAtom {
+            label: "C1".to_string(),
+            type_: "C".to_string(),
+            position: cgmath::Point3::new(0.0, 0.0, z),
+            occupancy: 1.0,
+        };
+
+        Cluster {
+            atoms: vec![atom(0.0), atom(distance)],
+        }
+    }
+
+    #[test]
+    fn test_dimer() {
+        let parameters = DebyeParameters {
+            q_min: 0.1,
+            q_max: 10.0,
+            q_step: 0.1,
+            ..Default::default()
+        };
+
+        let scattering = DebyeScattering::calculate(&dimer(1.5), &parameters).unwrap();
+
+        for (q, intensity) in scattering.q.iter().zip(&scattering.intensity) {
+            let expected = 2.0 * 36.0 * (1.0 + (q * 1.5).sin() / (q * 1.5));
+
+            assert!((intensity - expected).abs() < 1e-8);
+        }
+    }
+
+    #[test]
+    fn test_form_factors() {
+        let parameters = DebyeParameters::default();
+
+        let scattering =
+            DebyeScattering::calculate_with_form_factors(&dimer(1.5), &parameters, |_, q| {
+                (-0.1 * q * q).exp()
+            })
+            .unwrap();
+
+        let q = scattering.q[0];
+
+        assert!(
+            (scattering.intensity[0]
+                - 2.0 * (-0.2 * q * q).exp() * (1.0 + (q * 1.5).sin() / (q * 1.5)))
+                .abs()
+                < 1e-8
+        );
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        for parameters in [
+            DebyeParameters {
+                q_step: 0.0,
+                ..Default::default()
+            },
+            DebyeParameters {
+                q_step: -0.1,
+                ..Default::default()
+            },
+            DebyeParameters {
+                q_min: 10.0,
+                q_max: 5.0,
+                ..Default::default()
+            },
+            DebyeParameters {
+                q_max: f64::INFINITY,
+                ..Default::default()
+            },
+            DebyeParameters {
+                bin_width: 0.0,
+                ..Default::default()
+            },
+        ] {
+            assert!(DebyeScattering::calculate(&dimer(1.5), &parameters).is_err());
+        }
+
+        let mut unknown = dimer(1.5);
+        unknown.atoms[0].type_ = "Xx".to_string();
+
+        assert!(DebyeScattering::calculate(&unknown, &DebyeParameters::default()).is_err());
+    }
+}
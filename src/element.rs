@@ -0,0 +1,424 @@
+use std::str::FromStr;
+
+/// A chemical element, with the atomic number as discriminant.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(u8)]
+#[rustfmt::skip]
+pub enum Element {
+    H = 1, He, Li, Be, B, C, N, O, F, Ne, Na, Mg, Al, Si, P, S, Cl, Ar, K, Ca, Sc, Ti, V, Cr, Mn,
+    Fe, Co, Ni, Cu, Zn, Ga, Ge, As, Se, Br, Kr, Rb, Sr, Y, Zr, Nb, Mo, Tc, Ru, Rh, Pd, Ag, Cd, In,
+    Sn, Sb, Te, I, Xe, Cs, Ba, La, Ce, Pr, Nd, Pm, Sm, Eu, Gd, Tb, Dy, Ho, Er, Tm, Yb, Lu, Hf, Ta,
+    W, Re, Os, Ir, Pt, Au, Hg, Tl, Pb, Bi, Po, At, Rn, Fr, Ra, Ac, Th, Pa, U, Np, Pu, Am, Cm, Bk,
+    Cf, Es, Fm, Md, No, Lr, Rf, Db, Sg, Bh, Hs, Mt, Ds, Rg, Cn, Nh, Fl, Mc, Lv, Ts, Og,
+}
+
+impl Element {
+    /// Returns the element with atomic number `z`.
+    pub fn from_atomic_number(z: u8) -> Option<Self> {
+        ELEMENTS.get(usize::from(z).checked_sub(1)?).copied()
+    }
+
+    /// Returns the element of a symbol, e.g. `Fe`, ignoring case.
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        SYMBOLS
+            .iter()
+            .position(|s| s.eq_ignore_ascii_case(symbol))
+            .map(|i| ELEMENTS[i])
+    }
+
+    pub fn atomic_number(self) -> u8 {
+        self as u8
+    }
+
+    pub fn symbol(self) -> &'static str {
+        SYMBOLS[self as usize - 1]
+    }
+
+    /// Returns the period, i.e. the row of the periodic table.
+    pub fn period(self) -> u8 {
+        let z = self.atomic_number();
+
+        PERIOD_ENDS.iter().position(|&end| z <= end).unwrap_or(6) as u8 + 1
+    }
+
+    /// Returns the IUPAC group `1..=18`, or `None` for the lanthanides La–Lu and the
+    /// actinides Ac–Lr.
+    pub fn group(self) -> Option<u8> {
+        let z = self.atomic_number();
+        let period = usize::from(self.period());
+        let index = z - PERIOD_ENDS
+            .get(period.wrapping_sub(2))
+            .copied()
+            .unwrap_or(0);
+
+        match (period, index) {
+            (1, 1) => Some(1),
+            (1, _) => Some(18),
+            (2 | 3, 1..=2) => Some(index),
+            (2 | 3, _) => Some(index + 10),
+            (4 | 5, _) => Some(index),
+            (_, 1..=2) => Some(index),
+            (_, 3..=17) => None,
+            (_, _) => Some(index - 14),
+        }
+    }
+
+    /// Returns all elements in order of atomic number.
+    pub fn all() -> &'static [Element; 118] {
+        &ELEMENTS
+    }
+}
+
+impl std::fmt::Display for Element {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownElementError(String);
+
+impl UnknownElementError {
+    pub fn new(s: impl AsRef<str>) -> Self {
+        Self(format!("Unknown element in atom type: {}", s.as_ref()))
+    }
+}
+
+impl std::fmt::Display for UnknownElementError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UnknownElementError {}
+
+impl FromStr for Element {
+    type Err = UnknownElementError;
+
+    /// Parses an atom type symbol, see [`AtomType`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<AtomType>().map(|atom_type| atom_type.element)
+    }
+}
+
+/// An atom type symbol split into element, oxidation state and isotope.
+///
+/// The parser accepts the common forms of CIF `_atom_type_symbol` and of atom labels used in
+/// their place:
+///
+/// - charges as `Fe2+`, `Fe+2`, `O2-`, `O-` or `Fe(3+)`
+/// - mass numbers as `13C` or `^13C`, and `D` and `T` for hydrogen isotopes
+/// - trailing label numbers and suffixes, e.g. `Fe3`, `Ow` or `C12a`, which are ignored
+///
+/// Two letters are read as one symbol if the second is lower case, e.g. `Co`, or if the
+/// symbol consists of exactly two upper case letters, e.g. `FE` as in PDB files.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct AtomType {
+    pub element: Element,
+    pub oxidation_state: Option<i8>,
+    pub mass_number: Option<u16>,
+}
+
+impl AtomType {
+    pub fn new(element: Element) -> Self {
+        Self {
+            element,
+            oxidation_state: None,
+            mass_number: None,
+        }
+    }
+}
+
+impl FromStr for AtomType {
+    type Err = UnknownElementError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let symbol = s
+            .trim()
+            .chars()
+            .filter(|c| !matches!(c, '(' | ')' | '^'))
+            .collect::<String>();
+
+        let digits = symbol.chars().take_while(char::is_ascii_digit).count();
+        let mut mass_number = symbol[..digits].parse::<u16>().ok();
+        let rest = &symbol[digits..];
+
+        let letters = rest.chars().take_while(char::is_ascii_alphabetic).count();
+        let (name, charge) = rest.split_at(letters);
+
+        let two_letters = name.get(..2).filter(|two| {
+            let is_lower = two.chars().nth(1).is_some_and(|c| c.is_ascii_lowercase());
+            let is_upper = name.len() == 2 && two.chars().all(|c| c.is_ascii_uppercase());
+
+            (is_lower || is_upper) && Element::from_symbol(two).is_some()
+        });
+
+        let element = match (two_letters, name.get(..1)) {
+            (Some(two), _) => Element::from_symbol(two),
+            (None, Some("D")) => {
+                mass_number = mass_number.or(Some(2));
+                Some(Element::H)
+            }
+            (None, Some("T")) => {
+                mass_number = mass_number.or(Some(3));
+                Some(Element::H)
+            }
+            (None, Some(one)) => Element::from_symbol(one),
+            (None, None) => None,
+        }
+        .ok_or_else(|| UnknownElementError::new(s))?;
+
+        Ok(Self {
+            element,
+            oxidation_state: parse_charge(charge),
+            mass_number,
+        })
+    }
+}
+
+impl std::fmt::Display for AtomType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(mass_number) = self.mass_number {
+            write!(f, "{mass_number}")?;
+        }
+
+        write!(f, "{}", self.element)?;
+
+        match self.oxidation_state {
+            Some(charge) if charge < 0 => write!(f, "{}-", -charge),
+            Some(charge) => write!(f, "{charge}+"),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the charge of a suffix like `2+`, `+2`, `-` or `1-`. Digits without a sign are
+/// label numbers and give `None`.
+fn parse_charge(suffix: &str) -> Option<i8> {
+    let sign_position = suffix.find(['+', '-'])?;
+    let sign = match &suffix[sign_position..sign_position + 1] {
+        "-" => -1,
+        _ => 1,
+    };
+
+    let before = suffix[..sign_position]
+        .chars()
+        .rev()
+        .take_while(char::is_ascii_digit)
+        .count();
+    let after = suffix[sign_position + 1..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .count();
+
+    let magnitude = match (before, after) {
+        (0, 0) => "1",
+        (0, after) => &suffix[sign_position + 1..sign_position + 1 + after],
+        (before, _) => &suffix[sign_position - before..sign_position],
+    };
+
+    magnitude.parse::<i8>().ok().map(|m| sign * m)
+}
+
+pub trait ToElement {
+    fn to_element(&self) -> Option<Element>;
+
+    /// Returns the atom type, keeping the isotope and oxidation state where known.
+    fn to_atom_type(&self) -> Option<AtomType> {
+        self.to_element().map(AtomType::new)
+    }
+}
+
+impl ToElement for Element {
+    fn to_element(&self) -> Option<Element> {
+        Some(*self)
+    }
+}
+
+impl ToElement for AtomType {
+    fn to_element(&self) -> Option<Element> {
+        Some(self.element)
+    }
+
+    fn to_atom_type(&self) -> Option<AtomType> {
+        Some(*self)
+    }
+}
+
+impl ToElement for &str {
+    fn to_element(&self) -> Option<Element> {
+        self.parse().ok()
+    }
+
+    fn to_atom_type(&self) -> Option<AtomType> {
+        self.parse().ok()
+    }
+}
+
+impl ToElement for String {
+    fn to_element(&self) -> Option<Element> {
+        self.as_str().to_element()
+    }
+
+    fn to_atom_type(&self) -> Option<AtomType> {
+        self.as_str().to_atom_type()
+    }
+}
+
+impl ToElement for &String {
+    fn to_element(&self) -> Option<Element> {
+        self.as_str().to_element()
+    }
+
+    fn to_atom_type(&self) -> Option<AtomType> {
+        self.as_str().to_atom_type()
+    }
+}
+
+impl ToElement for u8 {
+    fn to_element(&self) -> Option<Element> {
+        Element::from_atomic_number(*self)
+    }
+}
+
+impl crate::Atom {
+    /// Returns the parsed atom type, falling back to the label if the type is empty.
+    pub fn atom_type(&self) -> Option<AtomType> {
+        match self.type_.trim().is_empty() {
+            true => self.label.parse().ok(),
+            false => self.type_.parse().ok(),
+        }
+    }
+
+    /// Returns the element of the atom type, see [`Self::atom_type`].
+    pub fn element(&self) -> Option<Element> {
+        self.atom_type().map(|atom_type| atom_type.element)
+    }
+}
+
+/// The atomic numbers of the last element of each period.
+const PERIOD_ENDS: [u8; 7] = [2, 10, 18, 36, 54, 86, 118];
+
+#[rustfmt::skip]
+const ELEMENTS: [Element; 118] = {
+    use Element::*;
+
+    [
+        H, He, Li, Be, B, C, N, O, F, Ne, Na, Mg, Al, Si, P, S, Cl, Ar, K, Ca, Sc, Ti, V, Cr, Mn,
+        Fe, Co, Ni, Cu, Zn, Ga, Ge, As, Se, Br, Kr, Rb, Sr, Y, Zr, Nb, Mo, Tc, Ru, Rh, Pd, Ag, Cd,
+        In, Sn, Sb, Te, I, Xe, Cs, Ba, La, Ce, Pr, Nd, Pm, Sm, Eu, Gd, Tb, Dy, Ho, Er, Tm, Yb, Lu,
+        Hf, Ta, W, Re, Os, Ir, Pt, Au, Hg, Tl, Pb, Bi, Po, At, Rn, Fr, Ra, Ac, Th, Pa, U, Np, Pu,
+        Am, Cm, Bk, Cf, Es, Fm, Md, No, Lr, Rf, Db, Sg, Bh, Hs, Mt, Ds, Rg, Cn, Nh, Fl, Mc, Lv, Ts,
+        Og,
+    ]
+};
+
+#[rustfmt::skip]
+const SYMBOLS: [&str; 118] = [
+    "H", "He", "Li", "Be", "B", "C", "N", "O", "F", "Ne", "Na", "Mg", "Al", "Si", "P", "S", "Cl",
+    "Ar", "K", "Ca", "Sc", "Ti", "V", "Cr", "Mn", "Fe", "Co", "Ni", "Cu", "Zn", "Ga", "Ge", "As",
+    "Se", "Br", "Kr", "Rb", "Sr", "Y", "Zr", "Nb", "Mo", "Tc", "Ru", "Rh", "Pd", "Ag", "Cd", "In",
+    "Sn", "Sb", "Te", "I", "Xe", "Cs", "Ba", "La", "Ce", "Pr", "Nd", "Pm", "Sm", "Eu", "Gd", "Tb",
+    "Dy", "Ho", "Er", "Tm", "Yb", "Lu", "Hf", "Ta", "W", "Re", "Os", "Ir", "Pt", "Au", "Hg", "Tl",
+    "Pb", "Bi", "Po", "At", "Rn", "Fr", "Ra", "Ac", "Th", "Pa", "U", "Np", "Pu", "Am", "Cm", "Bk",
+    "Cf", "Es", "Fm", "Md", "No", "Lr", "Rf", "Db", "Sg", "Bh", "Hs", "Mt", "Ds", "Rg", "Cn", "Nh",
+    "Fl", "Mc", "Lv", "Ts", "Og",
+];
+
+#[cfg(test)]
+mod test_element {
+    use super::{AtomType, Element, ToElement};
+    use crate::Atom;
+
+    fn parse(s: &str) -> (Element, Option<i8>, Option<u16>) {
+        let atom_type: AtomType = s.parse().unwrap();
+
+        (
+            atom_type.element,
+            atom_type.oxidation_state,
+            atom_type.mass_number,
+        )
+    }
+
+    #[test]
+    fn test_element() {
+        assert_eq!(Element::from_atomic_number(26), Some(Element::Fe));
+        assert_eq!(Element::from_atomic_number(118), Some(Element::Og));
+        assert_eq!(Element::from_atomic_number(0), None);
+        assert_eq!(Element::from_atomic_number(119), None);
+        assert_eq!(Element::Og.atomic_number(), 118);
+        assert_eq!(Element::from_symbol("cl"), Some(Element::Cl));
+        assert_eq!(Element::Cl.to_string(), "Cl");
+        assert!(Element::all()
+            .iter()
+            .enumerate()
+            .all(|(i, e)| e.atomic_number() as usize == i + 1));
+    }
+
+    #[test]
+    fn test_group_and_period() {
+        let group_and_period = |e: Element| (e.group(), e.period());
+
+        assert_eq!(group_and_period(Element::H), (Some(1), 1));
+        assert_eq!(group_and_period(Element::He), (Some(18), 1));
+        assert_eq!(group_and_period(Element::B), (Some(13), 2));
+        assert_eq!(group_and_period(Element::Ar), (Some(18), 3));
+        assert_eq!(group_and_period(Element::Fe), (Some(8), 4));
+        assert_eq!(group_and_period(Element::Xe), (Some(18), 5));
+        assert_eq!(group_and_period(Element::Ba), (Some(2), 6));
+        assert_eq!(group_and_period(Element::Gd), (None, 6));
+        assert_eq!(group_and_period(Element::Hf), (Some(4), 6));
+        assert_eq!(group_and_period(Element::U), (None, 7));
+        assert_eq!(group_and_period(Element::Og), (Some(18), 7));
+    }
+
+    #[test]
+    fn test_atom_type() {
+        assert_eq!(parse("Fe"), (Element::Fe, None, None));
+        assert_eq!(parse("Fe2+"), (Element::Fe, Some(2), None));
+        assert_eq!(parse("Fe+3"), (Element::Fe, Some(3), None));
+        assert_eq!(parse("Fe(3+)"), (Element::Fe, Some(3), None));
+        assert_eq!(parse("O2-"), (Element::O, Some(-2), None));
+        assert_eq!(parse("O1-"), (Element::O, Some(-1), None));
+        assert_eq!(parse("Cl-"), (Element::Cl, Some(-1), None));
+        assert_eq!(parse("Fe3"), (Element::Fe, None, None));
+        assert_eq!(parse("Ow"), (Element::O, None, None));
+        assert_eq!(parse("Hw1"), (Element::H, None, None));
+        assert_eq!(parse("CA"), (Element::Ca, None, None));
+        assert_eq!(parse("C12a"), (Element::C, None, None));
+        assert_eq!(parse("D"), (Element::H, None, Some(2)));
+        assert_eq!(parse("T"), (Element::H, None, Some(3)));
+        assert_eq!(parse("13C"), (Element::C, None, Some(13)));
+        assert_eq!(parse("^57Fe3+"), (Element::Fe, Some(3), Some(57)));
+
+        assert!("".parse::<AtomType>().is_err());
+        assert!("Xx".parse::<AtomType>().is_err());
+        assert!("2+".parse::<AtomType>().is_err());
+
+        assert_eq!(
+            AtomType {
+                element: Element::Fe,
+                oxidation_state: Some(-2),
+                mass_number: Some(56),
+            }
+            .to_string(),
+            "56Fe2-"
+        );
+        assert_eq!("O2-".parse::<AtomType>().unwrap().to_string(), "O2-");
+    }
+
+    #[test]
+    fn test_to_element() {
+        assert_eq!("Fe2+".to_element(), Some(Element::Fe));
+        assert_eq!(8u8.to_element(), Some(Element::O));
+        assert_eq!("Fe2+".to_string().to_element(), Some(Element::Fe));
+
+        let atom = Atom {
+            label: "Na1".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(atom.element(), Some(Element::Na));
+    }
+}
@@ -0,0 +1,310 @@
+use std::f64::consts::PI;
+
+use cgmath::{EuclideanSpace, Matrix, Matrix3, Matrix4, Point3, SquareMatrix, Vector3};
+
+use crate::{Atom, Cell};
+
+/// Anisotropic displacement ellipsoid of an atom in Cartesian coordinates.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThermalEllipsoid {
+    pub center: Point3<f64>,
+    /// Unit vectors along the principal axes.
+    pub axes: [Vector3<f64>; 3],
+    /// Mean-square displacements along the principal axes in Å², the eigenvalues of `U`.
+    pub eigenvalues: [f64; 3],
+    /// Semi-axis lengths in Å enclosing the atom with the requested probability. Axes with
+    /// non-positive eigenvalues have zero length.
+    pub semi_axes: [f64; 3],
+    /// `false` if `U` has non-positive eigenvalues and does not describe an ellipsoid.
+    pub is_positive_definite: bool,
+}
+
+impl ThermalEllipsoid {
+    /// Creates the ellipsoid of a Cartesian displacement tensor for a probability level, e.g.
+    /// `0.5` for the common 50% ellipsoids.
+    pub fn new(center: Point3<f64>, u_cart: Matrix3<f64>, probability: f64) -> Self {
+        let (eigenvalues, eigenvectors) = symmetric_eigen(u_cart);
+        let scale = probability_scale(probability);
+
+        Self {
+            center,
+            axes: [eigenvectors.x, eigenvectors.y, eigenvectors.z],
+            eigenvalues,
+            semi_axes: eigenvalues.map(|e| scale * e.max(0.0).sqrt()),
+            is_positive_definite: eigenvalues.iter().all(|e| *e > 0.0),
+        }
+    }
+
+    /// Returns the transformation matrix mapping the unit sphere onto the ellipsoid.
+    ///
+    /// The matrix does not mirror, so the winding order of meshes is kept.
+    pub fn transformation_matrix(&self) -> Matrix4<f32> {
+        let columns = [0, 1, 2].map(|i| (self.axes[i] * self.semi_axes[i]).cast::<f32>().unwrap());
+
+        let mut m = Matrix3::from_cols(columns[0], columns[1], columns[2]);
+
+        if m.determinant() < 0.0 {
+            m.x = -m.x;
+        }
+
+        let mut m = Matrix4::from(m);
+        m.w = self.center.to_vec().cast::<f32>().unwrap().extend(1.0);
+
+        m
+    }
+}
+
+impl Atom {
+    /// Returns the displacement ellipsoid in the Cartesian frame of
+    /// [`Cell::orthogonalization_matrix`] or `None` for multipole expansions.
+    pub fn thermal_ellipsoid(&self, cell: &Cell, probability: f64) -> Option<ThermalEllipsoid> {
+        let u = self.u_cif(cell)?;
+        let center = cell.orthogonalization_matrix() * Vector3::new(self.x, self.y, self.z);
+
+        Some(ThermalEllipsoid::new(
+            Point3::from_vec(center),
+            cell.u_cif_to_u_cart(u),
+            probability,
+        ))
+    }
+
+    /// Returns the displacement ellipsoid in the Y-up frame of
+    /// [`Cell::transformation_matrix_y_up`] or `None` for multipole expansions.
+    pub fn thermal_ellipsoid_y_up(
+        &self,
+        cell: &Cell,
+        probability: f64,
+    ) -> Option<ThermalEllipsoid> {
+        let u = self.u_cif(cell)?;
+
+        let m = cell.transformation_matrix_y_up().cast::<f64>()?;
+        let m = Matrix3::from_cols(m.x.truncate(), m.z.truncate(), m.y.truncate());
+        let a = m * Matrix3::from_diagonal(cell.reciprocal_lengths());
+
+        let center = m * Vector3::new(self.x, self.y, self.z);
+
+        Some(ThermalEllipsoid::new(
+            Point3::from_vec(center),
+            a * u * a.transpose(),
+            probability,
+        ))
+    }
+}
+
+/// Returns the scale of the root-mean-square displacements enclosing the trivariate normal
+/// distribution with the given probability.
+pub fn probability_scale(probability: f64) -> f64 {
+    // cumulative distribution of the chi distribution with three degrees of freedom
+    let cdf = |r: f64| erf(r / 2f64.sqrt()) - (2.0 / PI).sqrt() * r * (-0.5 * r * r).exp();
+
+    let (mut low, mut high) = (0.0, 10.0);
+
+    for _ in 0..100 {
+        let middle = 0.5 * (low + high);
+
+        match cdf(middle) < probability {
+            true => low = middle,
+            false => high = middle,
+        }
+    }
+
+    0.5 * (low + high)
+}
+
+/// Error function with an absolute error below `1.2e-7`.
+fn erf(x: f64) -> f64 {
+    // complementary error function from Numerical Recipes
+    let t = 1.0 / (1.0 + 0.5 * x.abs());
+    let tau = t
+        * (-x * x - 1.26551223
+            + t * (1.00002368
+                + t * (0.37409196
+                    + t * (0.09678418
+                        + t * (-0.18628806
+                            + t * (0.27886807
+                                + t * (-1.13520398
+                                    + t * (1.48851587 + t * (-0.82215223 + t * 0.17087277)))))))))
+            .exp();
+
+    match x >= 0.0 {
+        true => 1.0 - tau,
+        false => tau - 1.0,
+    }
+}
+
+/// Returns the eigenvalues in ascending order and the corresponding eigenvectors as columns of
+/// a symmetric matrix using Jacobi rotations.
+pub(crate) fn symmetric_eigen(m: Matrix3<f64>) -> ([f64; 3], Matrix3<f64>) {
+    let mut a = m;
+    let mut v = Matrix3::<f64>::identity();
+
+    for _ in 0..50 {
+        let off_diagonal = a.x.y.powi(2) + a.x.z.powi(2) + a.y.z.powi(2);
+
+        if off_diagonal < 1e-30 {
+            break;
+        }
+
+        for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+            if a[p][q].abs() < 1e-300 {
+                continue;
+            }
+
+            let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+            let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+            let c = 1.0 / (t * t + 1.0).sqrt();
+            let s = t * c;
+
+            let mut rotation = Matrix3::identity();
+            rotation[p][p] = c;
+            rotation[q][q] = c;
+            rotation[q][p] = s;
+            rotation[p][q] = -s;
+
+            a = rotation.transpose() * a * rotation;
+            v = v * rotation;
+        }
+    }
+
+    let mut order = [0, 1, 2];
+    order.sort_by(|i, j| a[*i][*i].total_cmp(&a[*j][*j]));
+
+    (
+        order.map(|i| a[i][i]),
+        Matrix3::from_cols(v[order[0]], v[order[1]], v[order[2]]),
+    )
+}
+
+#[cfg(test)]
+mod test_thermal_ellipsoid {
+    use cgmath::{
+        assert_abs_diff_eq, InnerSpace, Matrix3, Point3, SquareMatrix, Transform, Vector3,
+    };
+
+    use crate::{AdpType, Atom, Cell};
+
+    #[test]
+    fn test_probability_scale() {
+        assert_abs_diff_eq!(super::probability_scale(0.5), 1.5382, epsilon = 1e-4);
+        assert_abs_diff_eq!(super::probability_scale(0.99), 3.3682, epsilon = 1e-4);
+    }
+
+    #[test]
+    fn test_symmetric_eigen() {
+        let m = Matrix3::new(2.0, 1.0, 0.0, 1.0, 2.0, 0.0, 0.0, 0.0, 5.0);
+        let (eigenvalues, eigenvectors) = super::symmetric_eigen(m);
+
+        assert_abs_diff_eq!(eigenvalues[0], 1.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(eigenvalues[1], 3.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(eigenvalues[2], 5.0, epsilon = 1e-12);
+
+        for i in 0..3 {
+            assert_abs_diff_eq!(
+                m * eigenvectors[i],
+                eigenvectors[i] * eigenvalues[i],
+                epsilon = 1e-12
+            );
+        }
+    }
+
+    #[test]
+    fn test_thermal_ellipsoid() {
+        let cell = Cell {
+            a: 4.0,
+            b: 5.0,
+            c: 6.0,
+            alpha: 90.0,
+            beta: 90.0,
+            gamma: 90.0,
+            ..Default::default()
+        };
+
+        let atom = Atom {
+            x: 0.5,
+            adp_type: AdpType::Uani,
+            u11: 0.04,
+            u22: 0.01,
+            u33: 0.09,
+            ..Default::default()
+        };
+
+        let ellipsoid = atom.thermal_ellipsoid(&cell, 0.5).unwrap();
+
+        assert!(ellipsoid.is_positive_definite);
+        assert_abs_diff_eq!(
+            ellipsoid.center,
+            Point3::new(2.0, 0.0, 0.0),
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(ellipsoid.semi_axes[0], 0.1 * 1.5382, epsilon = 1e-4);
+        assert_abs_diff_eq!(ellipsoid.semi_axes[2], 0.3 * 1.5382, epsilon = 1e-4);
+        assert_abs_diff_eq!(ellipsoid.axes[2].z.abs(), 1.0, epsilon = 1e-12);
+
+        let m = ellipsoid.transformation_matrix();
+        let tip = m.transform_point(Point3::new(0.0, 0.0, 1.0));
+
+        assert!(m.determinant() > 0.0);
+
+        assert_abs_diff_eq!(
+            (tip - Point3::new(2.0, 0.0, 0.0)).magnitude(),
+            0.3 * 1.5382,
+            epsilon = 1e-4
+        );
+
+        // c points up in the viewer frame
+        let ellipsoid = atom.thermal_ellipsoid_y_up(&cell, 0.5).unwrap();
+
+        assert_abs_diff_eq!(ellipsoid.axes[2].y.abs(), 1.0, epsilon = 1e-6);
+    }
+
+    #[test]
+    fn test_right_handed_transformation() {
+        let ellipsoid = super::ThermalEllipsoid {
+            center: Point3::new(1.0, 2.0, 3.0),
+            axes: [Vector3::unit_x(), Vector3::unit_y(), -Vector3::unit_z()],
+            eigenvalues: [0.01, 0.04, 0.09],
+            semi_axes: [0.1, 0.2, 0.3],
+            is_positive_definite: true,
+        };
+
+        let m = ellipsoid.transformation_matrix();
+
+        assert!(m.determinant() > 0.0);
+        assert_abs_diff_eq!(
+            m.transform_vector(Vector3::unit_x()).magnitude(),
+            0.1,
+            epsilon = 1e-6
+        );
+        assert_abs_diff_eq!(
+            m.transform_point(Point3::new(0.0, 0.0, 1.0)),
+            Point3::new(1.0, 2.0, 2.7),
+            epsilon = 1e-6
+        );
+    }
+
+    #[test]
+    fn test_non_positive_definite() {
+        let atom = Atom {
+            adp_type: AdpType::Uani,
+            u11: 0.04,
+            u22: -0.01,
+            u33: 0.09,
+            ..Default::default()
+        };
+
+        let cell = Cell {
+            a: 4.0,
+            b: 4.0,
+            c: 4.0,
+            alpha: 90.0,
+            beta: 90.0,
+            gamma: 90.0,
+            ..Default::default()
+        };
+
+        let ellipsoid = atom.thermal_ellipsoid(&cell, 0.5).unwrap();
+
+        assert!(!ellipsoid.is_positive_definite);
+        assert_eq!(ellipsoid.semi_axes[0], 0.0);
+    }
+}
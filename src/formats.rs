@@ -0,0 +1,257 @@
+use crate::Cell;
+
+mod castep;
+mod espresso;
+mod lammps;
+mod mmcif;
+mod pdb;
+mod poscar;
+mod registry;
+#[cfg(feature = "symmetry")]
+mod shelx;
+mod xyz;
+
+pub use castep::CastepCell;
+pub use espresso::{PwInput, PwSpecies};
+pub use lammps::{LammpsAtomType, LammpsData, LammpsDumpFrame, LammpsDumpFrames};
+pub use mmcif::Mmcif;
+pub use pdb::{Pdb, PdbAtomInfo};
+pub use poscar::Poscar;
+pub use registry::{
+    format_names, read_structure, register_format, write_structure, StructureFileError,
+    StructureFormat,
+};
+#[cfg(feature = "symmetry")]
+pub use shelx::{Shelx, ShelxAtomInfo};
+pub use xyz::{XyzFrame, XyzFrames, XyzProperty, XyzValues};
+
+/// Bohr radius in Å.
+const BOHR: f64 = 0.529177210903;
+
+/// Error raised while parsing a structure file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line: Option<usize>,
+    message: String,
+}
+
+impl ParseError {
+    /// Creates an error for a 1-based line number.
+    pub fn new(line: usize, message: impl AsRef<str>) -> Self {
+        Self {
+            line: Some(line),
+            message: message.as_ref().to_string(),
+        }
+    }
+
+    /// Creates an error that is not tied to a line, e.g. for a truncated file.
+    pub fn without_line(message: impl AsRef<str>) -> Self {
+        Self {
+            line: None,
+            message: message.as_ref().to_string(),
+        }
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parses whitespace separated floats, reporting the line on failure.
+fn parse_floats<const N: usize>(
+    line_number: usize,
+    tokens: &[&str],
+    what: &str,
+) -> Result<[f64; N], ParseError> {
+    if tokens.len() < N {
+        return Err(ParseError::new(
+            line_number,
+            format!("Expected {} values for {}", N, what),
+        ));
+    }
+
+    let mut values = [0.0; N];
+
+    for (value, token) in values.iter_mut().zip(tokens) {
+        *value = token.parse().map_err(|_| {
+            ParseError::new(
+                line_number,
+                format!("Invalid number in {}: {}", what, token),
+            )
+        })?;
+    }
+
+    Ok(values)
+}
+
+/// Creates a cell from its parameters and a space group symbol in either notation, e.g.
+/// `P 21 21 21` or `P2_12_12_1`.
+fn cell_from_parameters(parameters: [f64; 6], space_group: &str) -> Cell {
+    let [a, b, c, alpha, beta, gamma] = parameters;
+
+    let mut cell = Cell {
+        a,
+        b,
+        c,
+        alpha,
+        beta,
+        gamma,
+        space_group: compact_space_group_symbol(space_group),
+        ..Default::default()
+    };
+    cell.volume = cell.calculate_volume();
+
+    #[cfg(feature = "symmetry")]
+    {
+        use crate::IntoSpaceGroupNumber;
+
+        let short_monoclinic = match cell.space_group.split_at_checked(1) {
+            Some((lattice, axis)) => format!("{}1{}1", lattice, axis),
+            None => String::new(),
+        };
+
+        cell.space_group_number = cell
+            .space_group
+            .as_str()
+            .into_space_group_number()
+            .or_else(|| short_monoclinic.as_str().into_space_group_number())
+            .unwrap_or_default();
+    }
+
+    cell
+}
+
+/// Converts a space group symbol with spaces between the axes, e.g. `P 21/c` or `H 3`, into
+/// the compact form of the symmetry tables, e.g. `P2_1/c` or `R3`.
+fn compact_space_group_symbol(symbol: &str) -> String {
+    let mut tokens = symbol.split_whitespace();
+
+    let lattice = match tokens.next() {
+        Some("H") => "R",
+        Some(lattice) => lattice,
+        None => return String::new(),
+    };
+
+    let axes = tokens
+        .map(|token| {
+            let (sign, rest) = match token.strip_prefix('-') {
+                Some(rest) => ("-", rest),
+                None => ("", token),
+            };
+
+            let digits = rest.chars().take_while(|c| c.is_ascii_digit()).count();
+
+            match digits == 2 && !rest.contains('_') {
+                true => format!("{}{}_{}", sign, &rest[..1], &rest[1..]),
+                false => token.to_string(),
+            }
+        })
+        .collect::<String>();
+
+    format!("{}{}", lattice, axes)
+}
+
+/// Converts a compact space group symbol, e.g. `P2_12_12_1`, into the notation with spaces
+/// used by PDB and mmCIF files, e.g. `P 21 21 21`.
+fn spaced_space_group_symbol(symbol: &str) -> String {
+    let mut chars = symbol.chars().peekable();
+    let mut tokens = chars
+        .next()
+        .map(|c| vec![c.to_string()])
+        .unwrap_or_default();
+
+    while let Some(c) = chars.next() {
+        let mut token = c.to_string();
+
+        if c == '-' || c.is_ascii_digit() {
+            if c == '-' {
+                token.extend(chars.next_if(|c| c.is_ascii_digit()));
+            }
+
+            if chars.next_if_eq(&'_').is_some() {
+                token.extend(chars.next_if(|c| c.is_ascii_digit()));
+            }
+
+            if let Some(slash) = chars.next_if_eq(&'/') {
+                token.push(slash);
+                token.extend(chars.next_if(|c| c.is_alphabetic()));
+            }
+        }
+
+        tokens.push(token);
+    }
+
+    tokens.join(" ")
+}
+
+/// Removes a comment starting with one of `markers` that is not inside a quoted value, e.g.
+/// `! comment` but not `prefix = 'a#b'`.
+fn strip_comment<'a>(line: &'a str, markers: &[char]) -> &'a str {
+    let mut quote = None;
+
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (c, None) if markers.contains(&c) => return &line[..i],
+            _ => {}
+        }
+    }
+
+    line
+}
+
+/// Parses a number and drops its standard uncertainty, e.g. `5.431(2)`.
+fn parse_float_with_uncertainty(token: &str) -> Option<f64> {
+    token.split('(').next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod test_formats {
+    #[test]
+    fn test_space_group_symbols() {
+        for (spaced, compact) in [
+            ("P 21 21 21", "P2_12_12_1"),
+            ("P 1 21/c 1", "P12_1/c1"),
+            ("F m -3 m", "Fm-3m"),
+            ("I 41/a m d", "I4_1/amd"),
+            ("P -1", "P-1"),
+            ("P 42/m n m", "P4_2/mnm"),
+        ] {
+            assert_eq!(super::compact_space_group_symbol(spaced), compact);
+            assert_eq!(super::spaced_space_group_symbol(compact), spaced);
+        }
+
+        assert_eq!(super::compact_space_group_symbol("H 3 2"), "R32");
+    }
+
+    #[cfg(feature = "symmetry")]
+    #[test]
+    fn test_cell_from_parameters() {
+        let parameters = [5.0, 6.0, 7.0, 90.0, 100.0, 90.0];
+
+        assert_eq!(
+            super::cell_from_parameters(parameters, "P 21").space_group_number,
+            4
+        );
+        assert_eq!(
+            super::cell_from_parameters(parameters, "P 1 21 1").space_group_number,
+            4
+        );
+    }
+}
@@ -0,0 +1,446 @@
+use std::str::FromStr;
+
+use cgmath::{Matrix3, Matrix4, SquareMatrix, Vector3};
+
+use super::{parse_floats, strip_comment, ParseError, BOHR};
+use crate::{Atom, Atoms, Cell, Phase};
+
+/// A CASTEP `.cell` file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CastepCell {
+    /// Cell and all atoms of the unit cell in P1.
+    pub phase: Phase,
+    /// Operations of the `SYMMETRY_OPS` block in fractional coordinates.
+    pub symmetry_operations: Vec<Matrix4<f64>>,
+    /// Keywords outside of blocks, e.g. `KPOINT_MP_GRID 4 4 4`, in file order.
+    pub keywords: Vec<String>,
+}
+
+impl CastepCell {
+    /// Parses the `LATTICE_ABC` or `LATTICE_CART`, `POSITIONS_FRAC` or `POSITIONS_ABS` and
+    /// `SYMMETRY_OPS` blocks of a `.cell` file. Other blocks are ignored.
+    ///
+    /// Atom types are the species without their label, e.g. `Fe` for `Fe:1`.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let mut cell = None;
+        let mut positions: Option<Vec<(String, Vector3<f64>)>> = None;
+        let mut is_cartesian = false;
+        let mut symmetry_operations = Vec::new();
+        let mut keywords = Vec::new();
+
+        let lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, strip_comment(line, &['!', '#']).trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut index = 0;
+
+        while index < lines.len() {
+            let (line_number, line) = lines[index];
+            index += 1;
+
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+            if !tokens[0].eq_ignore_ascii_case("%BLOCK") {
+                keywords.push(line.to_string());
+                continue;
+            }
+
+            let name = tokens
+                .get(1)
+                .ok_or_else(|| ParseError::new(line_number, "Missing block name"))?
+                .to_ascii_uppercase();
+
+            let start = index;
+
+            while index < lines.len()
+                && !lines[index].1.to_ascii_uppercase().starts_with("%ENDBLOCK")
+            {
+                index += 1;
+            }
+
+            if index == lines.len() {
+                return Err(ParseError::new(
+                    line_number,
+                    format!("Unterminated block {}", name),
+                ));
+            }
+
+            let mut body = &lines[start..index];
+            index += 1;
+
+            // optional unit line
+            let mut scale = 1.0;
+
+            if let Some((_, unit)) = body.first() {
+                if let Some(unit_scale) = unit_scale(unit) {
+                    scale = unit_scale;
+                    body = &body[1..];
+                }
+            }
+
+            match name.as_str() {
+                "LATTICE_ABC" => {
+                    let [(n1, l1), (n2, l2)] = body[..] else {
+                        return Err(ParseError::new(
+                            line_number,
+                            "Expected two lines in LATTICE_ABC",
+                        ));
+                    };
+
+                    let lengths = parse_floats::<3>(
+                        n1,
+                        &l1.split_whitespace().collect::<Vec<_>>(),
+                        "lengths",
+                    )?;
+                    let angles = parse_floats::<3>(
+                        n2,
+                        &l2.split_whitespace().collect::<Vec<_>>(),
+                        "angles",
+                    )?;
+
+                    let mut abc = Cell {
+                        a: lengths[0] * scale,
+                        b: lengths[1] * scale,
+                        c: lengths[2] * scale,
+                        alpha: angles[0],
+                        beta: angles[1],
+                        gamma: angles[2],
+                        space_group: "P1".to_string(),
+                        space_group_number: 1,
+                        ..Default::default()
+                    };
+                    abc.volume = abc.calculate_volume();
+
+                    cell = Some((abc, None));
+                }
+                "LATTICE_CART" => {
+                    if body.len() != 3 {
+                        return Err(ParseError::new(
+                            line_number,
+                            "Expected three lattice vectors",
+                        ));
+                    }
+
+                    let mut vectors = [Vector3::new(0.0, 0.0, 0.0); 3];
+
+                    for (vector, (line_number, line)) in vectors.iter_mut().zip(body) {
+                        let tokens = line.split_whitespace().collect::<Vec<_>>();
+                        let [x, y, z] = parse_floats::<3>(*line_number, &tokens, "lattice vector")?;
+                        *vector = Vector3::new(x, y, z) * scale;
+                    }
+
+                    cell = Some((
+                        Cell::from_lattice_vectors(vectors),
+                        Some(Matrix3::from_cols(vectors[0], vectors[1], vectors[2])),
+                    ));
+                }
+                "POSITIONS_FRAC" | "POSITIONS_ABS" => {
+                    let mut atoms = Vec::with_capacity(body.len());
+
+                    for (line_number, line) in body {
+                        let tokens = line.split_whitespace().collect::<Vec<_>>();
+                        let [x, y, z] = parse_floats::<3>(*line_number, &tokens[1..], "position")?;
+
+                        atoms.push((tokens[0].to_string(), Vector3::new(x, y, z) * scale));
+                    }
+
+                    is_cartesian = name == "POSITIONS_ABS";
+                    positions = Some(atoms);
+                }
+                "SYMMETRY_OPS" => {
+                    if body.len() % 4 != 0 {
+                        return Err(ParseError::new(
+                            line_number,
+                            "SYMMETRY_OPS needs four lines per operation",
+                        ));
+                    }
+
+                    for chunk in body.chunks(4) {
+                        let mut operation = Matrix4::identity();
+
+                        for (row, (line_number, line)) in chunk.iter().enumerate() {
+                            let tokens = line.split_whitespace().collect::<Vec<_>>();
+                            let values =
+                                parse_floats::<3>(*line_number, &tokens, "symmetry operation")?;
+
+                            for (column, value) in values.iter().enumerate() {
+                                match row {
+                                    3 => operation.w[column] = *value,
+                                    _ => operation[column][row] = *value,
+                                }
+                            }
+                        }
+
+                        symmetry_operations.push(operation);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let (cell, lattice) =
+            cell.ok_or_else(|| ParseError::without_line("Missing lattice block"))?;
+        let positions =
+            positions.ok_or_else(|| ParseError::without_line("Missing positions block"))?;
+
+        let inverse_lattice = lattice
+            .unwrap_or_else(|| cell.orthogonalization_matrix())
+            .invert()
+            .ok_or_else(|| ParseError::without_line("Lattice vectors are linearly dependent"))?;
+
+        let mut counts: Vec<(String, usize)> = Vec::new();
+
+        let atoms = positions
+            .into_iter()
+            .map(|(species, position)| {
+                let type_ = species.split(':').next().unwrap_or_default().to_string();
+
+                let position = match is_cartesian {
+                    true => inverse_lattice * position,
+                    false => position,
+                };
+
+                let index = match counts.iter_mut().find(|(t, _)| *t == type_) {
+                    Some((_, count)) => {
+                        *count += 1;
+                        *count
+                    }
+                    None => {
+                        counts.push((type_.clone(), 1));
+                        1
+                    }
+                };
+
+                Atom {
+                    label: format!("{}{}", type_, index),
+                    type_,
+                    x: position.x,
+                    y: position.y,
+                    z: position.z,
+                    occupancy: 1.0,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            phase: Phase {
+                cell,
+                atoms: Atoms(atoms),
+            },
+            symmetry_operations,
+            keywords,
+        })
+    }
+
+    /// Creates a `.cell` file of a phase expanded to P1 with the operations of its space group.
+    #[cfg(feature = "symmetry")]
+    pub fn from_phase(phase: &Phase) -> Self {
+        Self {
+            phase: phase.expand_to_p1(),
+            symmetry_operations: phase
+                .cell
+                .symmetry_operations()
+                .cloned()
+                .unwrap_or_default(),
+            keywords: Vec::new(),
+        }
+    }
+}
+
+/// Returns the length in Å of a unit line of a block, or `None` if the line is no known unit.
+fn unit_scale(unit: &str) -> Option<f64> {
+    match unit.to_ascii_lowercase().as_str() {
+        "ang" => Some(1.0),
+        "bohr" | "a0" => Some(BOHR),
+        "nm" => Some(10.0),
+        "m" => Some(1e10),
+        "cm" => Some(1e8),
+        _ => None,
+    }
+}
+
+impl FromStr for CastepCell {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for CastepCell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cell = &self.phase.cell;
+
+        writeln!(f, "%BLOCK LATTICE_ABC")?;
+        writeln!(f, "ang")?;
+        writeln!(f, " {:16.10} {:16.10} {:16.10}", cell.a, cell.b, cell.c)?;
+        writeln!(
+            f,
+            " {:16.10} {:16.10} {:16.10}",
+            cell.alpha, cell.beta, cell.gamma
+        )?;
+        writeln!(f, "%ENDBLOCK LATTICE_ABC")?;
+        writeln!(f)?;
+        writeln!(f, "%BLOCK POSITIONS_FRAC")?;
+
+        for atom in self.phase.atoms.iter() {
+            writeln!(
+                f,
+                " {:<4} {:16.12} {:16.12} {:16.12}",
+                atom.type_, atom.x, atom.y, atom.z
+            )?;
+        }
+
+        writeln!(f, "%ENDBLOCK POSITIONS_FRAC")?;
+
+        if !self.symmetry_operations.is_empty() {
+            writeln!(f)?;
+            writeln!(f, "%BLOCK SYMMETRY_OPS")?;
+
+            for operation in &self.symmetry_operations {
+                for row in 0..3 {
+                    writeln!(
+                        f,
+                        " {:16.12} {:16.12} {:16.12}",
+                        operation.x[row], operation.y[row], operation.z[row]
+                    )?;
+                }
+
+                writeln!(
+                    f,
+                    " {:16.12} {:16.12} {:16.12}",
+                    operation.w.x, operation.w.y, operation.w.z
+                )?;
+            }
+
+            writeln!(f, "%ENDBLOCK SYMMETRY_OPS")?;
+        }
+
+        if !self.keywords.is_empty() {
+            writeln!(f)?;
+
+            for keyword in &self.keywords {
+                writeln!(f, "{}", keyword)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_castep_cell {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::CastepCell;
+
+    const CELL: &str = "\
+%BLOCK LATTICE_CART
+bohr
+  10.0 0.0 0.0
+  0.0 10.0 0.0
+  0.0 0.0 10.0
+%ENDBLOCK LATTICE_CART
+
+%block positions_abs
+ang
+Fe:1 0.0 0.0 0.0
+Fe:2 2.645886 2.645886 2.645886 SPIN=-2.0
+%endblock positions_abs
+
+%BLOCK SYMMETRY_OPS
+  -1 0 0
+  0 -1 0
+  0 0 1
+  0.5 0 0.5
+%ENDBLOCK SYMMETRY_OPS
+
+KPOINT_MP_GRID 4 4 4 # comment
+COMMENT 'run #2' ! note
+";
+
+    #[test]
+    fn test_parse() {
+        let cell: CastepCell = CELL.parse().unwrap();
+
+        assert_abs_diff_eq!(cell.phase.cell.a, 10.0 * super::BOHR, epsilon = 1e-10);
+        assert_eq!(cell.phase.atoms[1].type_, "Fe");
+        assert_eq!(cell.phase.atoms[1].label, "Fe2");
+        assert_abs_diff_eq!(cell.phase.atoms[1].x, 0.5, epsilon = 1e-6);
+        assert_eq!(cell.symmetry_operations.len(), 1);
+        assert_eq!(cell.symmetry_operations[0].x.x, -1.0);
+        assert_eq!(cell.symmetry_operations[0].w.z, 0.5);
+        assert_eq!(
+            cell.keywords,
+            vec!["KPOINT_MP_GRID 4 4 4", "COMMENT 'run #2'"]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = CELL
+            .replace("0.0 10.0 0.0", "0.0 1x.0 0.0")
+            .parse::<CastepCell>()
+            .unwrap_err();
+        assert_eq!(error.line(), Some(4));
+
+        assert!(CELL
+            .replace("%ENDBLOCK SYMMETRY_OPS", "")
+            .parse::<CastepCell>()
+            .is_err());
+
+        // a misspelled unit is no unit line
+        let error = CELL
+            .replace("\nbohr\n", "\nbohrs\n")
+            .parse::<CastepCell>()
+            .unwrap_err();
+        assert_eq!(error.line(), Some(1));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let cell: CastepCell = CELL.parse().unwrap();
+        let reread: CastepCell = cell.to_string().parse().unwrap();
+
+        assert_eq!(reread.symmetry_operations, cell.symmetry_operations);
+        assert_eq!(reread.keywords, cell.keywords);
+        assert_abs_diff_eq!(reread.phase.cell.c, cell.phase.cell.c, epsilon = 1e-8);
+        assert_abs_diff_eq!(reread.phase.atoms[1].z, 0.5, epsilon = 1e-6);
+    }
+
+    #[cfg(feature = "symmetry")]
+    #[test]
+    fn test_from_phase() {
+        use crate::{Atom, Atoms, Cell, Phase};
+
+        let phase = Phase {
+            cell: Cell {
+                a: 4.0,
+                b: 4.0,
+                c: 4.0,
+                alpha: 90.0,
+                beta: 90.0,
+                gamma: 90.0,
+                space_group_number: 221,
+                ..Default::default()
+            },
+            atoms: Atoms(vec![Atom {
+                label: "Cs1".to_string(),
+                type_: "Cs".to_string(),
+                occupancy: 1.0,
+                ..Default::default()
+            }]),
+        };
+
+        let cell = CastepCell::from_phase(&phase);
+        let written = cell.to_string();
+
+        assert!(written.contains("%BLOCK SYMMETRY_OPS"));
+        assert_eq!(cell.symmetry_operations.len(), 48);
+        assert_eq!(written.parse::<CastepCell>().unwrap().phase.atoms.len(), 1);
+    }
+}
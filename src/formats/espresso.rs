@@ -0,0 +1,632 @@
+use std::str::FromStr;
+
+use cgmath::{Matrix3, SquareMatrix, Vector3};
+
+use super::{parse_floats, strip_comment, ParseError, BOHR};
+use crate::{Atom, AtomType, Atoms, Cell, Phase};
+
+/// Cards of `pw.x` input files. Lines starting with them end the preceding card.
+const CARDS: [&str; 11] = [
+    "ATOMIC_SPECIES",
+    "ATOMIC_POSITIONS",
+    "K_POINTS",
+    "ADDITIONAL_K_POINTS",
+    "CELL_PARAMETERS",
+    "CONSTRAINTS",
+    "OCCUPATIONS",
+    "ATOMIC_VELOCITIES",
+    "ATOMIC_FORCES",
+    "SOLVENTS",
+    "HUBBARD",
+];
+
+/// A Quantum ESPRESSO `pw.x` input file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PwInput {
+    pub phase: Phase,
+    /// Namelists, e.g. `CONTROL` or `SYSTEM`, with their parameters in file order. `ibrav`,
+    /// `nat` and `ntyp` are derived from the phase when writing.
+    pub namelists: Vec<(String, Vec<(String, String)>)>,
+    pub species: Vec<PwSpecies>,
+    /// Index into `species` for each atom of the phase.
+    pub atom_species: Vec<usize>,
+    /// Lines of the `K_POINTS` card including its header.
+    pub k_points: Vec<String>,
+}
+
+/// An entry of the `ATOMIC_SPECIES` card.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PwSpecies {
+    /// Label of the species, e.g. `Fe1` for one of two magnetic sublattices.
+    pub name: String,
+    /// Mass in u.
+    pub mass: f64,
+    pub pseudopotential: String,
+}
+
+impl PwInput {
+    /// Parses the namelists and the `ATOMIC_SPECIES`, `CELL_PARAMETERS`, `ATOMIC_POSITIONS`
+    /// and `K_POINTS` cards of a `pw.x` input file with `ibrav = 0`.
+    ///
+    /// Atom types are the species labels without trailing digits and charges, e.g. `Fe` for
+    /// `Fe1`. Other cards are ignored.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let lines = s
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, strip_comment(line, &['!', '#']).trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect::<Vec<_>>();
+
+        let mut input = Self::default();
+        let mut lattice = None;
+        let mut positions = Vec::new();
+        let mut position_unit = String::new();
+        let mut index = 0;
+
+        while index < lines.len() {
+            let (line_number, line) = lines[index];
+            index += 1;
+
+            if let Some(header) = line.strip_prefix('&') {
+                let (name, rest) = header
+                    .split_once(char::is_whitespace)
+                    .unwrap_or((header, ""));
+                let mut parameters = Vec::new();
+                let (mut entries_line, mut entries) = (line_number, rest);
+
+                // entries may follow the name and precede the terminating `/` on the same line
+                loop {
+                    let (assignments, is_terminated) = split_terminator(entries);
+
+                    for assignment in split_assignments(assignments) {
+                        let (key, value) = assignment
+                            .split_once('=')
+                            .ok_or_else(|| ParseError::new(entries_line, "Expected key = value"))?;
+
+                        parameters.push((key.trim().to_string(), value.trim().to_string()));
+                    }
+
+                    if is_terminated {
+                        break;
+                    }
+
+                    (entries_line, entries) = *lines.get(index).ok_or_else(|| {
+                        ParseError::new(line_number, format!("Unterminated namelist {}", name))
+                    })?;
+                    index += 1;
+                }
+
+                input
+                    .namelists
+                    .push((name.to_ascii_uppercase(), parameters));
+                continue;
+            }
+
+            let mut tokens = line.split_whitespace();
+            let card = tokens.next().unwrap_or_default().to_ascii_uppercase();
+            let option = tokens
+                .next()
+                .unwrap_or_default()
+                .trim_matches(['(', ')', '{', '}'])
+                .to_ascii_lowercase();
+
+            // lines of the card up to the next card
+            let start = index;
+
+            while index < lines.len() && !is_card(lines[index].1) {
+                index += 1;
+            }
+
+            let body = &lines[start..index];
+
+            match card.as_str() {
+                "ATOMIC_SPECIES" => {
+                    for (line_number, line) in body {
+                        let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+                        let [name, mass, pseudopotential] = tokens[..] else {
+                            return Err(ParseError::new(*line_number, "Invalid species"));
+                        };
+
+                        input.species.push(PwSpecies {
+                            name: name.to_string(),
+                            mass: mass.parse().map_err(|_| {
+                                ParseError::new(*line_number, format!("Invalid mass: {}", mass))
+                            })?,
+                            pseudopotential: pseudopotential.to_string(),
+                        });
+                    }
+                }
+                "CELL_PARAMETERS" => {
+                    let unit = match option.as_str() {
+                        "" if parameter(&input.namelists, "SYSTEM", "celldm(1)").is_some() => {
+                            "alat"
+                        }
+                        "" => "bohr",
+                        option => option,
+                    };
+                    let scale = unit_scale(unit, &input.namelists, line_number)?;
+                    let mut vectors = [Vector3::new(0.0, 0.0, 0.0); 3];
+
+                    for (vector, (line_number, line)) in vectors.iter_mut().zip(body) {
+                        let tokens = line.split_whitespace().collect::<Vec<_>>();
+                        let [x, y, z] = parse_floats::<3>(*line_number, &tokens, "lattice vector")?;
+                        *vector = Vector3::new(x, y, z) * scale;
+                    }
+
+                    if body.len() < 3 {
+                        return Err(ParseError::new(
+                            line_number,
+                            "Expected three lattice vectors",
+                        ));
+                    }
+
+                    lattice = Some(vectors);
+                }
+                "ATOMIC_POSITIONS" => {
+                    for (line_number, line) in body {
+                        let tokens = line.split_whitespace().collect::<Vec<_>>();
+                        let [x, y, z] = parse_floats::<3>(
+                            *line_number,
+                            tokens.get(1..).unwrap_or_default(),
+                            "position",
+                        )?;
+
+                        positions.push((
+                            *line_number,
+                            tokens[0].to_string(),
+                            Vector3::new(x, y, z),
+                        ));
+                    }
+
+                    position_unit = match option.as_str() {
+                        "" => "alat".to_string(),
+                        option => option.to_string(),
+                    };
+                }
+                "K_POINTS" => {
+                    input.k_points = std::iter::once(line)
+                        .chain(body.iter().map(|(_, line)| *line))
+                        .map(|line| line.to_string())
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(ibrav) = parameter(&input.namelists, "SYSTEM", "ibrav") {
+            if ibrav.parse::<i32>() != Ok(0) {
+                return Err(ParseError::without_line(format!(
+                    "Only ibrav = 0 is supported, found {}",
+                    ibrav
+                )));
+            }
+        }
+
+        let vectors = lattice.ok_or_else(|| ParseError::without_line("Missing CELL_PARAMETERS"))?;
+        let lattice = Matrix3::from_cols(vectors[0], vectors[1], vectors[2]);
+        let inverse_lattice = lattice
+            .invert()
+            .ok_or_else(|| ParseError::without_line("Lattice vectors are linearly dependent"))?;
+
+        let mut atoms = Vec::with_capacity(positions.len());
+
+        for (line_number, name, position) in positions {
+            let species = input
+                .species
+                .iter()
+                .position(|s| s.name == name)
+                .ok_or_else(|| {
+                    ParseError::new(line_number, format!("Unknown species: {}", name))
+                })?;
+
+            let position = match position_unit.as_str() {
+                "crystal" => position,
+                unit => {
+                    inverse_lattice * position * unit_scale(unit, &input.namelists, line_number)?
+                }
+            };
+
+            let type_ = element_of_species(&name);
+
+            atoms.push(Atom {
+                label: format!("{}{}", type_, atoms.len() + 1),
+                type_,
+                x: position.x,
+                y: position.y,
+                z: position.z,
+                occupancy: 1.0,
+                ..Default::default()
+            });
+            input.atom_species.push(species);
+        }
+
+        input.phase = Phase {
+            cell: Cell::from_lattice_vectors(vectors),
+            atoms: Atoms(atoms),
+        };
+
+        Ok(input)
+    }
+
+    /// Creates an `scf` input of a phase expanded to P1 with masses from [`crate::ATOMIC_MASSES`]
+    /// and a Monkhorst-Pack grid with at least 20 Å per direction.
+    ///
+    /// The cutoffs and pseudopotential file names, `<type>.upf`, are placeholders to adapt.
+    #[cfg(all(feature = "data", feature = "symmetry"))]
+    pub fn from_phase(phase: &Phase) -> Self {
+        let p1 = phase.expand_to_p1();
+
+        let mut species: Vec<PwSpecies> = Vec::new();
+        let mut atom_species = Vec::with_capacity(p1.atoms.len());
+
+        for atom in p1.atoms.iter() {
+            let index = match species.iter().position(|s| s.name == atom.type_) {
+                Some(index) => index,
+                None => {
+                    let mass = crate::ATOMIC_MASSES.get(&atom.type_).copied();
+
+                    if mass.is_none() {
+                        log::warn!("Unknown atom type: {}. Using a mass of 1.0.", atom.type_);
+                    }
+
+                    species.push(PwSpecies {
+                        name: atom.type_.clone(),
+                        mass: mass.unwrap_or(1.0) as f64,
+                        pseudopotential: format!("{}.upf", atom.type_),
+                    });
+                    species.len() - 1
+                }
+            };
+
+            atom_species.push(index);
+        }
+
+        let cell = &p1.cell;
+        let grid = [cell.a, cell.b, cell.c].map(|length| (20.0 / length).ceil().max(1.0));
+
+        let parameters = |parameters: &[(&str, &str)]| {
+            parameters
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        Self {
+            namelists: vec![
+                (
+                    "CONTROL".to_string(),
+                    parameters(&[("calculation", "'scf'"), ("pseudo_dir", "'./'")]),
+                ),
+                (
+                    "SYSTEM".to_string(),
+                    parameters(&[("ecutwfc", "40"), ("ecutrho", "320")]),
+                ),
+                ("ELECTRONS".to_string(), Vec::new()),
+            ],
+            species,
+            atom_species,
+            k_points: vec![
+                "K_POINTS automatic".to_string(),
+                format!("{} {} {} 0 0 0", grid[0], grid[1], grid[2]),
+            ],
+            phase: p1,
+        }
+    }
+}
+
+fn is_card(line: &str) -> bool {
+    let first = line.split_whitespace().next().unwrap_or_default();
+
+    CARDS.iter().any(|card| first.eq_ignore_ascii_case(card)) || line.starts_with('&')
+}
+
+/// Splits a namelist line into assignments at commas outside of quotes.
+fn split_assignments(line: &str) -> Vec<&str> {
+    let mut assignments = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            (',', None) => {
+                assignments.push(&line[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+
+    assignments.push(&line[start..]);
+    assignments.retain(|a| !a.trim().is_empty());
+
+    assignments
+}
+
+/// Splits a namelist line at a terminating `/` outside of quotes and returns the part before
+/// it and whether the line terminates the namelist.
+fn split_terminator(line: &str) -> (&str, bool) {
+    let mut quote = None;
+
+    for (i, c) in line.char_indices() {
+        match (c, quote) {
+            ('\'' | '"', None) => quote = Some(c),
+            (c, Some(q)) if c == q => quote = None,
+            ('/', None) => return (&line[..i], true),
+            _ => {}
+        }
+    }
+
+    (line, false)
+}
+
+fn parameter<'a>(
+    namelists: &'a [(String, Vec<(String, String)>)],
+    namelist: &str,
+    key: &str,
+) -> Option<&'a str> {
+    namelists
+        .iter()
+        .filter(|(name, _)| name.eq_ignore_ascii_case(namelist))
+        .flat_map(|(_, parameters)| parameters)
+        .find(|(k, _)| k.eq_ignore_ascii_case(key))
+        .map(|(_, v)| v.as_str())
+}
+
+/// Returns the length in Å of the unit of a card option.
+fn unit_scale(
+    unit: &str,
+    namelists: &[(String, Vec<(String, String)>)],
+    line_number: usize,
+) -> Result<f64, ParseError> {
+    let float = |key: &str| {
+        parameter(namelists, "SYSTEM", key)
+            .and_then(|v| v.replace(['d', 'D'], "e").parse::<f64>().ok())
+    };
+
+    match unit {
+        "angstrom" => Ok(1.0),
+        "bohr" => Ok(BOHR),
+        "alat" => float("celldm(1)")
+            .map(|alat| alat * BOHR)
+            .or_else(|| float("A"))
+            .ok_or_else(|| ParseError::new(line_number, "alat units without celldm(1) or A")),
+        unit => Err(ParseError::new(
+            line_number,
+            format!("Unsupported unit: {}", unit),
+        )),
+    }
+}
+
+/// Returns the atom type of a species label, e.g. `Fe` for `Fe1`, `FE1` or `Fe_up`, or the
+/// label itself if it names no element.
+fn element_of_species(name: &str) -> String {
+    match name.parse::<AtomType>() {
+        Ok(atom_type) => AtomType {
+            oxidation_state: None,
+            ..atom_type
+        }
+        .to_string(),
+        Err(_) => name.to_string(),
+    }
+}
+
+impl FromStr for PwInput {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for PwInput {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // the cell is written as CELL_PARAMETERS in Å, which pw.x rejects together with
+        // celldm or the lattice parameters
+        let derived = [
+            "ibrav", "nat", "ntyp", "celldm", "A", "B", "C", "cosAB", "cosAC", "cosBC",
+        ];
+
+        let mut namelists = self.namelists.clone();
+
+        if !namelists.iter().any(|(name, _)| name == "SYSTEM") {
+            namelists.push(("SYSTEM".to_string(), Vec::new()));
+        }
+
+        for (name, parameters) in &namelists {
+            writeln!(f, "&{}", name)?;
+
+            if name == "SYSTEM" {
+                writeln!(f, "  ibrav = 0")?;
+                writeln!(f, "  nat = {}", self.phase.atoms.len())?;
+                writeln!(f, "  ntyp = {}", self.species.len())?;
+            }
+
+            for (key, value) in parameters {
+                let name = key.split('(').next().unwrap_or_default().trim();
+
+                if !derived.iter().any(|d| name.eq_ignore_ascii_case(d)) {
+                    writeln!(f, "  {} = {}", key, value)?;
+                }
+            }
+
+            writeln!(f, "/")?;
+        }
+
+        writeln!(f, "ATOMIC_SPECIES")?;
+
+        for species in &self.species {
+            writeln!(
+                f,
+                "{} {:.4} {}",
+                species.name, species.mass, species.pseudopotential
+            )?;
+        }
+
+        let m = self.phase.cell.orthogonalization_matrix();
+
+        writeln!(f, "CELL_PARAMETERS angstrom")?;
+
+        for v in [m.x, m.y, m.z] {
+            writeln!(f, " {:18.12} {:18.12} {:18.12}", v.x, v.y, v.z)?;
+        }
+
+        writeln!(f, "ATOMIC_POSITIONS crystal")?;
+
+        for (i, atom) in self.phase.atoms.iter().enumerate() {
+            let name = match self.atom_species.get(i).and_then(|s| self.species.get(*s)) {
+                Some(species) => &species.name,
+                None => &atom.type_,
+            };
+
+            writeln!(
+                f,
+                "{} {:16.12} {:16.12} {:16.12}",
+                name, atom.x, atom.y, atom.z
+            )?;
+        }
+
+        for line in &self.k_points {
+            writeln!(f, "{}", line)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_pw_input {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::PwInput;
+
+    const INPUT: &str = "\
+&CONTROL
+  calculation = 'relax', prefix = 'fe#1' /
+&SYSTEM
+  ibrav = 0, nat = 2, ntyp = 2
+  celldm(1) = 5.4235 ! bcc iron
+  ecutwfc = 40.0
+/
+&ELECTRONS conv_thr = 1d-8 /
+ATOMIC_SPECIES
+Fe1 55.845 Fe.pbe-spn-rrkjus_psl.0.2.1.UPF
+FE2 55.845 Fe.pbe-spn-rrkjus_psl.0.2.1.UPF
+CELL_PARAMETERS alat
+ 1.0 0.0 0.0
+ 0.0 1.0 0.0
+ 0.0 0.0 1.0
+ATOMIC_POSITIONS alat
+Fe1 0.0 0.0 0.0
+FE2 0.5 0.5 0.5 0 0 0
+K_POINTS automatic
+ 8 8 8 0 0 0
+";
+
+    #[test]
+    fn test_parse() {
+        let input: PwInput = INPUT.parse().unwrap();
+
+        assert_abs_diff_eq!(input.phase.cell.a, 5.4235 * super::BOHR, epsilon = 1e-10);
+        assert_eq!(input.species.len(), 2);
+        assert_eq!(input.atom_species, vec![0, 1]);
+        assert_eq!(input.phase.atoms[1].type_, "Fe");
+        assert_abs_diff_eq!(input.phase.atoms[1].z, 0.5, epsilon = 1e-12);
+        assert_eq!(
+            input.namelists[0].1[1],
+            ("prefix".to_string(), "'fe#1'".to_string())
+        );
+        assert_eq!(input.k_points, vec!["K_POINTS automatic", "8 8 8 0 0 0"]);
+        assert_eq!(
+            input.namelists[2],
+            (
+                "ELECTRONS".to_string(),
+                vec![("conv_thr".to_string(), "1d-8".to_string())]
+            )
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = INPUT
+            .replace("FE2 0.5", "Fe3 0.5")
+            .parse::<PwInput>()
+            .unwrap_err();
+        assert_eq!(error.line(), Some(18));
+
+        assert!(INPUT
+            .replace("ibrav = 0", "ibrav = 2")
+            .parse::<PwInput>()
+            .is_err());
+
+        let error = INPUT
+            .replace("conv_thr = 1d-8 /", "conv_thr = 1d-8")
+            .parse::<PwInput>()
+            .unwrap_err();
+        assert_eq!(error.line(), Some(9));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let input: PwInput = INPUT.parse().unwrap();
+        let written = input.to_string();
+
+        assert!(written.contains("ATOMIC_POSITIONS crystal"));
+        assert!(written.contains("CELL_PARAMETERS angstrom"));
+        assert!(!written.contains("celldm"));
+
+        let reread: PwInput = written.parse().unwrap();
+
+        assert_eq!(reread.species, input.species);
+        assert_eq!(reread.atom_species, input.atom_species);
+        assert_abs_diff_eq!(reread.phase.cell.a, input.phase.cell.a, epsilon = 1e-10);
+        assert_abs_diff_eq!(reread.phase.atoms[1].y, 0.5, epsilon = 1e-10);
+    }
+
+    #[cfg(all(feature = "data", feature = "symmetry"))]
+    #[test]
+    fn test_from_phase() {
+        use crate::{Atom, Atoms, Cell, Phase};
+
+        let phase = Phase {
+            cell: Cell {
+                a: 5.64,
+                b: 5.64,
+                c: 5.64,
+                alpha: 90.0,
+                beta: 90.0,
+                gamma: 90.0,
+                space_group_number: 225,
+                ..Default::default()
+            },
+            atoms: Atoms(vec![
+                Atom {
+                    label: "Na1".to_string(),
+                    type_: "Na".to_string(),
+                    occupancy: 1.0,
+                    ..Default::default()
+                },
+                Atom {
+                    label: "Cl1".to_string(),
+                    type_: "Cl".to_string(),
+                    x: 0.5,
+                    occupancy: 1.0,
+                    ..Default::default()
+                },
+            ]),
+        };
+
+        let input = PwInput::from_phase(&phase);
+
+        assert_eq!(input.phase.atoms.len(), 8);
+        assert_abs_diff_eq!(input.species[1].mass, 35.45, epsilon = 1e-4);
+        assert_eq!(input.k_points[1], "4 4 4 0 0 0");
+
+        let reread: PwInput = input.to_string().parse().unwrap();
+
+        assert_eq!(reread.phase.atoms.len(), 8);
+    }
+}
@@ -0,0 +1,1008 @@
+use std::{
+    iter::{Enumerate, Peekable},
+    str::{FromStr, Lines},
+};
+
+use cgmath::{Matrix3, SquareMatrix, Vector3};
+
+use super::{parse_floats, ParseError, StructureFileError};
+use crate::{Atom, Atoms, Cell, Phase};
+
+/// A LAMMPS data file as read by `read_data`.
+///
+/// Only atoms are supported. Bonds, angles, force field coefficients and velocities are
+/// skipped when reading. [`LammpsData::to_data`] fails unless `atom_types` and `charges` have
+/// one entry per atom and every type is in `types`. The [`Display`](std::fmt::Display)
+/// implementation does not check them; it writes charges only if every atom has one and
+/// type 0 for atoms without a valid type.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LammpsData {
+    /// First line of the file.
+    pub comment: String,
+    pub phase: Phase,
+    pub types: Vec<LammpsAtomType>,
+    /// Index into `types` for each atom of the phase, i.e. the LAMMPS atom type minus one.
+    pub atom_types: Vec<usize>,
+    /// Charge for each atom of the phase. Written with `atom_style charge` if set and with
+    /// `atom_style atomic` otherwise.
+    pub charges: Option<Vec<f64>>,
+    /// Lower corner of the box, `xlo ylo zlo`, in Å.
+    pub origin: [f64; 3],
+}
+
+/// An entry of the `Masses` section.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LammpsAtomType {
+    /// Name from the comment of the `Masses` line, or the type number if there is none.
+    pub name: String,
+    /// Mass in u.
+    pub mass: f64,
+}
+
+impl LammpsData {
+    /// Parses the header and the `Masses` and `Atoms` sections of a data file.
+    ///
+    /// The atom style is taken from the comment of the `Atoms` line, e.g. `Atoms # full`, and
+    /// otherwise guessed from the number of columns. Supported styles are `atomic`, `charge`,
+    /// `full` and `molecular`. Image flags are applied to the positions and atoms are sorted by
+    /// their id.
+    ///
+    /// Six or nine columns fit both `charge` and `molecular` and are rejected without a style
+    /// comment, see [`LammpsData::parse_with_atom_style`].
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        Self::parse_atom_style(s, None)
+    }
+
+    /// Parses a data file like [`LammpsData::parse`], using `atom_style` if the `Atoms` line
+    /// has no style comment.
+    pub fn parse_with_atom_style(s: &str, atom_style: &str) -> Result<Self, ParseError> {
+        Self::parse_atom_style(s, Some(atom_style))
+    }
+
+    fn parse_atom_style(s: &str, explicit_style: Option<&str>) -> Result<Self, ParseError> {
+        let mut lines = s.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+        let comment = lines
+            .next()
+            .map(|(_, line)| line.trim().to_string())
+            .ok_or_else(|| ParseError::without_line("File is empty"))?;
+
+        let mut number_of_atoms = None;
+        let mut number_of_types = None;
+        let mut bounds = [None; 3];
+        let mut tilt = [0.0; 3];
+
+        let mut section = None;
+        let mut atom_style = explicit_style.map(|style| style.to_string());
+        let mut masses: Vec<(usize, f64, Option<String>)> = Vec::new();
+        let mut atoms = Vec::new();
+
+        for (line_number, line) in lines {
+            let (content, note) = match line.split_once('#') {
+                Some((content, note)) => (content.trim(), Some(note.trim())),
+                None => (line.trim(), None),
+            };
+
+            if content.is_empty() {
+                continue;
+            }
+
+            if content.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                if let Some(style) = note
+                    .filter(|_| content == "Atoms")
+                    .and_then(|note| note.split_whitespace().next())
+                {
+                    atom_style = Some(style.to_string());
+                }
+
+                section = Some(content.to_string());
+                continue;
+            }
+
+            let tokens = content.split_whitespace().collect::<Vec<_>>();
+
+            match section.as_deref() {
+                None => match tokens[1..] {
+                    ["atoms"] => number_of_atoms = Some(parse_count(line_number, tokens[0])?),
+                    ["atom", "types"] => {
+                        number_of_types = Some(parse_count(line_number, tokens[0])?)
+                    }
+                    [_, "xlo", "xhi"] | [_, "ylo", "yhi"] | [_, "zlo", "zhi"] => {
+                        let axis = usize::from(tokens[2].as_bytes()[0] - b'x');
+                        bounds[axis] = Some(parse_floats::<2>(line_number, &tokens, "bounds")?);
+                    }
+                    [_, _, "xy", "xz", "yz"] => {
+                        tilt = parse_floats::<3>(line_number, &tokens, "tilt factors")?;
+                    }
+                    _ => {}
+                },
+                Some("Masses") => {
+                    let [_, mass] = parse_floats::<2>(line_number, &tokens, "mass")?;
+                    let name = note
+                        .and_then(|note| note.split_whitespace().next())
+                        .map(|name| name.to_string());
+
+                    masses.push((parse_count(line_number, tokens[0])?, mass, name));
+                }
+                Some("Atoms") => {
+                    atoms.push(parse_atom(line_number, &tokens, atom_style.as_deref())?);
+                }
+                Some(_) => {}
+            }
+        }
+
+        let [x, y, z] = bounds;
+        let (x, y, z) = x
+            .zip(y)
+            .zip(z)
+            .map(|((x, y), z)| (x, y, z))
+            .ok_or_else(|| ParseError::without_line("Missing box bounds"))?;
+
+        let origin = [x[0], y[0], z[0]];
+        let lattice = box_lattice([x[1] - x[0], y[1] - y[0], z[1] - z[0]], tilt);
+        let inverse_lattice = lattice
+            .invert()
+            .ok_or_else(|| ParseError::without_line("Box is degenerate"))?;
+
+        if let Some(number_of_atoms) = number_of_atoms {
+            if number_of_atoms != atoms.len() {
+                return Err(ParseError::without_line(format!(
+                    "Expected {} atoms, found {}",
+                    number_of_atoms,
+                    atoms.len()
+                )));
+            }
+        }
+
+        let number_of_types = number_of_types
+            .or_else(|| atoms.iter().map(|atom| atom.type_).max())
+            .unwrap_or_default();
+
+        let types = (1..=number_of_types)
+            .map(|type_| {
+                let (mass, name) = masses
+                    .iter()
+                    .find(|(t, _, _)| *t == type_)
+                    .map(|(_, mass, name)| (*mass, name.clone()))
+                    .unwrap_or_default();
+
+                LammpsAtomType {
+                    name: name.unwrap_or_else(|| type_.to_string()),
+                    mass,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        atoms.sort_by_key(|atom| atom.id);
+
+        let mut atom_types = Vec::with_capacity(atoms.len());
+        let mut phase_atoms = Vec::with_capacity(atoms.len());
+
+        for atom in &atoms {
+            if atom.type_ == 0 || atom.type_ > types.len() {
+                return Err(ParseError::new(
+                    atom.line,
+                    format!("Atom type {} out of range", atom.type_),
+                ));
+            }
+
+            let position = inverse_lattice * (atom.position - Vector3::from(origin)) + atom.image;
+
+            atom_types.push(atom.type_ - 1);
+            phase_atoms.push(Atom {
+                type_: types[atom.type_ - 1].name.clone(),
+                x: position.x,
+                y: position.y,
+                z: position.z,
+                occupancy: 1.0,
+                ..Default::default()
+            });
+        }
+
+        label_atoms(&mut phase_atoms);
+
+        let charges = atoms
+            .iter()
+            .map(|atom| atom.charge)
+            .collect::<Option<Vec<_>>>()
+            .filter(|charges| !charges.is_empty());
+
+        Ok(Self {
+            comment,
+            phase: Phase {
+                cell: Cell::from_lattice_vectors([lattice.x, lattice.y, lattice.z]),
+                atoms: Atoms(phase_atoms),
+            },
+            types,
+            atom_types,
+            charges,
+            origin,
+        })
+    }
+
+    /// Creates a data file of a phase expanded to P1 and replicated `replicate` times along
+    /// the cell axes, with masses from [`crate::ATOMIC_MASSES`].
+    ///
+    /// Atom types are numbered in order of their first occurrence. Charges can be added with
+    /// [`Self::set_charges`].
+    #[cfg(all(feature = "data", feature = "symmetry"))]
+    pub fn from_phase(phase: &Phase, replicate: [usize; 3]) -> Self {
+        let p1 = phase.expand_to_p1();
+        let [na, nb, nc] = replicate.map(|n| n.max(1));
+
+        let mut types: Vec<LammpsAtomType> = Vec::new();
+        let mut atom_types = Vec::new();
+        let mut atoms = Vec::with_capacity(p1.atoms.len() * na * nb * nc);
+
+        for i in 0..na {
+            for j in 0..nb {
+                for k in 0..nc {
+                    for atom in p1.atoms.iter() {
+                        let index = match types.iter().position(|t| t.name == atom.type_) {
+                            Some(index) => index,
+                            None => {
+                                let mass = crate::ATOMIC_MASSES.get(&atom.type_).copied();
+
+                                if mass.is_none() {
+                                    log::warn!(
+                                        "Unknown atom type: {}. Using a mass of 1.0.",
+                                        atom.type_
+                                    );
+                                }
+
+                                types.push(LammpsAtomType {
+                                    name: atom.type_.clone(),
+                                    mass: mass.unwrap_or(1.0) as f64,
+                                });
+                                types.len() - 1
+                            }
+                        };
+
+                        atom_types.push(index);
+                        atoms.push(Atom {
+                            x: (atom.x + i as f64) / na as f64,
+                            y: (atom.y + j as f64) / nb as f64,
+                            z: (atom.z + k as f64) / nc as f64,
+                            ..atom.clone()
+                        });
+                    }
+                }
+            }
+        }
+
+        let mut cell = Cell {
+            a: p1.cell.a * na as f64,
+            b: p1.cell.b * nb as f64,
+            c: p1.cell.c * nc as f64,
+            space_group: "P1".to_string(),
+            space_group_number: 1,
+            ..p1.cell.clone()
+        };
+        cell.volume = cell.calculate_volume();
+
+        Self {
+            comment: format!("{} {}x{}x{}", phase.cell.space_group, na, nb, nc),
+            phase: Phase {
+                cell,
+                atoms: Atoms(atoms),
+            },
+            types,
+            atom_types,
+            charges: None,
+            origin: [0.0; 3],
+        }
+    }
+
+    /// Sets the charge of every atom from its type. Types not listed are neutral.
+    pub fn set_charges(&mut self, charges: &[(&str, f64)]) {
+        self.charges = Some(
+            self.atom_types
+                .iter()
+                .map(|&type_| {
+                    charges
+                        .iter()
+                        .find(|(name, _)| *name == self.types[type_].name)
+                        .map_or(0.0, |(_, charge)| *charge)
+                })
+                .collect(),
+        );
+    }
+
+    /// Returns the content of the data file, failing unless `atom_types` and `charges` have
+    /// one entry per atom and every type is in `types`.
+    pub fn to_data(&self) -> Result<String, StructureFileError> {
+        let number_of_atoms = self.phase.atoms.len();
+
+        if self.atom_types.len() != number_of_atoms {
+            return Err(StructureFileError::new(format!(
+                "Expected {} atom types, found {}",
+                number_of_atoms,
+                self.atom_types.len()
+            )));
+        }
+
+        if let Some(&type_) = self.atom_types.iter().find(|&&t| t >= self.types.len()) {
+            return Err(StructureFileError::new(format!(
+                "Atom type {} exceeds the {} types",
+                type_ + 1,
+                self.types.len()
+            )));
+        }
+
+        if let Some(charges) = self.charges.as_ref().filter(|c| c.len() != number_of_atoms) {
+            return Err(StructureFileError::new(format!(
+                "Expected {} charges, found {}",
+                number_of_atoms,
+                charges.len()
+            )));
+        }
+
+        Ok(self.to_string())
+    }
+}
+
+/// A row of the `Atoms` section.
+struct AtomLine {
+    line: usize,
+    id: u64,
+    type_: usize,
+    charge: Option<f64>,
+    position: Vector3<f64>,
+    image: Vector3<f64>,
+}
+
+fn parse_atom(
+    line_number: usize,
+    tokens: &[&str],
+    atom_style: Option<&str>,
+) -> Result<AtomLine, ParseError> {
+    let atom_style = match (atom_style, tokens.len()) {
+        (Some(style), _) => style,
+        (None, 5 | 8) => "atomic",
+        (None, 6 | 9) => {
+            return Err(ParseError::new(
+                line_number,
+                "Atom style is ambiguous, expected a comment like `Atoms # charge`",
+            ))
+        }
+        (None, _) => "full",
+    };
+
+    let (type_column, charge_column, position_column) = match atom_style {
+        "atomic" => (1, None, 2),
+        "charge" => (1, Some(2), 3),
+        "full" => (2, Some(3), 4),
+        "molecular" => (2, None, 3),
+        style => {
+            return Err(ParseError::new(
+                line_number,
+                format!("Unsupported atom style: {}", style),
+            ))
+        }
+    };
+
+    if tokens.len() < position_column + 3 {
+        return Err(ParseError::new(
+            line_number,
+            format!(
+                "Expected {} columns for atom style {}",
+                position_column + 3,
+                atom_style
+            ),
+        ));
+    }
+
+    let [x, y, z] = parse_floats::<3>(line_number, &tokens[position_column..], "position")?;
+
+    let image = match tokens.get(position_column + 3..position_column + 6) {
+        Some(flags) => {
+            let [nx, ny, nz] = parse_floats::<3>(line_number, flags, "image flags")?;
+            Vector3::new(nx, ny, nz)
+        }
+        None => Vector3::new(0.0, 0.0, 0.0),
+    };
+
+    let charge = match charge_column {
+        Some(column) => Some(
+            tokens[column]
+                .parse::<f64>()
+                .map_err(|_| ParseError::new(line_number, "Invalid charge"))?,
+        ),
+        None => None,
+    };
+
+    Ok(AtomLine {
+        line: line_number,
+        id: tokens[0]
+            .parse()
+            .map_err(|_| ParseError::new(line_number, "Invalid atom id"))?,
+        type_: parse_count(line_number, tokens[type_column])?,
+        charge,
+        position: Vector3::new(x, y, z),
+        image,
+    })
+}
+
+fn parse_count(line_number: usize, token: &str) -> Result<usize, ParseError> {
+    token
+        .parse()
+        .map_err(|_| ParseError::new(line_number, format!("Invalid integer: {}", token)))
+}
+
+/// Returns the lattice vectors as columns of a box with edge lengths `lx ly lz` and tilt
+/// factors `xy xz yz`.
+fn box_lattice([lx, ly, lz]: [f64; 3], [xy, xz, yz]: [f64; 3]) -> Matrix3<f64> {
+    Matrix3::new(lx, 0.0, 0.0, xy, ly, 0.0, xz, yz, lz)
+}
+
+/// Labels atoms by their type and a running number per type, e.g. `O1`, `O2`.
+fn label_atoms(atoms: &mut [Atom]) {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+
+    for atom in atoms {
+        let index = match counts.iter_mut().find(|(t, _)| *t == atom.type_) {
+            Some((_, count)) => {
+                *count += 1;
+                *count
+            }
+            None => {
+                counts.push((atom.type_.clone(), 1));
+                1
+            }
+        };
+
+        atom.label = format!("{}{}", atom.type_, index);
+    }
+}
+
+impl FromStr for LammpsData {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for LammpsData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let number_of_atoms = self.phase.atoms.len();
+        let charges = self
+            .charges
+            .as_ref()
+            .filter(|charges| charges.len() == number_of_atoms);
+
+        let m = self.phase.cell.orthogonalization_matrix();
+        let [xlo, ylo, zlo] = self.origin;
+        let (xy, xz, yz) = (m.y.x, m.z.x, m.z.y);
+
+        if xy.abs() > m.x.x / 2.0 || xz.abs() > m.x.x / 2.0 || yz.abs() > m.y.y / 2.0 {
+            log::warn!("Box is strongly tilted. LAMMPS needs `box tilt large` to read it.");
+        }
+
+        writeln!(f, "{}", self.comment)?;
+        writeln!(f)?;
+        writeln!(f, "{} atoms", number_of_atoms)?;
+        writeln!(f, "{} atom types", self.types.len())?;
+        writeln!(f)?;
+        writeln!(f, "{:.10} {:.10} xlo xhi", xlo, xlo + m.x.x)?;
+        writeln!(f, "{:.10} {:.10} ylo yhi", ylo, ylo + m.y.y)?;
+        writeln!(f, "{:.10} {:.10} zlo zhi", zlo, zlo + m.z.z)?;
+
+        if [xy, xz, yz].iter().any(|t| t.abs() > 1e-10) {
+            writeln!(f, "{:.10} {:.10} {:.10} xy xz yz", xy, xz, yz)?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "Masses")?;
+        writeln!(f)?;
+
+        for (i, type_) in self.types.iter().enumerate() {
+            writeln!(f, "{} {:.4} # {}", i + 1, type_.mass, type_.name)?;
+        }
+
+        writeln!(f)?;
+
+        match charges {
+            Some(_) => writeln!(f, "Atoms # charge")?,
+            None => writeln!(f, "Atoms # atomic")?,
+        }
+
+        writeln!(f)?;
+
+        for (i, atom) in self.phase.atoms.iter().enumerate() {
+            let position = m * Vector3::new(atom.x, atom.y, atom.z);
+            let type_ = match self.atom_types.get(i) {
+                Some(&t) if t < self.types.len() => t + 1,
+                _ => 0,
+            };
+
+            write!(f, "{} {}", i + 1, type_)?;
+
+            if let Some(charges) = charges {
+                write!(f, " {:.6}", charges[i])?;
+            }
+
+            writeln!(
+                f,
+                " {:.10} {:.10} {:.10}",
+                position.x + xlo,
+                position.y + ylo,
+                position.z + zlo
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A snapshot of a LAMMPS text dump file, e.g. from `dump 1 all custom 100 dump.lammpstrj id
+/// type xs ys zs`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LammpsDumpFrame {
+    pub timestep: u64,
+    /// Atoms sorted by id. Their type is the `element` column if present and the numeric
+    /// `type` otherwise.
+    pub phase: Phase,
+    /// Atom ids in the order of the atoms of the phase.
+    pub ids: Vec<u64>,
+    /// Remaining numeric columns, e.g. `vx` or `c_pe`, in the order of the atoms of the phase.
+    pub columns: Vec<(String, Vec<f64>)>,
+}
+
+/// Iterator over the snapshots of a LAMMPS dump file.
+pub struct LammpsDumpFrames<'a> {
+    lines: Peekable<Enumerate<Lines<'a>>>,
+}
+
+impl<'a> Iterator for LammpsDumpFrames<'a> {
+    type Item = Result<LammpsDumpFrame, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self
+            .lines
+            .peek()
+            .is_some_and(|(_, line)| line.trim().is_empty())
+        {
+            self.lines.next();
+        }
+
+        self.lines.peek()?;
+
+        let frame =
+            LammpsDumpFrame::parse_frame(&mut self.lines.by_ref().map(|(i, line)| (i + 1, line)));
+
+        // stop after the first error since the frame boundaries are lost
+        if frame.is_err() {
+            self.lines.by_ref().for_each(drop);
+        }
+
+        Some(frame)
+    }
+}
+
+/// Position columns of dump files by preference.
+const POSITION_COLUMNS: [([&str; 3], bool); 4] = [
+    (["xs", "ys", "zs"], true),
+    (["xsu", "ysu", "zsu"], true),
+    (["x", "y", "z"], false),
+    (["xu", "yu", "zu"], false),
+];
+
+impl LammpsDumpFrame {
+    /// Returns an iterator over the snapshots of a dump file.
+    pub fn frames(s: &str) -> LammpsDumpFrames<'_> {
+        LammpsDumpFrames {
+            lines: s.lines().enumerate().peekable(),
+        }
+    }
+
+    /// Parses the first snapshot of a dump file.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        Self::frames(s)
+            .next()
+            .unwrap_or_else(|| Err(ParseError::without_line("File contains no frames")))
+    }
+
+    /// Reads items until and including `ITEM: ATOMS`. Unknown items, e.g. `ITEM: TIME`, are
+    /// expected to span a single line.
+    fn parse_frame<'a>(
+        lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    ) -> Result<Self, ParseError> {
+        let mut next_line = |what: &str| {
+            lines.next().ok_or_else(|| {
+                ParseError::without_line(format!("Unexpected end of file before {}", what))
+            })
+        };
+
+        let mut timestep = 0;
+        let mut number_of_atoms = None;
+        let mut bounds = None;
+
+        let (header_line_number, column_names) = loop {
+            let (line_number, line) = next_line("ITEM: ATOMS")?;
+            let item = line
+                .trim()
+                .strip_prefix("ITEM:")
+                .ok_or_else(|| ParseError::new(line_number, "Expected an ITEM line"))?
+                .trim();
+
+            if item == "TIMESTEP" {
+                let (line_number, line) = next_line("the timestep")?;
+                timestep = parse_count(line_number, line.trim())? as u64;
+            } else if item == "NUMBER OF ATOMS" {
+                let (line_number, line) = next_line("the number of atoms")?;
+                number_of_atoms = Some(parse_count(line_number, line.trim())?);
+            } else if let Some(flags) = item.strip_prefix("BOX BOUNDS") {
+                let is_triclinic = flags.split_whitespace().next() == Some("xy");
+                let mut rows = [[0.0; 3]; 3];
+
+                for row in rows.iter_mut() {
+                    let (line_number, line) = next_line("the box bounds")?;
+                    let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+                    *row = match is_triclinic {
+                        true => parse_floats::<3>(line_number, &tokens, "box bounds")?,
+                        false => {
+                            let [lo, hi] = parse_floats::<2>(line_number, &tokens, "box bounds")?;
+                            [lo, hi, 0.0]
+                        }
+                    };
+                }
+
+                bounds = Some(rows);
+            } else if let Some(columns) = item.strip_prefix("ATOMS") {
+                break (line_number, columns.split_whitespace().collect::<Vec<_>>());
+            } else {
+                next_line(item)?;
+            }
+        };
+
+        let number_of_atoms = number_of_atoms
+            .ok_or_else(|| ParseError::new(header_line_number, "Missing ITEM: NUMBER OF ATOMS"))?;
+        let [[xlo, xhi, xy], [ylo, yhi, xz], [zlo, zhi, yz]] = bounds
+            .ok_or_else(|| ParseError::new(header_line_number, "Missing ITEM: BOX BOUNDS"))?;
+
+        // bounds of triclinic boxes enclose the tilted box
+        let xlo = xlo - [0.0, xy, xz, xy + xz].into_iter().fold(0.0, f64::min);
+        let xhi = xhi - [0.0, xy, xz, xy + xz].into_iter().fold(0.0, f64::max);
+        let ylo = ylo - yz.min(0.0);
+        let yhi = yhi - yz.max(0.0);
+
+        let lattice = box_lattice([xhi - xlo, yhi - ylo, zhi - zlo], [xy, xz, yz]);
+        let inverse_lattice = lattice
+            .invert()
+            .ok_or_else(|| ParseError::new(header_line_number, "Box is degenerate"))?;
+
+        let column = |name: &str| column_names.iter().position(|c| *c == name);
+
+        let (position_columns, is_scaled) = POSITION_COLUMNS
+            .iter()
+            .find_map(|(names, is_scaled)| {
+                let [x, y, z] = names.map(column);
+                Some(([x?, y?, z?], *is_scaled))
+            })
+            .ok_or_else(|| ParseError::new(header_line_number, "Missing position columns"))?;
+
+        let id_column = column("id");
+        let type_column = column("element").or_else(|| column("type"));
+
+        let mut columns = column_names
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                !position_columns.contains(i) && Some(*i) != id_column && Some(*i) != type_column
+            })
+            .map(|(i, name)| (i, name.to_string(), Vec::with_capacity(number_of_atoms)))
+            .collect::<Vec<_>>();
+
+        let mut atoms = Vec::with_capacity(number_of_atoms);
+
+        for index in 0..number_of_atoms {
+            let (line_number, line) = next_line("the atoms")?;
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+            if tokens.len() < column_names.len() {
+                return Err(ParseError::new(
+                    line_number,
+                    format!("Expected {} columns", column_names.len()),
+                ));
+            }
+
+            let id = match id_column {
+                Some(column) => tokens[column]
+                    .parse()
+                    .map_err(|_| ParseError::new(line_number, "Invalid atom id"))?,
+                None => index as u64 + 1,
+            };
+
+            let [x, y, z] = parse_floats::<3>(
+                line_number,
+                &position_columns.map(|column| tokens[column]),
+                "position",
+            )?;
+
+            let position = match is_scaled {
+                true => Vector3::new(x, y, z),
+                false => inverse_lattice * Vector3::new(x - xlo, y - ylo, z - zlo),
+            };
+
+            for (column, name, values) in columns.iter_mut() {
+                values.push(tokens[*column].parse::<f64>().map_err(|_| {
+                    ParseError::new(line_number, format!("Invalid value of {}", name))
+                })?);
+            }
+
+            atoms.push((
+                id,
+                Atom {
+                    type_: type_column.map_or_else(String::new, |c| tokens[c].to_string()),
+                    x: position.x,
+                    y: position.y,
+                    z: position.z,
+                    occupancy: 1.0,
+                    ..Default::default()
+                },
+            ));
+        }
+
+        // dumps are unordered unless `dump_modify sort` is used
+        let mut order = (0..atoms.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&i| atoms[i].0);
+
+        let ids = order.iter().map(|&i| atoms[i].0).collect();
+        let mut sorted = order
+            .iter()
+            .map(|&i| atoms[i].1.clone())
+            .collect::<Vec<_>>();
+        label_atoms(&mut sorted);
+
+        Ok(Self {
+            timestep,
+            phase: Phase {
+                cell: Cell::from_lattice_vectors([lattice.x, lattice.y, lattice.z]),
+                atoms: Atoms(sorted),
+            },
+            ids,
+            columns: columns
+                .into_iter()
+                .map(|(_, name, values)| (name, order.iter().map(|&i| values[i]).collect()))
+                .collect(),
+        })
+    }
+}
+
+impl FromStr for LammpsDumpFrame {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+#[cfg(test)]
+mod test_lammps {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::{LammpsData, LammpsDumpFrame};
+
+    const DATA: &str = "\
+triclinic test box
+
+2 atoms
+2 atom types
+3 bonds
+
+1.0 5.0 xlo xhi
+0.0 3.0 ylo yhi
+0.0 5.0 zlo zhi
+1.0 0.5 -0.5 xy xz yz
+
+Masses
+
+1 22.990 # Na
+2 35.45
+
+Atoms # charge
+
+2 2 -1.0 1.0 0.0 0.0 1 0 0
+1 1 1.0 3.75 1.25 2.5 0 0 0
+
+Bonds
+
+1 1 1 2
+";
+
+    const DUMP: &str = "\
+ITEM: TIMESTEP
+100
+ITEM: NUMBER OF ATOMS
+2
+ITEM: BOX BOUNDS xy xz yz pp pp pp
+1.0 6.5 1.0
+-0.5 3.0 0.5
+0.0 5.0 -0.5
+ITEM: ATOMS id type x y z vx
+2 2 1.0 0.0 0.0 -0.5
+1 1 3.75 1.25 2.5 0.5
+ITEM: TIMESTEP
+200
+ITEM: NUMBER OF ATOMS
+1
+ITEM: BOX BOUNDS pp pp pp
+0.0 2.0
+0.0 2.0
+0.0 2.0
+ITEM: ATOMS id element xs ys zs
+7 Cl 0.25 0.5 0.75
+";
+
+    #[test]
+    fn test_parse() {
+        let data: LammpsData = DATA.parse().unwrap();
+        let cell = &data.phase.cell;
+
+        assert_eq!(data.comment, "triclinic test box");
+        assert_eq!(data.origin, [1.0, 0.0, 0.0]);
+        assert_abs_diff_eq!(cell.a, 4.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(cell.b, 10f64.sqrt(), epsilon = 1e-10);
+        assert_abs_diff_eq!(cell.volume, 60.0, epsilon = 1e-8);
+
+        assert_eq!(data.types[0].name, "Na");
+        assert_eq!(data.types[1].name, "2");
+        assert_eq!(data.atom_types, vec![0, 1]);
+        assert_eq!(data.charges, Some(vec![1.0, -1.0]));
+
+        let atoms = &data.phase.atoms;
+        assert_eq!(atoms[0].label, "Na1");
+        assert_abs_diff_eq!(atoms[0].x, 0.5, epsilon = 1e-10);
+        assert_abs_diff_eq!(atoms[0].y, 0.5, epsilon = 1e-10);
+        assert_abs_diff_eq!(atoms[0].z, 0.5, epsilon = 1e-10);
+        // image flags
+        assert_abs_diff_eq!(atoms[1].x, 1.0, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = DATA
+            .replace("3.75", "3.7x")
+            .parse::<LammpsData>()
+            .unwrap_err();
+        assert_eq!(error.line(), Some(20));
+
+        let error = DATA
+            .replace("1 1 1.0", "1 3 1.0")
+            .parse::<LammpsData>()
+            .unwrap_err();
+        assert_eq!(error.line(), Some(20));
+
+        assert!(DATA
+            .replace("2 atoms", "3 atoms")
+            .parse::<LammpsData>()
+            .is_err());
+        assert!(DATA
+            .replace("0.0 3.0 ylo yhi", "")
+            .parse::<LammpsData>()
+            .is_err());
+
+        // six columns fit both `charge` and `molecular`
+        let without_style = DATA
+            .replace("Atoms # charge", "Atoms")
+            .replace(" 1 0 0\n", "\n")
+            .replace(" 0 0 0\n", "\n");
+        assert_eq!(
+            without_style.parse::<LammpsData>().unwrap_err().line(),
+            Some(19)
+        );
+
+        let charge = LammpsData::parse_with_atom_style(&without_style, "charge").unwrap();
+        assert_eq!(charge.charges, Some(vec![1.0, -1.0]));
+
+        // the charge column is read as the atom type
+        let error = LammpsData::parse_with_atom_style(&without_style, "molecular").unwrap_err();
+        assert_eq!(error.line(), Some(19));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let data: LammpsData = DATA.parse().unwrap();
+        let written = data.to_string();
+        let reread: LammpsData = written.parse().unwrap();
+
+        assert!(written.contains("1.0000000000 0.5000000000 -0.5000000000 xy xz yz"));
+        assert!(written.contains("2 35.4500 # 2"));
+        assert_eq!(reread.types, data.types);
+        assert_eq!(reread.charges, data.charges);
+        assert_eq!(reread.origin, data.origin);
+        assert_abs_diff_eq!(reread.phase.atoms[1].x, 1.0, epsilon = 1e-8);
+
+        assert_eq!(data.to_data().unwrap(), written);
+
+        let mut missing_charge = data.clone();
+        missing_charge.charges = Some(vec![1.0]);
+        assert!(missing_charge.to_data().is_err());
+        assert!(missing_charge.to_string().contains("Atoms # atomic"));
+
+        let mut unknown_type = data;
+        unknown_type.atom_types[1] = 2;
+        assert!(unknown_type.to_data().is_err());
+        assert!(unknown_type.to_string().parse::<LammpsData>().is_err());
+    }
+
+    #[test]
+    fn test_dump() {
+        let frames = LammpsDumpFrame::frames(DUMP)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(frames.len(), 2);
+
+        let data: LammpsData = DATA.parse().unwrap();
+        let first = &frames[0];
+
+        assert_eq!(first.timestep, 100);
+        assert_eq!(first.ids, vec![1, 2]);
+        assert_eq!(first.columns, vec![("vx".to_string(), vec![0.5, -0.5])]);
+        assert_abs_diff_eq!(
+            first.phase.cell.volume,
+            data.phase.cell.volume,
+            epsilon = 1e-8
+        );
+        assert_abs_diff_eq!(
+            first.phase.cell.gamma,
+            data.phase.cell.gamma,
+            epsilon = 1e-8
+        );
+        assert_eq!(first.phase.atoms[0].type_, "1");
+        assert_abs_diff_eq!(first.phase.atoms[0].y, 0.5, epsilon = 1e-10);
+
+        let second = &frames[1];
+
+        assert_eq!(second.phase.atoms[0].label, "Cl1");
+        assert_abs_diff_eq!(second.phase.atoms[0].z, 0.75, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_dump_errors() {
+        let error = DUMP
+            .replace("2 2 1.0 0.0 0.0", "2 2 1.0 0.0 0.x")
+            .parse::<LammpsDumpFrame>()
+            .unwrap_err();
+        assert_eq!(error.line(), Some(10));
+
+        let mut frames = LammpsDumpFrame::frames(&DUMP[..DUMP.len() - 20]);
+
+        assert!(frames.next().unwrap().is_ok());
+        assert!(frames.next().unwrap().is_err());
+        assert!(frames.next().is_none());
+    }
+
+    #[cfg(all(feature = "data", feature = "symmetry"))]
+    #[test]
+    fn test_from_phase() {
+        let phase = crate::test_util::rock_salt();
+
+        let mut data = LammpsData::from_phase(&phase, [2, 1, 1]);
+        data.set_charges(&[("Na", 1.0), ("Cl", -1.0)]);
+
+        assert_eq!(data.phase.atoms.len(), 16);
+        assert_eq!(data.types.len(), 2);
+        assert_abs_diff_eq!(data.types[1].mass, 35.45, epsilon = 1e-3);
+        assert_abs_diff_eq!(data.phase.cell.a, 11.28, epsilon = 1e-10);
+        assert_abs_diff_eq!(
+            data.charges.as_ref().unwrap().iter().sum::<f64>(),
+            0.0,
+            epsilon = 1e-10
+        );
+        assert!(data.phase.atoms.iter().all(|atom| atom.x < 1.0));
+
+        let reread: LammpsData = data.to_string().parse().unwrap();
+
+        assert_eq!(reread.phase.atoms.len(), 16);
+        assert_eq!(reread.types[0].name, "Na");
+        assert_abs_diff_eq!(
+            reread.phase.cell.volume,
+            data.phase.cell.volume,
+            epsilon = 1e-6
+        );
+    }
+}
@@ -0,0 +1,815 @@
+use std::{collections::HashMap, str::FromStr};
+
+use cgmath::{Matrix3, Vector3};
+
+use super::{
+    cell_from_parameters, parse_float_with_uncertainty,
+    pdb::{capitalize, CartesianSite},
+    spaced_space_group_symbol, ParseError, PdbAtomInfo,
+};
+use crate::{u_to_b, AdpType, Atom, Atoms, Phase};
+
+/// Coordinates of a macromolecular CIF file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Mmcif {
+    /// Name of the data block.
+    pub name: String,
+    /// Cell and atoms in fractional coordinates. Files without cell parameters get a cubic
+    /// cell with 1 Å edges, so fractional and Cartesian coordinates coincide.
+    pub phase: Phase,
+    /// Residue information for each atom of the phase.
+    pub atom_info: Vec<PdbAtomInfo>,
+}
+
+impl Mmcif {
+    /// Parses the first data block of an mmCIF file.
+    ///
+    /// Reads the `_cell` and `_symmetry` items, the `_atom_site` loop and the
+    /// `_atom_site_anisotrop` loop. The Cartesian coordinates are converted with the PDB
+    /// orthogonalization convention.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let block = DataBlock::parse(s)?;
+
+        let parameters = [
+            "_cell.length_a",
+            "_cell.length_b",
+            "_cell.length_c",
+            "_cell.angle_alpha",
+            "_cell.angle_beta",
+            "_cell.angle_gamma",
+        ]
+        .map(|tag| {
+            block
+                .items
+                .get(tag)
+                .and_then(|v| parse_float_with_uncertainty(v))
+        });
+
+        let space_group = [
+            "_symmetry.space_group_name_H-M",
+            "_space_group.name_H-M_alt",
+        ]
+        .iter()
+        .find_map(|tag| block.items.get(*tag))
+        .map_or("P 1", |s| s.as_str());
+
+        let cell = match parameters {
+            [Some(a), Some(b), Some(c), Some(alpha), Some(beta), Some(gamma)] => {
+                cell_from_parameters([a, b, c, alpha, beta, gamma], space_group)
+            }
+            _ => cell_from_parameters([1.0, 1.0, 1.0, 90.0, 90.0, 90.0], "P 1"),
+        };
+
+        let atom_site = block
+            .loop_with("_atom_site.Cartn_x")
+            .ok_or_else(|| ParseError::without_line("Missing _atom_site loop"))?;
+
+        let mut u_cart = HashMap::new();
+
+        if let Some(anisotrop) = block.loop_with("_atom_site_anisotrop.U[1][1]") {
+            for row in 0..anisotrop.len() {
+                let id = anisotrop
+                    .get(row, "_atom_site_anisotrop.id")
+                    .unwrap_or_default();
+                let mut u = [0.0; 6];
+
+                for (u, (i, j)) in
+                    u.iter_mut()
+                        .zip([(1, 1), (2, 2), (3, 3), (1, 2), (1, 3), (2, 3)])
+                {
+                    let tag = format!("_atom_site_anisotrop.U[{}][{}]", i, j);
+                    *u = anisotrop.float(row, &tag)?.unwrap_or_default();
+                }
+
+                u_cart.insert(
+                    id.to_string(),
+                    Matrix3::new(u[0], u[3], u[4], u[3], u[1], u[5], u[4], u[5], u[2]),
+                );
+            }
+        }
+
+        let mut sites = Vec::with_capacity(atom_site.len());
+        let mut atom_info = Vec::with_capacity(atom_site.len());
+
+        for row in 0..atom_site.len() {
+            let text = |tag: &str| atom_site.get(row, tag).unwrap_or_default().to_string();
+            let id = text("_atom_site.id");
+            let name = text("_atom_site.label_atom_id");
+
+            let coordinate = |tag: &str| {
+                atom_site
+                    .float(row, tag)?
+                    .ok_or_else(|| ParseError::new(atom_site.line(row), format!("Missing {}", tag)))
+            };
+
+            sites.push(CartesianSite {
+                label: name.clone(),
+                type_: capitalize(&text("_atom_site.type_symbol")),
+                position: Vector3::new(
+                    coordinate("_atom_site.Cartn_x")?,
+                    coordinate("_atom_site.Cartn_y")?,
+                    coordinate("_atom_site.Cartn_z")?,
+                ),
+                occupancy: atom_site.float(row, "_atom_site.occupancy")?.unwrap_or(1.0),
+                b_iso: atom_site
+                    .float(row, "_atom_site.B_iso_or_equiv")?
+                    .unwrap_or_default(),
+                u_cart: u_cart.get(&id).copied(),
+            });
+
+            let charge = match atom_site.float(row, "_atom_site.pdbx_formal_charge")? {
+                Some(charge) if charge != 0.0 => {
+                    format!("{}{}", charge.abs(), if charge > 0.0 { '+' } else { '-' })
+                }
+                _ => String::new(),
+            };
+
+            atom_info.push(PdbAtomInfo {
+                hetero: text("_atom_site.group_PDB") == "HETATM",
+                serial: id.parse().unwrap_or_default(),
+                name,
+                alt_loc: text("_atom_site.label_alt_id"),
+                residue_name: text("_atom_site.label_comp_id"),
+                chain_id: text("_atom_site.label_asym_id"),
+                residue_sequence: text("_atom_site.label_seq_id").parse().unwrap_or_default(),
+                insertion_code: text("_atom_site.pdbx_PDB_ins_code"),
+                charge,
+            });
+        }
+
+        let scale = cell
+            .fractionalization_matrix()
+            .ok_or_else(|| ParseError::without_line("Unit cell is degenerate"))?;
+
+        let atoms = sites
+            .into_iter()
+            .map(|site| site.into_atom(&cell, scale, Vector3::new(0.0, 0.0, 0.0)))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ParseError::without_line)?;
+
+        Ok(Self {
+            name: block.name,
+            phase: Phase {
+                cell,
+                atoms: Atoms(atoms),
+            },
+            atom_info,
+        })
+    }
+}
+
+/// Parses the first data block of a small-molecule CIF file with fractional coordinates.
+///
+/// Reads the `_cell` and space group items, the `_atom_site` loop and the `_atom_site_aniso`
+/// loop in the DDL1 notation of small-molecule files, e.g. `_atom_site_fract_x`. Symmetry
+/// operations from `_space_group_symop_operation_xyz` or `_symmetry_equiv_pos_as_xyz` of a
+/// setting that is not tabulated, e.g. `P2_1/n`, are kept as [`crate::Cell::setting_operations`].
+/// Without a Hermann-Mauguin symbol the space group is taken from `_space_group_IT_number`.
+pub(super) fn parse_small_molecule_cif(s: &str) -> Result<Phase, ParseError> {
+    let block = DataBlock::parse(s)?;
+
+    let parameters = [
+        "_cell_length_a",
+        "_cell_length_b",
+        "_cell_length_c",
+        "_cell_angle_alpha",
+        "_cell_angle_beta",
+        "_cell_angle_gamma",
+    ]
+    .map(|tag| {
+        block
+            .items
+            .get(tag)
+            .and_then(|v| parse_float_with_uncertainty(v))
+            .ok_or_else(|| ParseError::without_line(format!("Missing {}", tag)))
+    });
+    let [a, b, c, alpha, beta, gamma] = parameters;
+
+    let space_group = [
+        "_space_group_name_H-M_alt",
+        "_symmetry_space_group_name_H-M",
+    ]
+    .iter()
+    .find_map(|tag| block.items.get(*tag))
+    .map_or("", |s| s.as_str());
+
+    let mut cell = cell_from_parameters([a?, b?, c?, alpha?, beta?, gamma?], space_group);
+
+    if cell.space_group_number == 0 {
+        let number = ["_space_group_IT_number", "_symmetry_Int_Tables_number"]
+            .iter()
+            .find_map(|tag| block.items.get(*tag))
+            .and_then(|number| number.parse::<u8>().ok())
+            .filter(|number| (1..=230).contains(number));
+
+        if let Some(number) = number {
+            cell.space_group_number = number;
+
+            #[cfg(feature = "symmetry")]
+            if cell.space_group.is_empty() {
+                cell.space_group = crate::SPACEGROUP_SYMBOLS[&number].clone();
+            }
+        }
+    }
+
+    #[cfg(feature = "symmetry")]
+    {
+        let tags = [
+            "_space_group_symop_operation_xyz",
+            "_symmetry_equiv_pos_as_xyz",
+        ];
+
+        if let Some((symop, tag)) = tags
+            .iter()
+            .find_map(|tag| block.loop_with(tag).map(|symop| (symop, *tag)))
+        {
+            let operations = (0..symop.len())
+                .filter_map(|row| symop.get(row, tag).map(|operation| (row, operation)))
+                .map(|(row, operation)| {
+                    crate::parse_symmetry_operation(operation).ok_or_else(|| {
+                        ParseError::new(
+                            symop.line(row),
+                            format!("Invalid symmetry operation: {}", operation),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            match crate::SpaceGroupSymmetryOperations::find(&operations) {
+                Some(number) => {
+                    if cell.space_group_number != number {
+                        cell.space_group = crate::SPACEGROUP_SYMBOLS[&number].clone();
+                    }
+
+                    cell.space_group_number = number;
+                }
+                None => cell.setting_operations = operations,
+            }
+        }
+    }
+
+    if cell.space_group.is_empty() && cell.setting_operations.is_empty() {
+        cell.space_group = "P1".to_string();
+        cell.space_group_number = 1;
+    }
+
+    let atom_site = block
+        .loop_with("_atom_site_fract_x")
+        .ok_or_else(|| ParseError::without_line("Missing _atom_site loop"))?;
+
+    let mut u_ani = HashMap::new();
+
+    if let Some(aniso) = block.loop_with("_atom_site_aniso_U_11") {
+        for row in 0..aniso.len() {
+            let label = aniso.get(row, "_atom_site_aniso_label").unwrap_or_default();
+            let mut u = [0.0; 6];
+
+            for (u, ij) in u.iter_mut().zip(["11", "22", "33", "12", "13", "23"]) {
+                *u = aniso
+                    .float(row, &format!("_atom_site_aniso_U_{}", ij))?
+                    .unwrap_or_default();
+            }
+
+            u_ani.insert(label.to_string(), u);
+        }
+    }
+
+    let atoms = (0..atom_site.len())
+        .map(|row| {
+            let text = |tag: &str| atom_site.get(row, tag).unwrap_or_default().to_string();
+            let coordinate = |tag: &str| {
+                atom_site
+                    .float(row, tag)?
+                    .ok_or_else(|| ParseError::new(atom_site.line(row), format!("Missing {}", tag)))
+            };
+
+            let label = text("_atom_site_label");
+            let mut atom = Atom {
+                type_: match text("_atom_site_type_symbol") {
+                    type_ if type_.is_empty() => label.clone(),
+                    type_ => type_,
+                },
+                label,
+                x: coordinate("_atom_site_fract_x")?,
+                y: coordinate("_atom_site_fract_y")?,
+                z: coordinate("_atom_site_fract_z")?,
+                occupancy: atom_site.float(row, "_atom_site_occupancy")?.unwrap_or(1.0),
+                adp_type: AdpType::Uiso,
+                u_iso_or_equiv: atom_site
+                    .float(row, "_atom_site_U_iso_or_equiv")?
+                    .unwrap_or_default(),
+                ..Default::default()
+            };
+
+            if let Some([u11, u22, u33, u12, u13, u23]) = u_ani.get(&atom.label).copied() {
+                atom.adp_type = AdpType::Uani;
+                (atom.u11, atom.u22, atom.u33) = (u11, u22, u33);
+                (atom.u12, atom.u13, atom.u23) = (u12, u13, u23);
+            }
+
+            Ok(atom)
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+
+    Ok(Phase {
+        cell,
+        atoms: Atoms(atoms),
+    })
+}
+
+/// Items and loops of a CIF data block with unknown (`?`) and inapplicable (`.`) values
+/// removed.
+struct DataBlock {
+    name: String,
+    items: HashMap<String, String>,
+    loops: Vec<Loop>,
+}
+
+struct Loop {
+    tags: Vec<String>,
+    /// Values with the line they start on, row by row.
+    values: Vec<(usize, String)>,
+}
+
+impl Loop {
+    fn len(&self) -> usize {
+        self.values.len() / self.tags.len()
+    }
+
+    fn line(&self, row: usize) -> usize {
+        self.values[row * self.tags.len()].0
+    }
+
+    fn get(&self, row: usize, tag: &str) -> Option<&str> {
+        let column = self.tags.iter().position(|t| t.eq_ignore_ascii_case(tag))?;
+        let value = self.values[row * self.tags.len() + column].1.as_str();
+
+        match value {
+            "?" | "." => None,
+            value => Some(value),
+        }
+    }
+
+    fn float(&self, row: usize, tag: &str) -> Result<Option<f64>, ParseError> {
+        self.get(row, tag)
+            .map(|value| {
+                parse_float_with_uncertainty(value).ok_or_else(|| {
+                    ParseError::new(
+                        self.line(row),
+                        format!("Invalid number in {}: {}", tag, value),
+                    )
+                })
+            })
+            .transpose()
+    }
+}
+
+impl DataBlock {
+    fn parse(s: &str) -> Result<Self, ParseError> {
+        let tokens = tokenize(s)?;
+        let mut tokens = tokens.into_iter().peekable();
+
+        let name = loop {
+            match tokens.next() {
+                Some((_, token)) if token.to_ascii_lowercase().starts_with("data_") => {
+                    break token[5..].to_string()
+                }
+                Some(_) => continue,
+                None => return Err(ParseError::without_line("Missing data block")),
+            }
+        };
+
+        let mut items = HashMap::new();
+        let mut loops = Vec::new();
+
+        while let Some((line_number, token)) = tokens.next() {
+            let lowercase = token.to_ascii_lowercase();
+
+            if lowercase.starts_with("data_") {
+                break;
+            }
+
+            if lowercase == "loop_" {
+                let mut tags = Vec::new();
+
+                while let Some((_, tag)) = tokens.next_if(|(_, t)| t.starts_with('_')) {
+                    tags.push(tag);
+                }
+
+                let mut values = Vec::new();
+
+                while let Some(value) = tokens.next_if(|(_, t)| {
+                    let t = t.to_ascii_lowercase();
+                    !t.starts_with('_') && t != "loop_" && !t.starts_with("data_")
+                }) {
+                    values.push(value);
+                }
+
+                if tags.is_empty() || values.len() % tags.len() != 0 {
+                    return Err(ParseError::new(
+                        line_number,
+                        "Number of loop values is not a multiple of the number of tags",
+                    ));
+                }
+
+                loops.push(Loop { tags, values });
+            } else if token.starts_with('_') {
+                let (_, value) = tokens.next().ok_or_else(|| {
+                    ParseError::new(line_number, format!("Missing value of {}", token))
+                })?;
+
+                if value != "?" && value != "." {
+                    items.insert(token, value);
+                }
+            }
+        }
+
+        Ok(Self { name, items, loops })
+    }
+
+    fn loop_with(&self, tag: &str) -> Option<&Loop> {
+        self.loops
+            .iter()
+            .find(|l| l.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+    }
+}
+
+/// Splits a CIF file into tokens with their line numbers, resolving quoted strings and
+/// semicolon text fields.
+fn tokenize(s: &str) -> Result<Vec<(usize, String)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut lines = s.lines().enumerate().map(|(i, line)| (i + 1, line));
+
+    while let Some((line_number, line)) = lines.next() {
+        if let Some(first) = line.strip_prefix(';') {
+            let mut text = first.to_string();
+
+            loop {
+                let (_, line) = lines
+                    .next()
+                    .ok_or_else(|| ParseError::new(line_number, "Unterminated text field"))?;
+
+                if line.starts_with(';') {
+                    break;
+                }
+
+                text.push('\n');
+                text.push_str(line);
+            }
+
+            tokens.push((line_number, text.trim().to_string()));
+            continue;
+        }
+
+        let mut rest = line.trim_start();
+
+        while !rest.is_empty() && !rest.starts_with('#') {
+            let token;
+
+            match rest.chars().next() {
+                Some(quote @ ('\'' | '"')) => {
+                    // quotes only close if followed by whitespace
+                    let end = rest[1..]
+                        .match_indices(quote)
+                        .map(|(i, _)| i + 1)
+                        .find(|i| rest[i + 1..].chars().next().is_none_or(char::is_whitespace))
+                        .ok_or_else(|| ParseError::new(line_number, "Unterminated string"))?;
+
+                    token = rest[1..end].to_string();
+                    rest = &rest[end + 1..];
+                }
+                _ => {
+                    let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+
+                    token = rest[..end].to_string();
+                    rest = &rest[end..];
+                }
+            }
+
+            tokens.push((line_number, token));
+            rest = rest.trim_start();
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Quotes a value if it would otherwise not be read back as a single token.
+fn quote(value: &str) -> String {
+    match value {
+        "" => "?".to_string(),
+        _ if value.contains(char::is_whitespace)
+            || value.starts_with(['_', '\'', '"', '#', '$']) =>
+        {
+            format!("'{}'", value)
+        }
+        _ => value.to_string(),
+    }
+}
+
+impl FromStr for Mmcif {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for Mmcif {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cell = &self.phase.cell;
+
+        writeln!(
+            f,
+            "data_{}",
+            if self.name.is_empty() {
+                "structure"
+            } else {
+                &self.name
+            }
+        )?;
+        writeln!(f, "#")?;
+
+        for (tag, value) in [
+            ("length_a", cell.a),
+            ("length_b", cell.b),
+            ("length_c", cell.c),
+            ("angle_alpha", cell.alpha),
+            ("angle_beta", cell.beta),
+            ("angle_gamma", cell.gamma),
+        ] {
+            writeln!(f, "_cell.{:<12} {:.4}", tag, value)?;
+        }
+
+        writeln!(f, "#")?;
+        writeln!(
+            f,
+            "_symmetry.space_group_name_H-M {}",
+            quote(&spaced_space_group_symbol(&cell.space_group))
+        )?;
+        writeln!(f, "#")?;
+        writeln!(f, "loop_")?;
+
+        for tag in [
+            "group_PDB",
+            "id",
+            "type_symbol",
+            "label_atom_id",
+            "label_alt_id",
+            "label_comp_id",
+            "label_asym_id",
+            "label_seq_id",
+            "pdbx_PDB_ins_code",
+            "Cartn_x",
+            "Cartn_y",
+            "Cartn_z",
+            "occupancy",
+            "B_iso_or_equiv",
+            "pdbx_formal_charge",
+        ] {
+            writeln!(f, "_atom_site.{}", tag)?;
+        }
+
+        let orthogonalization_matrix = cell.orthogonalization_matrix();
+        let atom_info = self
+            .phase
+            .atoms
+            .iter()
+            .enumerate()
+            .map(|(i, atom)| match self.atom_info.get(i) {
+                Some(info) => info.clone(),
+                None => PdbAtomInfo {
+                    hetero: true,
+                    serial: i as u32 + 1,
+                    name: atom.label.clone(),
+                    residue_name: "UNL".to_string(),
+                    residue_sequence: 1,
+                    ..Default::default()
+                },
+            })
+            .collect::<Vec<_>>();
+
+        for (atom, info) in self.phase.atoms.iter().zip(&atom_info) {
+            let position = orthogonalization_matrix * Vector3::new(atom.x, atom.y, atom.z);
+
+            let charge = match info
+                .charge
+                .split_at_checked(info.charge.len().saturating_sub(1))
+            {
+                Some((magnitude, "-")) => format!("-{}", magnitude),
+                Some((magnitude, "+")) => magnitude.to_string(),
+                _ => "?".to_string(),
+            };
+
+            writeln!(
+                f,
+                "{} {} {} {} {} {} {} {} {} {:.3} {:.3} {:.3} {:.2} {:.2} {}",
+                if info.hetero { "HETATM" } else { "ATOM" },
+                info.serial,
+                quote(&atom.type_),
+                quote(&info.name),
+                if info.alt_loc.is_empty() {
+                    "."
+                } else {
+                    &info.alt_loc
+                },
+                quote(&info.residue_name),
+                quote(&info.chain_id),
+                info.residue_sequence,
+                quote(&info.insertion_code),
+                position.x,
+                position.y,
+                position.z,
+                atom.occupancy,
+                atom.u_equiv(cell).map(u_to_b).unwrap_or_default(),
+                charge
+            )?;
+        }
+
+        let anisotropic = self
+            .phase
+            .atoms
+            .iter()
+            .zip(&atom_info)
+            .filter(|(atom, _)| matches!(atom.adp_type, AdpType::Uani | AdpType::Bani))
+            .filter_map(|(atom, info)| Some((atom.u_cif(cell)?, info)))
+            .collect::<Vec<_>>();
+
+        if !anisotropic.is_empty() {
+            writeln!(f, "#")?;
+            writeln!(f, "loop_")?;
+
+            for tag in [
+                "id", "U[1][1]", "U[2][2]", "U[3][3]", "U[1][2]", "U[1][3]", "U[2][3]",
+            ] {
+                writeln!(f, "_atom_site_anisotrop.{}", tag)?;
+            }
+
+            for (u, info) in anisotropic {
+                let u = cell.u_cif_to_u_cart(u);
+
+                writeln!(
+                    f,
+                    "{} {:.5} {:.5} {:.5} {:.5} {:.5} {:.5}",
+                    info.serial, u.x.x, u.y.y, u.z.z, u.x.y, u.x.z, u.y.z
+                )?;
+            }
+        }
+
+        writeln!(f, "#")
+    }
+}
+
+#[cfg(test)]
+mod test_mmcif {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::{parse_small_molecule_cif, Mmcif};
+    use crate::AdpType;
+
+    const MMCIF: &str = "\
+data_1ABC
+#
+_cell.length_a    10.000
+_cell.length_b    12.000
+_cell.length_c    14.000(2)
+_cell.angle_alpha 90.00
+_cell.angle_beta  90.00
+_cell.angle_gamma 90.00
+#
+_symmetry.space_group_name_H-M 'P 21 21 21'
+#
+loop_
+_atom_site.group_PDB
+_atom_site.id
+_atom_site.type_symbol
+_atom_site.label_atom_id
+_atom_site.label_alt_id
+_atom_site.label_comp_id
+_atom_site.label_asym_id
+_atom_site.label_seq_id
+_atom_site.Cartn_x
+_atom_site.Cartn_y
+_atom_site.Cartn_z
+_atom_site.occupancy
+_atom_site.B_iso_or_equiv
+_atom_site.pdbx_formal_charge
+ATOM   1 N  N   . ALA A 1   1.000 2.400 3.500 1.00 15.79 ?
+HETATM 2 CA CA  . CA  B . 5.000 6.000 7.000 1.00 30.00 2
+#
+loop_
+_atom_site_anisotrop.id
+_atom_site_anisotrop.U[1][1]
+_atom_site_anisotrop.U[2][2]
+_atom_site_anisotrop.U[3][3]
+_atom_site_anisotrop.U[1][2]
+_atom_site_anisotrop.U[1][3]
+_atom_site_anisotrop.U[2][3]
+1 0.2000 0.1500 0.1000 0.0100 0.0000 0.0000
+#
+";
+
+    #[test]
+    fn test_parse() {
+        let mmcif: Mmcif = MMCIF.parse().unwrap();
+        let atoms = &mmcif.phase.atoms;
+
+        assert_eq!(mmcif.name, "1ABC");
+        assert_eq!(mmcif.phase.cell.space_group, "P2_12_12_1");
+        assert_abs_diff_eq!(mmcif.phase.cell.c, 14.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(atoms[0].y, 0.2, epsilon = 1e-12);
+        assert_eq!(atoms[0].adp_type, AdpType::Uani);
+        assert_abs_diff_eq!(atoms[0].u12, 0.01, epsilon = 1e-12);
+        assert_eq!(atoms[1].type_, "Ca");
+        assert_eq!(atoms[1].adp_type, AdpType::Uiso);
+        assert_eq!(mmcif.atom_info[1].charge, "2+");
+        assert!(mmcif.atom_info[1].hetero);
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let tokens = super::tokenize("_a 'it''s here' \"b c\" # comment\n;text\nfield\n;\n")
+            .unwrap()
+            .into_iter()
+            .map(|(_, t)| t)
+            .collect::<Vec<_>>();
+
+        assert_eq!(tokens, vec!["_a", "it''s here", "b c", "text\nfield"]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = MMCIF
+            .replace("2.400", "2.4x0")
+            .parse::<Mmcif>()
+            .unwrap_err();
+
+        assert_eq!(error.line(), Some(27));
+        assert!("data_x\n_cell.length_a 1.0\n".parse::<Mmcif>().is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let mmcif: Mmcif = MMCIF.parse().unwrap();
+        let reread: Mmcif = mmcif.to_string().parse().unwrap();
+
+        assert_eq!(reread.atom_info, mmcif.atom_info);
+        assert_eq!(reread.phase.cell.space_group, "P2_12_12_1");
+
+        for (a, b) in reread.phase.atoms.iter().zip(mmcif.phase.atoms.iter()) {
+            assert_abs_diff_eq!(a.x, b.x, epsilon = 1e-4);
+            assert_abs_diff_eq!(a.u11, b.u11, epsilon = 1e-4);
+            assert_abs_diff_eq!(a.u_iso_or_equiv, b.u_iso_or_equiv, epsilon = 1e-4);
+        }
+    }
+
+    #[cfg(feature = "symmetry")]
+    #[test]
+    fn test_small_molecule_setting() {
+        const CIF: &str = "\
+data_p21n
+_cell_length_a 5.0
+_cell_length_b 6.0
+_cell_length_c 7.0
+_cell_angle_alpha 90
+_cell_angle_beta 95.5
+_cell_angle_gamma 90
+_space_group_name_H-M_alt 'P 1 21/n 1'
+_space_group_IT_number 14
+loop_
+_space_group_symop_operation_xyz
+'x, y, z'
+'-x+1/2, y+1/2, -z+1/2'
+'-x, -y, -z'
+'x+1/2, -y+1/2, z+1/2'
+loop_
+_atom_site_label
+_atom_site_type_symbol
+_atom_site_fract_x
+_atom_site_fract_y
+_atom_site_fract_z
+Cu1 Cu 0 0 0
+O1 O 0.1 0.2 0.3
+";
+
+        let phase = parse_small_molecule_cif(CIF).unwrap();
+
+        assert_eq!(phase.cell.space_group_number, 14);
+        assert_eq!(phase.cell.setting_operations.len(), 4);
+        assert_eq!(phase.expand_to_p1().atoms.len(), 6);
+
+        // the number is enough without a symbol and operations
+        let (header, atom_site) = CIF.split_once("loop_\n_space_group").unwrap();
+        let atom_site = &atom_site[atom_site.find("loop_").unwrap()..];
+        let header = header.replace("_space_group_name_H-M_alt 'P 1 21/n 1'\n", "");
+        let phase = parse_small_molecule_cif(&format!("{}{}", header, atom_site)).unwrap();
+
+        assert_eq!(phase.cell.space_group_number, 14);
+        assert_eq!(phase.cell.space_group, crate::SPACEGROUP_SYMBOLS[&14]);
+        assert!(phase.cell.setting_operations.is_empty());
+
+        let error = parse_small_molecule_cif(&CIF.replace("'-x, -y, -z'", "'-x, -y'")).unwrap_err();
+
+        assert_eq!(error.line(), Some(14));
+    }
+}
@@ -0,0 +1,579 @@
+use std::str::FromStr;
+
+use cgmath::{Matrix, Matrix3, SquareMatrix, Vector3};
+
+use super::{cell_from_parameters, spaced_space_group_symbol, ParseError};
+use crate::{b_to_u, u_to_b, AdpType, Atom, Atoms, Cell, Phase};
+
+/// A Protein Data Bank file reduced to its crystal structure records.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Pdb {
+    /// Cell and atoms in fractional coordinates. Files without a `CRYST1` record get a cubic
+    /// cell with 1 Å edges, so fractional and Cartesian coordinates coincide.
+    pub phase: Phase,
+    /// Residue information for each atom of the phase.
+    pub atom_info: Vec<PdbAtomInfo>,
+    /// Number of polymeric chains in the unit cell from the `CRYST1` record. Written as the
+    /// number of symmetry operations of the space group if `None`.
+    pub z: Option<u32>,
+}
+
+/// Residue information of a coordinate record.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct PdbAtomInfo {
+    /// `true` for `HETATM` and `false` for `ATOM` records.
+    pub hetero: bool,
+    pub serial: u32,
+    pub name: String,
+    pub alt_loc: String,
+    pub residue_name: String,
+    pub chain_id: String,
+    pub residue_sequence: i32,
+    pub insertion_code: String,
+    /// Formal charge, e.g. `2+`.
+    pub charge: String,
+}
+
+impl PdbAtomInfo {
+    fn from_atom(index: usize, atom: &Atom) -> Self {
+        Self {
+            hetero: true,
+            serial: index as u32 + 1,
+            name: atom.label.chars().take(4).collect(),
+            residue_name: "UNL".to_string(),
+            residue_sequence: 1,
+            ..Default::default()
+        }
+    }
+}
+
+/// An atom in Cartesian coordinates as stored in PDB and mmCIF files.
+pub(super) struct CartesianSite {
+    pub label: String,
+    pub type_: String,
+    pub position: Vector3<f64>,
+    pub occupancy: f64,
+    pub b_iso: f64,
+    /// Cartesian `U` in Å².
+    pub u_cart: Option<Matrix3<f64>>,
+}
+
+impl CartesianSite {
+    /// Converts the site with the fractionalization `x_frac = S x + t` of the file.
+    pub fn into_atom(
+        self,
+        cell: &Cell,
+        scale: Matrix3<f64>,
+        translation: Vector3<f64>,
+    ) -> Result<Atom, &'static str> {
+        let position = scale * self.position + translation;
+
+        let mut atom = Atom {
+            label: self.label,
+            type_: self.type_,
+            x: position.x,
+            y: position.y,
+            z: position.z,
+            occupancy: self.occupancy,
+            adp_type: AdpType::Uiso,
+            u_iso_or_equiv: b_to_u(self.b_iso),
+            ..Default::default()
+        };
+
+        if let Some(u_cart) = self.u_cart {
+            // U_cart = A U_cif Aᵀ with A = S⁻¹ N, which reduces to `Cell::u_cart_to_u_cif` for
+            // the standard orthogonalization
+            let a = scale.invert().ok_or("SCALE matrix is singular")?
+                * Matrix3::from_diagonal(cell.reciprocal_lengths());
+            let a_inv = a.invert().ok_or("SCALE matrix is singular")?;
+
+            atom.adp_type = AdpType::Uani;
+            atom.set_u_ani(a_inv * u_cart * a_inv.transpose());
+            atom.u_iso_or_equiv = (u_cart.x.x + u_cart.y.y + u_cart.z.z) / 3.0;
+        }
+
+        Ok(atom)
+    }
+}
+
+impl Pdb {
+    /// Parses the `CRYST1`, `SCALEn`, `ATOM`, `HETATM` and `ANISOU` records of a PDB file.
+    ///
+    /// Cartesian coordinates are converted with the `SCALEn` matrix if present and with the
+    /// PDB orthogonalization convention, `a` along x and `b` in the xy plane, otherwise.
+    /// `B` factors become `Uiso`, `ANISOU` records `Uani` in the CIF convention.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let mut cell = None;
+        let mut z = None;
+        let mut scale = [None; 3];
+        let mut sites: Vec<CartesianSite> = Vec::new();
+        let mut atom_info: Vec<PdbAtomInfo> = Vec::new();
+
+        for (i, line) in s.lines().enumerate() {
+            let line_number = i + 1;
+            let record = column(line, 1, 6);
+
+            let float = |start: usize, end: usize, what: &str| {
+                column(line, start, end).parse::<f64>().map_err(|_| {
+                    ParseError::new(line_number, format!("Invalid {}: {}", what, line))
+                })
+            };
+
+            match record {
+                "CRYST1" => {
+                    let mut parameters = [0.0; 6];
+
+                    for (k, (start, end)) in
+                        [(7, 15), (16, 24), (25, 33), (34, 40), (41, 47), (48, 54)]
+                            .into_iter()
+                            .enumerate()
+                    {
+                        parameters[k] = float(start, end, "cell parameter")?;
+                    }
+
+                    let space_group = match column(line, 56, 66) {
+                        "" => "P 1",
+                        space_group => space_group,
+                    };
+
+                    cell = Some(cell_from_parameters(parameters, space_group));
+
+                    z = match column(line, 67, 70) {
+                        "" => None,
+                        value => Some(value.parse().map_err(|_| {
+                            ParseError::new(line_number, format!("Invalid Z: {}", line))
+                        })?),
+                    };
+                }
+                "SCALE1" | "SCALE2" | "SCALE3" => {
+                    let row = [
+                        float(11, 20, "SCALE")?,
+                        float(21, 30, "SCALE")?,
+                        float(31, 40, "SCALE")?,
+                        float(46, 55, "SCALE")?,
+                    ];
+
+                    scale[usize::from(record.as_bytes()[5] - b'1')] = Some(row);
+                }
+                "ATOM" | "HETATM" => {
+                    let name = column(line, 13, 16);
+
+                    let type_ = match column(line, 77, 78) {
+                        "" => element_from_name(line.get(12..16).unwrap_or(name)),
+                        element => capitalize(element),
+                    };
+
+                    let occupancy = match column(line, 55, 60) {
+                        "" => 1.0,
+                        _ => float(55, 60, "occupancy")?,
+                    };
+
+                    let b_iso = match column(line, 61, 66) {
+                        "" => 0.0,
+                        _ => float(61, 66, "B factor")?,
+                    };
+
+                    sites.push(CartesianSite {
+                        label: name.to_string(),
+                        type_,
+                        position: Vector3::new(
+                            float(31, 38, "x")?,
+                            float(39, 46, "y")?,
+                            float(47, 54, "z")?,
+                        ),
+                        occupancy,
+                        b_iso,
+                        u_cart: None,
+                    });
+
+                    atom_info.push(PdbAtomInfo {
+                        hetero: record == "HETATM",
+                        serial: column(line, 7, 11).parse().unwrap_or_default(),
+                        name: name.to_string(),
+                        alt_loc: column(line, 17, 17).to_string(),
+                        residue_name: column(line, 18, 20).to_string(),
+                        chain_id: column(line, 22, 22).to_string(),
+                        residue_sequence: column(line, 23, 26).parse().unwrap_or_default(),
+                        insertion_code: column(line, 27, 27).to_string(),
+                        charge: column(line, 79, 80).to_string(),
+                    });
+                }
+                "ANISOU" => {
+                    let serial = column(line, 7, 11).parse::<u32>().ok();
+
+                    let index = atom_info
+                        .iter()
+                        .rposition(|info| Some(info.serial) == serial)
+                        .ok_or_else(|| {
+                            ParseError::new(line_number, "ANISOU record without matching atom")
+                        })?;
+
+                    let mut u = [0.0; 6];
+
+                    for (k, u) in u.iter_mut().enumerate() {
+                        let start = 29 + 7 * k;
+                        *u = float(start, start + 6, "ANISOU")? * 1e-4;
+                    }
+
+                    sites[index].u_cart = Some(Matrix3::new(
+                        u[0], u[3], u[4], u[3], u[1], u[5], u[4], u[5], u[2],
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        let cell =
+            cell.unwrap_or_else(|| cell_from_parameters([1.0, 1.0, 1.0, 90.0, 90.0, 90.0], "P 1"));
+
+        let (scale, translation) = match scale {
+            [Some(s1), Some(s2), Some(s3)] => (
+                Matrix3::new(
+                    s1[0], s2[0], s3[0], s1[1], s2[1], s3[1], s1[2], s2[2], s3[2],
+                ),
+                Vector3::new(s1[3], s2[3], s3[3]),
+            ),
+            _ => (
+                cell.fractionalization_matrix()
+                    .ok_or_else(|| ParseError::without_line("Unit cell is degenerate"))?,
+                Vector3::new(0.0, 0.0, 0.0),
+            ),
+        };
+
+        let atoms = sites
+            .into_iter()
+            .map(|site| site.into_atom(&cell, scale, translation))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(ParseError::without_line)?;
+
+        Ok(Self {
+            phase: Phase {
+                cell,
+                atoms: Atoms(atoms),
+            },
+            atom_info,
+            z,
+        })
+    }
+
+    /// Creates a PDB file of the asymmetric unit of a phase with each atom in its own
+    /// `HETATM` record.
+    pub fn from_phase(phase: &Phase) -> Self {
+        Self {
+            phase: phase.clone(),
+            atom_info: phase
+                .atoms
+                .iter()
+                .enumerate()
+                .map(|(i, atom)| PdbAtomInfo::from_atom(i, atom))
+                .collect(),
+            z: None,
+        }
+    }
+}
+
+/// Returns the trimmed content of the 1-based, inclusive column range.
+fn column(line: &str, start: usize, end: usize) -> &str {
+    line.get(start - 1..end.min(line.len()))
+        .unwrap_or_default()
+        .trim()
+}
+
+pub(super) fn capitalize(element: &str) -> String {
+    let mut chars = element.chars();
+
+    match chars.next() {
+        Some(first) => {
+            first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+        }
+        None => String::new(),
+    }
+}
+
+/// Derives the element from an atom name whose element symbol is right-justified in the first
+/// two columns, e.g. ` CA ` for a carbon and `CA  ` for a calcium atom.
+fn element_from_name(name: &str) -> String {
+    let symbol = match name.starts_with([' ', '0', '1', '2', '3', '4', '5', '6', '7', '8', '9']) {
+        true => name.get(1..2).unwrap_or_default(),
+        false => name.get(0..2).unwrap_or(name),
+    };
+
+    capitalize(symbol.trim_matches(|c: char| !c.is_ascii_alphabetic()))
+}
+
+/// Returns the number of symmetry operations of the space group of the cell, or 1 if they are
+/// unknown.
+fn symmetry_multiplicity(cell: &Cell) -> u32 {
+    #[cfg(feature = "symmetry")]
+    if let Some(operations) = cell.symmetry_operations() {
+        return operations.len() as u32;
+    }
+
+    #[cfg(not(feature = "symmetry"))]
+    let _ = cell;
+
+    1
+}
+
+impl FromStr for Pdb {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for Pdb {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cell = &self.phase.cell;
+
+        writeln!(
+            f,
+            "CRYST1{:9.3}{:9.3}{:9.3}{:7.2}{:7.2}{:7.2} {:<11}{:4}",
+            cell.a,
+            cell.b,
+            cell.c,
+            cell.alpha,
+            cell.beta,
+            cell.gamma,
+            spaced_space_group_symbol(&cell.space_group),
+            self.z.unwrap_or_else(|| symmetry_multiplicity(cell))
+        )?;
+
+        let orthogonalization_matrix = cell.orthogonalization_matrix();
+
+        // a degenerate cell has no SCALE records
+        if let Some(scale) = cell.fractionalization_matrix() {
+            for (i, row) in [scale.row(0), scale.row(1), scale.row(2)]
+                .iter()
+                .enumerate()
+            {
+                writeln!(
+                    f,
+                    "SCALE{}    {:10.6}{:10.6}{:10.6}     {:10.5}",
+                    i + 1,
+                    row.x,
+                    row.y,
+                    row.z,
+                    0.0
+                )?;
+            }
+        }
+
+        for (i, atom) in self.phase.atoms.iter().enumerate() {
+            let info = match self.atom_info.get(i) {
+                Some(info) => info.clone(),
+                None => PdbAtomInfo::from_atom(i, atom),
+            };
+
+            let atom_type = atom.atom_type();
+
+            // element symbol and charge go into separate columns
+            let element = match atom_type.map(|t| t.element) {
+                Some(element) => element.symbol().to_ascii_uppercase(),
+                None => atom.type_.to_ascii_uppercase(),
+            };
+
+            let charge = match (
+                info.charge.as_str(),
+                atom_type.and_then(|t| t.oxidation_state),
+            ) {
+                ("", Some(charge)) if charge < 0 => format!("{}-", -charge),
+                ("", Some(charge)) if charge > 0 => format!("{}+", charge),
+                (charge, _) => charge.to_string(),
+            };
+
+            // names of atoms with one-letter elements start in the second column
+            let name = match info.name.len() < 4 && element.len() == 1 {
+                true => format!(" {:<3}", info.name),
+                false => format!("{:<4}", info.name),
+            };
+
+            let residue = format!(
+                "{:<4}{:1}{:>3} {:1}{:>4}{:1}",
+                name,
+                info.alt_loc,
+                info.residue_name,
+                info.chain_id,
+                info.residue_sequence,
+                info.insertion_code
+            );
+
+            let position = orthogonalization_matrix * Vector3::new(atom.x, atom.y, atom.z);
+            let b_iso = atom.u_equiv(cell).map(u_to_b).unwrap_or_default();
+
+            writeln!(
+                f,
+                "{:<6}{:>5} {}   {:8.3}{:8.3}{:8.3}{:6.2}{:6.2}          {:>2}{:>2}",
+                if info.hetero { "HETATM" } else { "ATOM" },
+                info.serial,
+                residue,
+                position.x,
+                position.y,
+                position.z,
+                atom.occupancy,
+                b_iso,
+                element,
+                charge
+            )?;
+
+            if matches!(atom.adp_type, AdpType::Uani | AdpType::Bani) {
+                if let Some(u) = atom.u_cif(cell) {
+                    let u = cell.u_cif_to_u_cart(u) * 1e4;
+
+                    writeln!(
+                        f,
+                        "ANISOU{:>5} {} {:7.0}{:7.0}{:7.0}{:7.0}{:7.0}{:7.0}      {:>2}{:>2}",
+                        info.serial,
+                        residue,
+                        u.x.x,
+                        u.y.y,
+                        u.z.z,
+                        u.x.y,
+                        u.x.z,
+                        u.y.z,
+                        element,
+                        charge
+                    )?;
+                }
+            }
+        }
+
+        writeln!(f, "END")
+    }
+}
+
+#[cfg(test)]
+mod test_pdb {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::Pdb;
+    use crate::AdpType;
+
+    const PDB: &str = "\
+CRYST1   10.000   12.000   14.000  90.00  90.00  90.00 P 21 21 21    4
+ATOM      1  N   ALA A   1       1.000   2.400   3.500  1.00 15.79           N
+ANISOU    1  N   ALA A   1     2000   1500   1000    100      0      0       N
+ATOM      2  CA  ALA A   1       2.000   3.000   7.000  0.50 20.00           C
+HETATM    3 CA   CA  B 101       5.000   6.000   7.000  1.00 30.00          CA2+
+END
+";
+
+    #[test]
+    fn test_parse() {
+        let pdb: Pdb = PDB.parse().unwrap();
+        let atoms = &pdb.phase.atoms;
+
+        assert_eq!(pdb.phase.cell.space_group, "P2_12_12_1");
+        #[cfg(feature = "symmetry")]
+        assert_eq!(pdb.phase.cell.space_group_number, 19);
+
+        assert_eq!(atoms.len(), 3);
+        assert_abs_diff_eq!(atoms[0].x, 0.1, epsilon = 1e-12);
+        assert_abs_diff_eq!(atoms[0].y, 0.2, epsilon = 1e-12);
+        assert_abs_diff_eq!(atoms[0].z, 0.25, epsilon = 1e-12);
+        assert_eq!(atoms[0].adp_type, AdpType::Uani);
+        assert_abs_diff_eq!(atoms[0].u11, 0.2, epsilon = 1e-12);
+        assert_abs_diff_eq!(atoms[0].u12, 0.01, epsilon = 1e-12);
+        assert_eq!(atoms[1].type_, "C");
+        assert_eq!(atoms[1].adp_type, AdpType::Uiso);
+        assert_abs_diff_eq!(
+            atoms[1].u_iso_or_equiv,
+            crate::b_to_u(20.0),
+            epsilon = 1e-12
+        );
+        assert_eq!(atoms[2].type_, "Ca");
+        assert_eq!(pdb.atom_info[2].charge, "2+");
+        assert_eq!(pdb.atom_info[2].chain_id, "B");
+        assert!(pdb.atom_info[2].hetero);
+    }
+
+    #[test]
+    fn test_element_from_name() {
+        assert_eq!(super::element_from_name(" CA "), "C");
+        assert_eq!(super::element_from_name("CA  "), "Ca");
+        assert_eq!(super::element_from_name("1HB "), "H");
+    }
+
+    #[test]
+    fn test_scale() {
+        // SCALE records of a cell shifted by half a unit cell along x
+        let pdb: Pdb = "\
+CRYST1   10.000   10.000   10.000  90.00  90.00  90.00 P 1           1
+SCALE1      0.100000  0.000000  0.000000        0.50000
+SCALE2      0.000000  0.100000  0.000000        0.00000
+SCALE3      0.000000  0.000000  0.100000        0.00000
+ATOM      1  O   HOH A   1       1.000   1.000   1.000  1.00 10.00           O
+"
+        .parse()
+        .unwrap();
+
+        assert_abs_diff_eq!(pdb.phase.atoms[0].x, 0.6, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error =
+            "ATOM      1  N   ALA A   1       1.000   x.400   3.500  1.00 15.79           N"
+                .parse::<Pdb>()
+                .unwrap_err();
+
+        assert_eq!(error.line(), Some(1));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let pdb: Pdb = PDB.parse().unwrap();
+        let written = pdb.to_string();
+
+        assert!(written
+            .starts_with("CRYST1   10.000   12.000   14.000  90.00  90.00  90.00 P 21 21 21    4"));
+
+        let reread: Pdb = written.parse().unwrap();
+
+        assert_eq!(reread.atom_info, pdb.atom_info);
+        assert_eq!(reread.z, Some(4));
+
+        for (a, b) in reread.phase.atoms.iter().zip(pdb.phase.atoms.iter()) {
+            assert_eq!(a.type_, b.type_);
+            assert_abs_diff_eq!(a.y, b.y, epsilon = 1e-4);
+            assert_abs_diff_eq!(a.u11, b.u11, epsilon = 1e-4);
+            assert_abs_diff_eq!(a.u_iso_or_equiv, b.u_iso_or_equiv, epsilon = 1e-4);
+        }
+    }
+
+    #[test]
+    fn test_from_phase() {
+        use crate::{Atom, Atoms, Cell, Phase};
+
+        let phase = Phase {
+            cell: Cell {
+                a: 10.0,
+                b: 12.0,
+                c: 14.0,
+                alpha: 90.0,
+                beta: 90.0,
+                gamma: 90.0,
+                space_group: "P2_12_12_1".to_string(),
+                space_group_number: 19,
+                ..Default::default()
+            },
+            atoms: Atoms(vec![Atom {
+                label: "Fe1".to_string(),
+                type_: "Fe2+".to_string(),
+                occupancy: 1.0,
+                ..Default::default()
+            }]),
+        };
+
+        let written = Pdb::from_phase(&phase).to_string();
+
+        #[cfg(feature = "symmetry")]
+        assert!(written.lines().next().unwrap().ends_with("    4"));
+
+        let reread: Pdb = written.parse().unwrap();
+
+        assert_eq!(reread.phase.atoms[0].type_, "Fe");
+        assert_eq!(reread.atom_info[0].charge, "2+");
+    }
+}
@@ -0,0 +1,520 @@
+use std::str::FromStr;
+
+use cgmath::{Array, ElementWise, Matrix3, SquareMatrix, Vector3};
+
+use super::{parse_floats, ParseError};
+use crate::{Atom, Atoms, Cell, Phase};
+
+/// A VASP POSCAR or CONTCAR file.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Poscar {
+    pub comment: String,
+    pub phase: Phase,
+    /// Flags allowing the relaxation of each coordinate of each atom.
+    pub selective_dynamics: Option<Vec<[bool; 3]>>,
+    /// Cartesian velocities in Å/fs in the frame of [`Cell::orthogonalization_matrix`].
+    pub velocities: Option<Vec<[f64; 3]>>,
+}
+
+impl Poscar {
+    /// Parses a POSCAR or CONTCAR file.
+    ///
+    /// The lattice vectors are converted into cell parameters and all coordinates into
+    /// fractional ones. Files without a species line (VASP 4) are rejected, see
+    /// [`Poscar::parse_with_species`].
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        Self::parse_species(s, None)
+    }
+
+    /// Parses a POSCAR or CONTCAR file, taking the species of a file without a species line
+    /// (VASP 4) from `species`, e.g. in the order of the POTCAR file.
+    ///
+    /// Files with a species line keep their species.
+    pub fn parse_with_species(s: &str, species: &[&str]) -> Result<Self, ParseError> {
+        Self::parse_species(s, Some(species))
+    }
+
+    fn parse_species(s: &str, explicit_species: Option<&[&str]>) -> Result<Self, ParseError> {
+        let mut lines = s.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let mut next_line = |what: &str| {
+            lines.next().ok_or_else(|| {
+                ParseError::without_line(format!("Unexpected end of file before {}", what))
+            })
+        };
+
+        let (_, comment) = next_line("the comment")?;
+
+        let (line_number, line) = next_line("the scaling factor")?;
+        let scale = line
+            .split_whitespace()
+            .map(|token| token.parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ParseError::new(line_number, "Invalid scaling factor"))?;
+
+        let mut vectors = [Vector3::new(0.0, 0.0, 0.0); 3];
+        let mut lattice_line_number = line_number;
+
+        for vector in vectors.iter_mut() {
+            let (line_number, line) = next_line("the lattice vectors")?;
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            let [x, y, z] = parse_floats::<3>(line_number, &tokens, "lattice vector")?;
+            *vector = Vector3::new(x, y, z);
+            lattice_line_number = line_number;
+        }
+
+        let linearly_dependent = || {
+            ParseError::new(
+                lattice_line_number,
+                "Lattice vectors are linearly dependent",
+            )
+        };
+
+        if Matrix3::from_cols(vectors[0], vectors[1], vectors[2]).determinant() == 0.0 {
+            return Err(linearly_dependent());
+        }
+
+        let scale = match scale.as_slice() {
+            [s] if *s < 0.0 => {
+                let volume = Matrix3::from_cols(vectors[0], vectors[1], vectors[2])
+                    .determinant()
+                    .abs();
+                Vector3::from_value((s.abs() / volume).cbrt())
+            }
+            [s] => Vector3::from_value(*s),
+            [sx, sy, sz] => Vector3::new(*sx, *sy, *sz),
+            _ => return Err(ParseError::new(line_number, "Invalid scaling factor")),
+        };
+
+        let vectors = vectors.map(|v| v.mul_element_wise(scale));
+
+        let (line_number, line) = next_line("the atom counts")?;
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+        let (species, (line_number, counts_line)) =
+            match tokens.first().is_some_and(|t| t.parse::<usize>().is_err()) {
+                true => (
+                    Some(tokens.iter().map(|t| t.to_string()).collect::<Vec<_>>()),
+                    next_line("the atom counts")?,
+                ),
+                false => (None, (line_number, line)),
+            };
+
+        let counts = counts_line
+            .split_whitespace()
+            .map(|t| t.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ParseError::new(line_number, "Invalid atom counts"))?;
+
+        let species = match species {
+            Some(species) if species.len() == counts.len() => species,
+            Some(_) => {
+                return Err(ParseError::new(
+                    line_number,
+                    "Number of species and atom counts differ",
+                ))
+            }
+            None => match explicit_species {
+                Some(species) if species.len() == counts.len() => {
+                    species.iter().map(|s| s.to_string()).collect()
+                }
+                Some(_) => {
+                    return Err(ParseError::new(
+                        line_number,
+                        "Number of given species and atom counts differ",
+                    ))
+                }
+                None => {
+                    return Err(ParseError::new(
+                        line_number,
+                        "Missing species line, the species of VASP 4 files must be given",
+                    ))
+                }
+            },
+        };
+
+        let (mut line_number, mut line) = next_line("the coordinate mode")?;
+        let has_selective_dynamics = line.trim_start().starts_with(['S', 's']);
+
+        if has_selective_dynamics {
+            (line_number, line) = next_line("the coordinate mode")?;
+        }
+
+        let is_cartesian = match line.trim_start().chars().next() {
+            Some('C' | 'c' | 'K' | 'k') => true,
+            Some('D' | 'd') => false,
+            _ => return Err(ParseError::new(line_number, "Invalid coordinate mode")),
+        };
+
+        let lattice = Matrix3::from_cols(vectors[0], vectors[1], vectors[2]);
+        let inverse_lattice = lattice.invert().ok_or_else(linearly_dependent)?;
+
+        let number_of_atoms: usize = counts.iter().sum();
+        let mut atoms = Vec::with_capacity(number_of_atoms);
+        let mut selective_dynamics = Vec::new();
+
+        for (type_, count) in species.iter().zip(&counts) {
+            for i in 0..*count {
+                let (line_number, line) = next_line("the atom positions")?;
+                let tokens = line.split_whitespace().collect::<Vec<_>>();
+                let [x, y, z] = parse_floats::<3>(line_number, &tokens, "position")?;
+
+                let position = match is_cartesian {
+                    true => inverse_lattice * Vector3::new(x, y, z).mul_element_wise(scale),
+                    false => Vector3::new(x, y, z),
+                };
+
+                if has_selective_dynamics {
+                    let flags = tokens.get(3..6).ok_or_else(|| {
+                        ParseError::new(line_number, "Missing selective dynamics flags")
+                    })?;
+
+                    let mut parsed = [true; 3];
+
+                    for (flag, token) in parsed.iter_mut().zip(flags) {
+                        *flag = match token.chars().next() {
+                            Some('T' | 't') => true,
+                            Some('F' | 'f') => false,
+                            _ => {
+                                return Err(ParseError::new(
+                                    line_number,
+                                    format!("Invalid selective dynamics flag: {}", token),
+                                ))
+                            }
+                        };
+                    }
+
+                    selective_dynamics.push(parsed);
+                }
+
+                atoms.push(Atom {
+                    label: format!("{}{}", type_, i + 1),
+                    type_: type_.clone(),
+                    x: position.x,
+                    y: position.y,
+                    z: position.z,
+                    occupancy: 1.0,
+                    ..Default::default()
+                });
+            }
+        }
+
+        let cell = Cell::from_lattice_vectors(vectors);
+
+        // velocities are rotated into the frame of the cell parameters
+        let rotation = cell.orthogonalization_matrix() * inverse_lattice;
+        let velocities = parse_velocities(&mut lines, number_of_atoms, &lattice)?.map(|v| {
+            v.into_iter()
+                .map(|v| {
+                    let v = rotation * Vector3::from(v);
+                    [v.x, v.y, v.z]
+                })
+                .collect()
+        });
+
+        Ok(Self {
+            comment: comment.to_string(),
+            phase: Phase {
+                cell,
+                atoms: Atoms(atoms),
+            },
+            selective_dynamics: has_selective_dynamics.then_some(selective_dynamics),
+            velocities,
+        })
+    }
+
+    /// Creates a POSCAR from a phase expanded to P1.
+    ///
+    /// Atoms are grouped by their type in order of first appearance as VASP requires.
+    #[cfg(feature = "symmetry")]
+    pub fn from_phase(phase: &Phase) -> Self {
+        let p1 = phase.expand_to_p1();
+
+        let mut species: Vec<&str> = Vec::new();
+
+        for atom in p1.atoms.iter() {
+            if !species.contains(&atom.type_.as_str()) {
+                species.push(&atom.type_);
+            }
+
+            if atom.occupancy != 1.0 {
+                log::warn!(
+                    "Atom {} has an occupancy of {}. POSCAR files only support full occupancy.",
+                    atom.label,
+                    atom.occupancy
+                );
+            }
+        }
+
+        let atoms = species
+            .iter()
+            .flat_map(|s| p1.atoms.iter().filter(move |a| a.type_ == *s).cloned())
+            .collect::<Vec<_>>();
+
+        let comment = species
+            .iter()
+            .map(|s| format!("{}{}", s, atoms.iter().filter(|a| a.type_ == *s).count()))
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        Self {
+            comment,
+            phase: Phase {
+                cell: p1.cell,
+                atoms: Atoms(atoms),
+            },
+            selective_dynamics: None,
+            velocities: None,
+        }
+    }
+}
+
+fn parse_velocities<'a>(
+    lines: &mut impl Iterator<Item = (usize, &'a str)>,
+    number_of_atoms: usize,
+    lattice: &Matrix3<f64>,
+) -> Result<Option<Vec<[f64; 3]>>, ParseError> {
+    let mut lines = lines
+        .skip_while(|(_, line)| line.trim().is_empty())
+        .peekable();
+
+    let Some((_, first)) = lines.peek() else {
+        return Ok(None);
+    };
+
+    let is_direct = match first.trim_start().chars().next() {
+        Some('D' | 'd') => true,
+        Some(c) if c.is_alphabetic() => false,
+        _ => {
+            let is_numeric = first
+                .split_whitespace()
+                .next()
+                .is_some_and(|t| t.parse::<f64>().is_ok());
+
+            if !is_numeric {
+                return Ok(None);
+            }
+
+            false
+        }
+    };
+
+    if first.trim_start().starts_with(char::is_alphabetic) {
+        lines.next();
+    }
+
+    let mut velocities = Vec::with_capacity(number_of_atoms);
+
+    for _ in 0..number_of_atoms {
+        let Some((line_number, line)) = lines.next() else {
+            return Err(ParseError::without_line(
+                "Unexpected end of file in velocities",
+            ));
+        };
+
+        let tokens = line.split_whitespace().collect::<Vec<_>>();
+        let [x, y, z] = parse_floats::<3>(line_number, &tokens, "velocity")?;
+
+        let velocity = match is_direct {
+            true => lattice * Vector3::new(x, y, z),
+            false => Vector3::new(x, y, z),
+        };
+
+        velocities.push([velocity.x, velocity.y, velocity.z]);
+    }
+
+    Ok(Some(velocities))
+}
+
+impl FromStr for Poscar {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for Poscar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let m = self.phase.cell.orthogonalization_matrix();
+
+        writeln!(f, "{}", self.comment.lines().next().unwrap_or_default())?;
+        writeln!(f, "1.0")?;
+
+        for v in [m.x, m.y, m.z] {
+            writeln!(f, " {:20.12} {:20.12} {:20.12}", v.x, v.y, v.z)?;
+        }
+
+        let mut species: Vec<(&str, usize)> = Vec::new();
+
+        for atom in self.phase.atoms.iter() {
+            match species.last_mut() {
+                Some((type_, count)) if *type_ == atom.type_ => *count += 1,
+                _ => species.push((&atom.type_, 1)),
+            }
+        }
+
+        let names = species
+            .iter()
+            .map(|(s, _)| format!("{:>5}", s))
+            .collect::<String>();
+        let counts = species
+            .iter()
+            .map(|(_, c)| format!("{:>5}", c))
+            .collect::<String>();
+
+        writeln!(f, "{}", names)?;
+        writeln!(f, "{}", counts)?;
+
+        if self.selective_dynamics.is_some() {
+            writeln!(f, "Selective dynamics")?;
+        }
+
+        writeln!(f, "Direct")?;
+
+        for (i, atom) in self.phase.atoms.iter().enumerate() {
+            write!(f, " {:18.12} {:18.12} {:18.12}", atom.x, atom.y, atom.z)?;
+
+            if let Some(flags) = self.selective_dynamics.as_ref().and_then(|s| s.get(i)) {
+                for flag in flags {
+                    write!(f, " {}", if *flag { "T" } else { "F" })?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        if let Some(velocities) = &self.velocities {
+            writeln!(f)?;
+
+            for v in velocities {
+                writeln!(f, " {:18.12} {:18.12} {:18.12}", v[0], v[1], v[2])?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_poscar {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::Poscar;
+
+    const POSCAR: &str = "Cubic BN
+   3.57
+ 0.0 0.5 0.5
+ 0.5 0.0 0.5
+ 0.5 0.5 0.0
+   B    N
+   1    1
+Selective dynamics
+Direct
+ 0.00 0.00 0.00 F F F
+ 0.25 0.25 0.25 T T T
+
+ 0.0 0.0 0.0
+ 0.1 0.2 0.3
+";
+
+    #[test]
+    fn test_parse() {
+        let poscar: Poscar = POSCAR.parse().unwrap();
+        let cell = &poscar.phase.cell;
+
+        assert_abs_diff_eq!(cell.a, 3.57 / 2f64.sqrt(), epsilon = 1e-10);
+        assert_abs_diff_eq!(cell.alpha, 60.0, epsilon = 1e-10);
+        assert_eq!(poscar.phase.atoms.len(), 2);
+        assert_eq!(poscar.phase.atoms[1].type_, "N");
+        assert_eq!(poscar.phase.atoms[1].label, "N1");
+        assert_abs_diff_eq!(poscar.phase.atoms[1].x, 0.25, epsilon = 1e-12);
+        assert_eq!(poscar.selective_dynamics, Some(vec![[false; 3], [true; 3]]));
+
+        // velocities are rotated with the lattice
+        let [vx, vy, vz] = poscar.velocities.unwrap()[1];
+        assert_abs_diff_eq!(vx * vx + vy * vy + vz * vz, 0.14, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_cartesian() {
+        let vasp4 = "Si
+ -20.0
+ 4.0 0.0 0.0
+ 0.0 5.0 0.0
+ 0.0 0.0 8.0
+ 2
+Cartesian
+ 0.0 0.0 0.0
+ 1.0 2.5 2.0
+";
+
+        // the comment line is no species line
+        assert_eq!(vasp4.parse::<Poscar>().unwrap_err().line(), Some(6));
+        assert!(Poscar::parse_with_species(vasp4, &["Si", "O"]).is_err());
+
+        let poscar = Poscar::parse_with_species(vasp4, &["Si"]).unwrap();
+
+        // the volume of 20 Å³ halves the lattice vectors
+        assert_abs_diff_eq!(poscar.phase.cell.a, 2.0, epsilon = 1e-10);
+        assert_eq!(poscar.phase.atoms[0].type_, "Si");
+        assert_abs_diff_eq!(poscar.phase.atoms[1].x, 0.25, epsilon = 1e-12);
+        assert_abs_diff_eq!(poscar.phase.atoms[1].y, 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(poscar.phase.atoms[1].z, 0.25, epsilon = 1e-12);
+        assert!(poscar.selective_dynamics.is_none());
+        assert!(poscar.velocities.is_none());
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = "comment\n1.0\n1 0 0\n0 1 0\n0 0 x\n1\nDirect\n0 0 0\n"
+            .parse::<Poscar>()
+            .unwrap_err();
+
+        assert_eq!(error.line(), Some(5));
+        assert!("comment\n1.0\n1 0 0\n".parse::<Poscar>().is_err());
+
+        let error = "comment\n1.0\n1 0 0\n0 1 0\n2 1 0\nH\n1\nDirect\n0 0 0\n"
+            .parse::<Poscar>()
+            .unwrap_err();
+
+        assert_eq!(error.line(), Some(5));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let poscar: Poscar = POSCAR.parse().unwrap();
+        let written = poscar.to_string();
+        let parsed: Poscar = written.parse().unwrap();
+
+        assert_abs_diff_eq!(parsed.phase.cell.a, poscar.phase.cell.a, epsilon = 1e-9);
+        assert_abs_diff_eq!(
+            parsed.phase.cell.gamma,
+            poscar.phase.cell.gamma,
+            epsilon = 1e-9
+        );
+        assert_eq!(parsed.selective_dynamics, poscar.selective_dynamics);
+        assert_eq!(parsed.phase.atoms[1].type_, "N");
+        assert_abs_diff_eq!(parsed.phase.atoms[1].z, 0.25, epsilon = 1e-9);
+    }
+
+    #[cfg(feature = "symmetry")]
+    #[test]
+    fn test_from_phase() {
+        let phase = crate::test_util::rock_salt();
+
+        let written = Poscar::from_phase(&phase).to_string();
+        let lines = written.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[0], "Na4 Cl4");
+        assert_eq!(
+            lines[5].split_whitespace().collect::<Vec<_>>(),
+            ["Na", "Cl"]
+        );
+        assert_eq!(lines[6].split_whitespace().collect::<Vec<_>>(), ["4", "4"]);
+
+        let parsed: Poscar = written.parse().unwrap();
+
+        assert_eq!(parsed.phase.atoms.len(), 8);
+        assert_abs_diff_eq!(parsed.phase.cell.a, 5.64, epsilon = 1e-9);
+    }
+}
@@ -0,0 +1,682 @@
+use std::sync::{Arc, LazyLock, PoisonError, RwLock};
+
+use super::{
+    CastepCell, LammpsData, LammpsDumpFrame, Mmcif, ParseError, Pdb, Poscar, PwInput, XyzFrame,
+};
+use crate::Phase;
+
+/// A structure file format that can be added to the formats used by [`Phase::read`] and
+/// [`Phase::write`] with [`register_format`].
+pub trait StructureFormat: Send + Sync {
+    /// Name used in error messages, e.g. `POSCAR`.
+    fn name(&self) -> &str;
+
+    /// File name patterns with at most one `*` wildcard, e.g. `*.xyz` or `POSCAR*`. Case is
+    /// ignored.
+    fn file_patterns(&self) -> &[&str];
+
+    /// Returns whether the content looks like this format. Only used for files whose name
+    /// matches no pattern.
+    fn sniff(&self, content: &str) -> bool;
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError>;
+
+    /// Returns the file content for a phase, or `None` if the format cannot be written.
+    fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+        let _ = phase;
+        None
+    }
+}
+
+/// Error raised while reading or writing a structure file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StructureFileError {
+    format: Option<String>,
+    line: Option<usize>,
+    message: String,
+}
+
+impl StructureFileError {
+    pub fn new(message: impl AsRef<str>) -> Self {
+        Self {
+            format: None,
+            line: None,
+            message: message.as_ref().to_string(),
+        }
+    }
+
+    fn from_parse_error(format: &dyn StructureFormat, error: ParseError) -> Self {
+        Self {
+            format: Some(format.name().to_string()),
+            line: error.line(),
+            message: error.message().to_string(),
+        }
+    }
+
+    fn with_format(self, format: &dyn StructureFormat) -> Self {
+        Self {
+            format: self.format.or_else(|| Some(format.name().to_string())),
+            ..self
+        }
+    }
+
+    /// Name of the format that failed, if one was chosen.
+    pub fn format(&self) -> Option<&str> {
+        self.format.as_deref()
+    }
+
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Display for StructureFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(format) = &self.format {
+            write!(f, "{}: ", format)?;
+        }
+
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for StructureFileError {}
+
+static FORMATS: LazyLock<RwLock<Vec<Arc<dyn StructureFormat>>>> =
+    LazyLock::new(|| RwLock::new(builtin_formats()));
+
+/// Adds a format. Formats registered later take precedence over earlier ones and over the
+/// built-in formats.
+pub fn register_format(format: impl StructureFormat + 'static) {
+    FORMATS
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(0, Arc::new(format));
+}
+
+/// Returns the names of all formats in order of precedence.
+pub fn format_names() -> Vec<String> {
+    formats().iter().map(|f| f.name().to_string()).collect()
+}
+
+fn formats() -> Vec<Arc<dyn StructureFormat>> {
+    FORMATS
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Reads a phase from the content of a file, choosing the format by file name or, if no
+/// pattern matches, by content.
+pub fn read_structure(content: &str, file_name: &str) -> Result<Phase, StructureFileError> {
+    let formats = formats();
+
+    let format = formats
+        .iter()
+        .find(|f| matches_file_name(f.as_ref(), file_name))
+        .or_else(|| formats.iter().find(|f| f.sniff(content)))
+        .ok_or_else(|| {
+            StructureFileError::new(format!("Unknown structure format: {}", file_name))
+        })?;
+
+    format
+        .read(content)
+        .map_err(|error| StructureFileError::from_parse_error(format.as_ref(), error))
+}
+
+/// Returns the content of a file in the first writable format matching the file name.
+pub fn write_structure(phase: &Phase, file_name: &str) -> Result<String, StructureFileError> {
+    formats()
+        .iter()
+        .filter(|f| matches_file_name(f.as_ref(), file_name))
+        .find_map(|f| {
+            f.write(phase)
+                .map(|content| content.map_err(|error| error.with_format(f.as_ref())))
+        })
+        .unwrap_or_else(|| {
+            Err(StructureFileError::new(format!(
+                "No writable format for {}",
+                file_name
+            )))
+        })
+}
+
+fn matches_file_name(format: &dyn StructureFormat, file_name: &str) -> bool {
+    let name = file_name.to_ascii_lowercase();
+
+    format.file_patterns().iter().any(|pattern| {
+        let pattern = pattern.to_ascii_lowercase();
+
+        match pattern.split_once('*') {
+            Some((prefix, suffix)) => {
+                name.len() >= prefix.len() + suffix.len()
+                    && name.starts_with(prefix)
+                    && name.ends_with(suffix)
+            }
+            None => name == pattern,
+        }
+    })
+}
+
+/// Built-in formats, ordered so that formats with distinctive content are sniffed first.
+fn builtin_formats() -> Vec<Arc<dyn StructureFormat>> {
+    let mut formats: Vec<Arc<dyn StructureFormat>> = vec![
+        Arc::new(LammpsDumpFormat),
+        Arc::new(CastepFormat),
+        Arc::new(PwFormat),
+    ];
+
+    #[cfg(feature = "symmetry")]
+    formats.push(Arc::new(ShelxFormat));
+
+    formats.extend([
+        Arc::new(MmcifFormat) as Arc<dyn StructureFormat>,
+        Arc::new(CifFormat),
+        Arc::new(PdbFormat),
+        Arc::new(LammpsDataFormat),
+        Arc::new(XyzFormat),
+        Arc::new(PoscarFormat),
+    ]);
+
+    formats
+}
+
+fn is_float_row(line: &str, counts: &[usize]) -> bool {
+    let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+    counts.contains(&tokens.len()) && tokens.iter().all(|t| t.parse::<f64>().is_ok())
+}
+
+struct XyzFormat;
+
+impl StructureFormat for XyzFormat {
+    fn name(&self) -> &str {
+        "extended XYZ"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["*.xyz", "*.extxyz"]
+    }
+
+    fn sniff(&self, content: &str) -> bool {
+        content
+            .lines()
+            .next()
+            .is_some_and(|line| line.trim().parse::<usize>().is_ok())
+    }
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError> {
+        XyzFrame::parse(content).map(|frame| frame.phase)
+    }
+
+    #[cfg(feature = "symmetry")]
+    fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+        Some(XyzFrame::from_phase(phase).to_xyz())
+    }
+}
+
+struct PoscarFormat;
+
+impl StructureFormat for PoscarFormat {
+    fn name(&self) -> &str {
+        "POSCAR"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["POSCAR*", "CONTCAR*", "*.vasp", "*.poscar"]
+    }
+
+    fn sniff(&self, content: &str) -> bool {
+        let lines = content.lines().map(str::trim).collect::<Vec<_>>();
+
+        lines.len() > 5
+            && is_float_row(lines[1], &[1, 3])
+            && lines[2..5].iter().all(|line| is_float_row(line, &[3]))
+    }
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError> {
+        Poscar::parse(content).map(|poscar| poscar.phase)
+    }
+
+    #[cfg(feature = "symmetry")]
+    fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+        Some(Ok(Poscar::from_phase(phase).to_string()))
+    }
+}
+
+struct PdbFormat;
+
+impl StructureFormat for PdbFormat {
+    fn name(&self) -> &str {
+        "PDB"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["*.pdb", "*.ent"]
+    }
+
+    fn sniff(&self, content: &str) -> bool {
+        content.lines().any(|line| {
+            ["CRYST1", "ATOM  ", "HETATM"]
+                .iter()
+                .any(|record| line.starts_with(record))
+        })
+    }
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError> {
+        Pdb::parse(content).map(|pdb| pdb.phase)
+    }
+
+    fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+        Some(Ok(Pdb::from_phase(phase).to_string()))
+    }
+}
+
+struct MmcifFormat;
+
+impl StructureFormat for MmcifFormat {
+    fn name(&self) -> &str {
+        "mmCIF"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["*.mmcif"]
+    }
+
+    fn sniff(&self, content: &str) -> bool {
+        content.contains("_atom_site.Cartn_x")
+    }
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError> {
+        Mmcif::parse(content).map(|mmcif| mmcif.phase)
+    }
+
+    fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+        let pdb = Pdb::from_phase(phase);
+
+        Some(Ok(Mmcif {
+            name: String::new(),
+            phase: pdb.phase,
+            atom_info: pdb.atom_info,
+        }
+        .to_string()))
+    }
+}
+
+/// CIF files with fractional coordinates, or with Cartesian ones as in mmCIF.
+struct CifFormat;
+
+impl StructureFormat for CifFormat {
+    fn name(&self) -> &str {
+        "CIF"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["*.cif"]
+    }
+
+    fn sniff(&self, content: &str) -> bool {
+        content.contains("_atom_site_fract_x")
+    }
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError> {
+        match MmcifFormat.sniff(content) {
+            true => MmcifFormat.read(content),
+            false => super::mmcif::parse_small_molecule_cif(content),
+        }
+    }
+
+    fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+        MmcifFormat.write(phase)
+    }
+}
+
+#[cfg(feature = "symmetry")]
+struct ShelxFormat;
+
+#[cfg(feature = "symmetry")]
+impl StructureFormat for ShelxFormat {
+    fn name(&self) -> &str {
+        "SHELX"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["*.res", "*.ins"]
+    }
+
+    fn sniff(&self, content: &str) -> bool {
+        let has_instruction = |instruction: &str| {
+            content
+                .lines()
+                .any(|line| line.to_ascii_uppercase().starts_with(instruction))
+        };
+
+        has_instruction("CELL ") && has_instruction("SFAC ")
+    }
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError> {
+        super::Shelx::parse(content).map(|shelx| shelx.phase)
+    }
+
+    fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+        Some(Ok(super::Shelx::from_phase(phase).to_string()))
+    }
+}
+
+struct PwFormat;
+
+impl StructureFormat for PwFormat {
+    fn name(&self) -> &str {
+        "pw.x"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["*.pwi"]
+    }
+
+    fn sniff(&self, content: &str) -> bool {
+        content.lines().any(|line| {
+            line.trim().eq_ignore_ascii_case("&system")
+                || line.trim_start().starts_with("ATOMIC_POSITIONS")
+        })
+    }
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError> {
+        PwInput::parse(content).map(|input| input.phase)
+    }
+
+    #[cfg(all(feature = "data", feature = "symmetry"))]
+    fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+        Some(Ok(PwInput::from_phase(phase).to_string()))
+    }
+}
+
+struct CastepFormat;
+
+impl StructureFormat for CastepFormat {
+    fn name(&self) -> &str {
+        "CASTEP"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["*.cell"]
+    }
+
+    fn sniff(&self, content: &str) -> bool {
+        content.lines().any(|line| {
+            let line = line.trim().to_ascii_uppercase();
+            line.starts_with("%BLOCK LATTICE_") || line.starts_with("%BLOCK POSITIONS_")
+        })
+    }
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError> {
+        CastepCell::parse(content).map(|cell| cell.phase)
+    }
+
+    #[cfg(feature = "symmetry")]
+    fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+        Some(Ok(CastepCell::from_phase(phase).to_string()))
+    }
+}
+
+struct LammpsDataFormat;
+
+impl StructureFormat for LammpsDataFormat {
+    fn name(&self) -> &str {
+        "LAMMPS data"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["*.lmp", "*.data", "data.*"]
+    }
+
+    fn sniff(&self, content: &str) -> bool {
+        content
+            .lines()
+            .any(|line| line.trim_end().ends_with("xlo xhi"))
+    }
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError> {
+        LammpsData::parse(content).map(|data| data.phase)
+    }
+
+    #[cfg(all(feature = "data", feature = "symmetry"))]
+    fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+        Some(LammpsData::from_phase(phase, [1, 1, 1]).to_data())
+    }
+}
+
+struct LammpsDumpFormat;
+
+impl StructureFormat for LammpsDumpFormat {
+    fn name(&self) -> &str {
+        "LAMMPS dump"
+    }
+
+    fn file_patterns(&self) -> &[&str] {
+        &["*.lammpstrj", "*.dump", "dump.*"]
+    }
+
+    fn sniff(&self, content: &str) -> bool {
+        content.trim_start().starts_with("ITEM: TIMESTEP")
+    }
+
+    fn read(&self, content: &str) -> Result<Phase, ParseError> {
+        LammpsDumpFrame::parse(content).map(|frame| frame.phase)
+    }
+}
+
+#[cfg(test)]
+mod test_registry {
+    use super::{
+        format_names, read_structure, register_format, write_structure, ParseError,
+        StructureFileError, StructureFormat,
+    };
+    use crate::Phase;
+
+    const XYZ: &str = "\
+2
+Lattice=\"3 0 0 0 3 0 0 0 3\"
+Na 0.0 0.0 0.0
+Cl 1.5 1.5 1.5
+";
+
+    const POSCAR: &str = "\
+NaCl
+1.0
+3.0 0.0 0.0
+0.0 3.0 0.0
+0.0 0.0 3.0
+Na Cl
+1 1
+Direct
+0.0 0.0 0.0
+0.5 0.5 0.5
+";
+
+    const CIF: &str = "\
+data_nacl
+_cell_length_a 5.6402(3)
+_cell_length_b 5.6402(3)
+_cell_length_c 5.6402(3)
+_cell_angle_alpha 90
+_cell_angle_beta 90
+_cell_angle_gamma 90
+_symmetry_space_group_name_H-M 'F m -3 m'
+loop_
+_atom_site_label
+_atom_site_type_symbol
+_atom_site_fract_x
+_atom_site_fract_y
+_atom_site_fract_z
+_atom_site_U_iso_or_equiv
+Na1 Na+ 0 0 0 0.012
+Cl1 Cl- 0.5 0.5 0.5 0.010
+";
+
+    #[test]
+    fn test_read_by_file_name() {
+        let phase = read_structure(XYZ, "nacl.XYZ").unwrap();
+
+        assert_eq!(phase.atoms.len(), 2);
+        assert_eq!(phase.atoms[1].type_, "Cl");
+        assert_eq!(phase.atoms[1].x, 0.5);
+
+        let phase = read_structure(POSCAR, "CONTCAR_relaxed").unwrap();
+        assert_eq!(phase.atoms[1].type_, "Cl");
+    }
+
+    #[test]
+    fn test_sniffing() {
+        assert_eq!(read_structure(XYZ, "structure.txt").unwrap().atoms.len(), 2);
+        assert_eq!(
+            read_structure(POSCAR, "structure.txt").unwrap().atoms.len(),
+            2
+        );
+
+        let error = read_structure("nothing to see", "structure.txt").unwrap_err();
+
+        assert_eq!(error.format(), None);
+        assert!(error.message().contains("structure.txt"));
+    }
+
+    #[test]
+    fn test_cif_extension() {
+        let phase = read_structure(POSCAR, "POSCAR").unwrap();
+        let mmcif = write_structure(&phase, "nacl.mmcif").unwrap();
+
+        // mmCIF content is still recognized in files with the common extension
+        assert_eq!(read_structure(&mmcif, "nacl.cif").unwrap().atoms.len(), 2);
+
+        // small-molecule files are not handed to the mmCIF reader
+        assert!(read_structure(CIF, "nacl.mmcif").is_err());
+        assert_eq!(read_structure(CIF, "nacl.cif").unwrap().atoms.len(), 2);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = read_structure(&POSCAR.replace("0.5 0.5", "0.5 O.5"), "POSCAR").unwrap_err();
+
+        assert_eq!(error.format(), Some("POSCAR"));
+        assert_eq!(error.line(), Some(10));
+        assert!(error.to_string().starts_with("POSCAR: line 10: "));
+    }
+
+    #[test]
+    fn test_register_format() {
+        struct Empty;
+
+        impl StructureFormat for Empty {
+            fn name(&self) -> &str {
+                "empty"
+            }
+
+            fn file_patterns(&self) -> &[&str] {
+                &["*.empty", "*.xyz.test"]
+            }
+
+            fn sniff(&self, _content: &str) -> bool {
+                false
+            }
+
+            fn read(&self, content: &str) -> Result<Phase, ParseError> {
+                match content.is_empty() {
+                    true => Ok(Phase::default()),
+                    false => Err(ParseError::new(1, "Expected an empty file")),
+                }
+            }
+
+            fn write(&self, phase: &Phase) -> Option<Result<String, StructureFileError>> {
+                match phase.atoms.is_empty() {
+                    true => None,
+                    false => Some(Err(StructureFileError::new("Expected no atoms"))),
+                }
+            }
+        }
+
+        register_format(Empty);
+
+        assert_eq!(format_names()[0], "empty");
+        assert_eq!(read_structure("", "a.empty").unwrap(), Phase::default());
+        assert_eq!(
+            read_structure(XYZ, "a.xyz.test").unwrap_err().to_string(),
+            "empty: line 1: Expected an empty file"
+        );
+        assert!(write_structure(&Phase::default(), "a.empty").is_err());
+
+        let phase = read_structure(XYZ, "nacl.xyz").unwrap();
+
+        assert_eq!(
+            write_structure(&phase, "a.empty").unwrap_err().to_string(),
+            "empty: Expected no atoms"
+        );
+    }
+
+    #[cfg(feature = "symmetry")]
+    #[test]
+    fn test_read_write() {
+        use crate::{Atoms, Cell};
+
+        let phase = Phase {
+            cell: Cell {
+                a: 3.0,
+                b: 3.0,
+                c: 3.0,
+                alpha: 90.0,
+                beta: 90.0,
+                gamma: 90.0,
+                space_group_number: 1,
+                ..Default::default()
+            },
+            atoms: Atoms(read_structure(XYZ, "nacl.xyz").unwrap().atoms.0),
+        };
+
+        let path = std::env::temp_dir().join(format!("crystallib-{}.vasp", std::process::id()));
+
+        phase.write(&path).unwrap();
+        let reread = Phase::read(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let reread = reread.unwrap();
+
+        assert_eq!(reread.atoms.len(), 2);
+        assert_eq!(reread.atoms[1].type_, "Cl");
+        assert_eq!(reread.cell.a, 3.0);
+        assert!(Phase::read(std::env::temp_dir().join("missing.xyz")).is_err());
+    }
+
+    #[test]
+    fn test_read_cif() {
+        let path = std::env::temp_dir().join(format!("crystallib-{}.cif", std::process::id()));
+
+        std::fs::write(&path, CIF).unwrap();
+        let phase = Phase::read(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let phase = phase.unwrap();
+
+        assert_eq!(phase.cell.a, 5.6402);
+        assert_eq!(phase.cell.space_group, "Fm-3m");
+        assert_eq!(phase.atoms.len(), 2);
+        assert_eq!(phase.atoms[1].type_, "Cl-");
+        assert_eq!(phase.atoms[1].x, 0.5);
+        assert_eq!(phase.atoms[0].u_iso_or_equiv, 0.012);
+
+        // mmCIF files with the same extension are still read as such
+        let mmcif = write_structure(&phase, "nacl.cif").unwrap();
+        let reread = read_structure(&mmcif, "nacl.cif").unwrap();
+
+        assert_eq!(reread.atoms.len(), 2);
+        assert!((reread.atoms[1].x - 0.5).abs() < 1e-3);
+
+        assert_eq!(read_structure(CIF, "structure.txt").unwrap().atoms.len(), 2);
+    }
+}
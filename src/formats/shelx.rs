@@ -0,0 +1,875 @@
+use std::str::FromStr;
+
+use cgmath::{Matrix4, SquareMatrix, Vector3};
+
+use super::{cell_from_parameters, parse_floats, ParseError};
+use crate::{
+    symmetry::{deduplicate_operations, is_same_operation},
+    AdpType, Atom, Atoms, Cell, Phase, SpaceGroupSymmetryOperations, SPACEGROUP_SYMBOLS,
+};
+
+/// Instructions that are not atoms. Lines starting with other names are read as atoms.
+const INSTRUCTIONS: [&str; 83] = [
+    "TITL", "CELL", "ZERR", "LATT", "SYMM", "SFAC", "DISP", "UNIT", "LAUE", "REM", "MORE", "TIME",
+    "END", "HKLF", "OMIT", "SHEL", "BASF", "TWIN", "TWST", "EXTI", "SWAT", "HOPE", "MERG", "SPEC",
+    "RESI", "MOVE", "ANIS", "AFIX", "HFIX", "FRAG", "FEND", "EXYZ", "EADP", "EQIV", "CONN", "PART",
+    "BIND", "FREE", "DFIX", "DANG", "BUMP", "SAME", "SADI", "CHIV", "FLAT", "DELU", "SIMU", "DEFS",
+    "ISOR", "NCSY", "SUMP", "L.S.", "CGLS", "BLOC", "DAMP", "STIR", "WGHT", "FVAR", "BOND", "CONF",
+    "MPLA", "RTAB", "HTAB", "LIST", "ACTA", "SIZE", "TEMP", "WPDB", "FMAP", "GRID", "PLAN", "MOLE",
+    "ABIN", "ANSC", "ANSR", "NOTR", "NEUT", "XNPD", "RIGU", "PRIG", "WIGL", "LONE", "BEDE",
+];
+
+/// A SHELX `.ins` or `.res` file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shelx {
+    pub title: String,
+    /// Wavelength in Å from the `CELL` instruction.
+    pub wavelength: f64,
+    /// `Z` and the standard uncertainties of the cell parameters from the `ZERR` instruction.
+    pub zerr: Option<[f64; 7]>,
+    pub phase: Phase,
+    /// Lattice type of the `LATT` instruction, `1` to `7` for P, I, R (obverse), F, A, B and C
+    /// centering, negative for non-centrosymmetric structures.
+    pub lattice_type: i8,
+    /// Operations of the `SYMM` instructions without the identity, centering and inversion.
+    pub symmetry_operations: Vec<Matrix4<f64>>,
+    /// Atom types of the `SFAC` instruction.
+    pub sfac: Vec<String>,
+    /// Number of atoms of each type per unit cell from the `UNIT` instruction.
+    pub unit: Vec<f64>,
+    /// Remaining instructions before the first atom, e.g. `L.S.` or `WGHT`, in file order.
+    pub instructions: Vec<String>,
+    /// Free variables of the `FVAR` instruction, starting with the overall scale factor.
+    pub free_variables: Vec<f64>,
+    /// `PART`, `AFIX` and the encoded parameters of each atom of the phase.
+    pub atom_info: Vec<ShelxAtomInfo>,
+}
+
+impl Default for Shelx {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            wavelength: 0.71073,
+            zerr: None,
+            phase: Phase::default(),
+            lattice_type: 1,
+            symmetry_operations: Vec::new(),
+            sfac: Vec::new(),
+            unit: Vec::new(),
+            instructions: Vec::new(),
+            free_variables: vec![1.0],
+            atom_info: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ShelxAtomInfo {
+    pub part: i32,
+    pub afix: i32,
+    /// Site occupation factor as written in the file, e.g. `21.0` for the value of free
+    /// variable 2. It is written back as long as it matches the occupancy of the atom.
+    pub sof: Option<f64>,
+    /// Isotropic displacement parameter as written in the file, e.g. `-1.5` for 1.5 times
+    /// `U_eq` of the preceding atom. It is written back as long as it matches `Uiso`.
+    pub u_iso: Option<f64>,
+}
+
+impl Shelx {
+    /// Parses a SHELX `.ins` or `.res` file.
+    ///
+    /// The space group is identified by comparing the operations generated from `LATT` and
+    /// `SYMM` with the symmetry tables. Operations of a setting that is not tabulated, e.g.
+    /// `P2_1/n`, are kept as [`Cell::setting_operations`]. Free variable references of the coordinates, site
+    /// occupation factors and displacement parameters are resolved, riding `Uiso` are
+    /// multiplied with the `U_eq` of the preceding atom. Site occupation factors are divided
+    /// by the site symmetry, so `occupancy` holds the chemical occupancy. Q peaks are skipped.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let mut shelx = Self {
+            free_variables: Vec::new(),
+            ..Default::default()
+        };
+
+        let mut cell_parameters = None;
+        let mut parameter_cell = None;
+        let mut part = 0;
+        let mut afix = 0;
+        let mut atoms: Vec<Atom> = Vec::new();
+        let mut riding_reference = None;
+
+        for (line_number, line) in join_continuation_lines(s) {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+            let Some(first) = tokens.first() else {
+                continue;
+            };
+
+            // restraints may apply to a residue, e.g. `DFIX_1` or `SADI_CCF`
+            let instruction = first.to_ascii_uppercase();
+            let instruction = match instruction.split_once('_') {
+                Some((name, _)) if INSTRUCTIONS.contains(&name) => name,
+                _ => instruction.as_str(),
+            };
+            let arguments = &tokens[1..];
+
+            match instruction {
+                "TITL" => shelx.title = arguments.join(" "),
+                "CELL" => {
+                    let values = parse_floats::<7>(line_number, arguments, "CELL")?;
+                    shelx.wavelength = values[0];
+                    let parameters = [
+                        values[1], values[2], values[3], values[4], values[5], values[6],
+                    ];
+
+                    cell_parameters = Some(parameters);
+                    parameter_cell = Some(cell_from_parameters(parameters, ""));
+                }
+                "ZERR" => shelx.zerr = Some(parse_floats::<7>(line_number, arguments, "ZERR")?),
+                "LATT" => {
+                    shelx.lattice_type = arguments
+                        .first()
+                        .and_then(|t| t.parse::<i8>().ok())
+                        .filter(|n| (1..=7).contains(&n.abs()))
+                        .ok_or_else(|| ParseError::new(line_number, "Invalid LATT"))?;
+                }
+                "SYMM" => shelx.symmetry_operations.push(
+                    crate::parse_symmetry_operation(&arguments.join(" "))
+                        .ok_or_else(|| ParseError::new(line_number, "Invalid SYMM"))?,
+                ),
+                "SFAC" => shelx.sfac.extend(
+                    arguments
+                        .iter()
+                        .filter(|t| t.parse::<f64>().is_err())
+                        .map(|t| capitalize(t)),
+                ),
+                "UNIT" => {
+                    shelx.unit = arguments
+                        .iter()
+                        .map(|t| t.parse::<f64>())
+                        .collect::<Result<_, _>>()
+                        .map_err(|_| ParseError::new(line_number, "Invalid UNIT"))?;
+                }
+                "FVAR" => {
+                    let values = arguments
+                        .iter()
+                        .map(|t| t.parse::<f64>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| ParseError::new(line_number, "Invalid FVAR"))?;
+                    shelx.free_variables.extend(values);
+                }
+                "PART" => part = parse_integer(line_number, arguments, "PART")?,
+                "AFIX" => afix = parse_integer(line_number, arguments, "AFIX")?,
+                "REM" => {}
+                "HKLF" | "END" => break,
+                _ if INSTRUCTIONS.contains(&instruction) => {
+                    if atoms.is_empty() {
+                        shelx.instructions.push(line.trim().to_string());
+                    }
+                }
+                _ if is_q_peak(first) => {}
+                _ => {
+                    let cell = parameter_cell
+                        .as_ref()
+                        .ok_or_else(|| ParseError::new(line_number, "Atom before CELL"))?;
+
+                    let (atom, sof, u_iso) = parse_atom(
+                        line_number,
+                        &tokens,
+                        &shelx.sfac,
+                        &shelx.free_variables,
+                        cell,
+                        &mut riding_reference,
+                    )?;
+
+                    atoms.push(atom);
+                    shelx.atom_info.push(ShelxAtomInfo {
+                        part,
+                        afix,
+                        sof,
+                        u_iso,
+                    });
+                }
+            }
+        }
+
+        let parameters = cell_parameters.ok_or_else(|| ParseError::without_line("Missing CELL"))?;
+
+        let operations = expand_operations(shelx.lattice_type, &shelx.symmetry_operations);
+        let space_group_number = SpaceGroupSymmetryOperations::find(&operations);
+
+        let space_group = match space_group_number {
+            Some(number) => SPACEGROUP_SYMBOLS[&number].clone(),
+            None => String::new(),
+        };
+
+        let mut cell = cell_from_parameters(parameters, &space_group);
+
+        match space_group_number {
+            Some(number) => cell.space_group_number = number,
+            None => cell.setting_operations = operations,
+        }
+
+        for atom in atoms.iter_mut() {
+            if let Some(site_symmetry) = atom.site_symmetry(&cell, 1e-2) {
+                atom.occupancy *= site_symmetry.operations.len() as f64;
+                atom.multiplicity = Some(site_symmetry.multiplicity as f64);
+            }
+        }
+
+        if shelx.free_variables.is_empty() {
+            shelx.free_variables.push(1.0);
+        }
+
+        shelx.phase = Phase {
+            cell,
+            atoms: Atoms(atoms),
+        };
+
+        Ok(shelx)
+    }
+
+    /// Creates an instruction file of a phase with `LATT` and `SYMM` derived from its space
+    /// group and `UNIT` from the content of the unit cell.
+    ///
+    /// Space groups whose centering is not expressible with `LATT` are written with all
+    /// operations as `SYMM` instructions.
+    pub fn from_phase(phase: &Phase) -> Self {
+        let identity = vec![Matrix4::identity()];
+        let operations = phase.cell.symmetry_operations().unwrap_or(&identity);
+        let (lattice_type, symmetry_operations) = compress_operations(operations);
+
+        let mut sfac: Vec<String> = Vec::new();
+
+        for atom in phase.atoms.iter() {
+            if !sfac.contains(&atom.type_) {
+                sfac.push(atom.type_.clone());
+            }
+        }
+
+        let p1 = phase.expand_to_p1();
+        let unit = sfac
+            .iter()
+            .map(|type_| {
+                p1.atoms
+                    .iter()
+                    .filter(|a| a.type_ == *type_)
+                    .map(|a| a.occupancy)
+                    .sum()
+            })
+            .collect();
+
+        Self {
+            title: phase.cell.space_group.clone(),
+            phase: phase.clone(),
+            lattice_type,
+            symmetry_operations,
+            sfac,
+            unit,
+            instructions: vec![
+                "L.S. 10".to_string(),
+                "FMAP 2".to_string(),
+                "PLAN 20".to_string(),
+            ],
+            atom_info: vec![ShelxAtomInfo::default(); phase.atoms.len()],
+            ..Default::default()
+        }
+    }
+}
+
+/// Joins lines ending with `=` with the following line and removes `!` comments.
+fn join_continuation_lines(s: &str) -> Vec<(usize, String)> {
+    let mut lines: Vec<(usize, String)> = Vec::new();
+    let mut continued = false;
+
+    for (i, line) in s.lines().enumerate() {
+        let line = line.split('!').next().unwrap_or_default().trim_end();
+        let (content, continues) = match line.strip_suffix('=') {
+            Some(content) => (content, true),
+            None => (line, false),
+        };
+
+        match (continued, lines.last_mut()) {
+            (true, Some((_, last))) => {
+                last.push(' ');
+                last.push_str(content);
+            }
+            _ => lines.push((i + 1, content.to_string())),
+        }
+
+        continued = continues;
+    }
+
+    lines
+}
+
+fn parse_integer(line_number: usize, arguments: &[&str], what: &str) -> Result<i32, ParseError> {
+    arguments
+        .first()
+        .map_or(Ok(0), |t| t.parse::<i32>())
+        .map_err(|_| ParseError::new(line_number, format!("Invalid {}", what)))
+}
+
+fn is_q_peak(name: &str) -> bool {
+    name.len() > 1 && name.starts_with(['Q', 'q']) && name[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+
+    match chars.next() {
+        Some(first) => {
+            first.to_ascii_uppercase().to_string() + &chars.as_str().to_ascii_lowercase()
+        }
+        None => String::new(),
+    }
+}
+
+/// Resolves a parameter encoded as `10 m + p`: free for `m = 0`, fixed for `|m| = 1`,
+/// `p fv(m)` for `m > 1` and `p (fv(-m) - 1)` for `m < -1`.
+fn decode_free_variable(
+    line_number: usize,
+    value: f64,
+    free_variables: &[f64],
+) -> Result<f64, ParseError> {
+    let m = (value / 10.0).round();
+    let p = value - 10.0 * m;
+
+    let free_variable = |m: f64| {
+        free_variables
+            .get(m as usize - 1)
+            .copied()
+            .ok_or_else(|| ParseError::new(line_number, format!("Missing free variable {}", m)))
+    };
+
+    match m as i64 {
+        0 => Ok(value),
+        1 | -1 => Ok(p),
+        m if m > 1 => Ok(p * free_variable(m as f64)?),
+        m => Ok(p * (free_variable(-m as f64)? - 1.0)),
+    }
+}
+
+/// Parses `name sfac x y z [sof [U | U11 U22 U33 U23 U13 U12]]` and also returns the site
+/// occupation factor and isotropic `U` as encoded in the file.
+fn parse_atom(
+    line_number: usize,
+    tokens: &[&str],
+    sfac: &[String],
+    free_variables: &[f64],
+    cell: &Cell,
+    riding_reference: &mut Option<f64>,
+) -> Result<(Atom, Option<f64>, Option<f64>), ParseError> {
+    let type_ = tokens
+        .get(1)
+        .and_then(|t| t.parse::<usize>().ok())
+        .and_then(|i| sfac.get(i.checked_sub(1)?))
+        .ok_or_else(|| ParseError::new(line_number, "Invalid atom type"))?;
+
+    let encoded = tokens[2..]
+        .iter()
+        .map(|t| {
+            t.parse::<f64>()
+                .map_err(|_| ParseError::new(line_number, format!("Invalid number: {}", t)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let values = encoded
+        .iter()
+        .map(|v| decode_free_variable(line_number, *v, free_variables))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if values.len() < 3 {
+        return Err(ParseError::new(line_number, "Missing coordinates"));
+    }
+
+    let mut atom = Atom {
+        label: tokens[0].to_string(),
+        type_: type_.clone(),
+        x: values[0],
+        y: values[1],
+        z: values[2],
+        occupancy: values.get(3).copied().unwrap_or(1.0),
+        ..Default::default()
+    };
+
+    match values.get(4..) {
+        Some([u11, u22, u33, u23, u13, u12, ..]) => {
+            atom.adp_type = AdpType::Uani;
+            atom.u11 = *u11;
+            atom.u22 = *u22;
+            atom.u33 = *u33;
+            atom.u23 = *u23;
+            atom.u13 = *u13;
+            atom.u12 = *u12;
+            atom.u_iso_or_equiv = cell.u_equiv(atom.u_ani());
+            *riding_reference = Some(atom.u_iso_or_equiv);
+        }
+        Some([u, ..]) if (-5.0..=-0.5).contains(u) => {
+            let reference = riding_reference.ok_or_else(|| {
+                ParseError::new(line_number, "Riding Uiso without preceding atom")
+            })?;
+
+            atom.u_iso_or_equiv = -u * reference;
+        }
+        Some([u, ..]) => {
+            atom.u_iso_or_equiv = *u;
+            *riding_reference = Some(*u);
+        }
+        _ => {
+            atom.u_iso_or_equiv = 0.05;
+            *riding_reference = Some(0.05);
+        }
+    }
+
+    let u_iso = match encoded.len() {
+        5 => encoded.get(4).copied(),
+        _ => None,
+    };
+
+    Ok((atom, encoded.get(3).copied(), u_iso))
+}
+
+fn centering_translations(lattice_type: i8) -> Vec<Vector3<f64>> {
+    let translations: &[[f64; 3]] = match lattice_type.abs() {
+        2 => &[[0.5, 0.5, 0.5]],
+        3 => &[
+            [2.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0],
+            [1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0],
+        ],
+        4 => &[[0.0, 0.5, 0.5], [0.5, 0.0, 0.5], [0.5, 0.5, 0.0]],
+        5 => &[[0.0, 0.5, 0.5]],
+        6 => &[[0.5, 0.0, 0.5]],
+        7 => &[[0.5, 0.5, 0.0]],
+        _ => &[],
+    };
+
+    std::iter::once(Vector3::new(0.0, 0.0, 0.0))
+        .chain(translations.iter().map(|t| Vector3::from(*t)))
+        .collect()
+}
+
+/// Generates all operations from the lattice type and the `SYMM` operations.
+fn expand_operations(lattice_type: i8, symmetry_operations: &[Matrix4<f64>]) -> Vec<Matrix4<f64>> {
+    let inversions = match lattice_type > 0 {
+        true => vec![1.0, -1.0],
+        false => vec![1.0],
+    };
+
+    let mut operations = Vec::new();
+
+    for centering in centering_translations(lattice_type) {
+        for inversion in &inversions {
+            for operation in std::iter::once(&Matrix4::identity()).chain(symmetry_operations) {
+                let mut operation = operation * *inversion;
+                operation.w.w = 1.0;
+                operation.w += centering.extend(0.0);
+                operations.push(operation);
+            }
+        }
+    }
+
+    deduplicate_operations(&mut operations);
+
+    operations
+}
+
+/// Splits the operations of a space group into the lattice type and the `SYMM` operations.
+fn compress_operations(operations: &[Matrix4<f64>]) -> (i8, Vec<Matrix4<f64>>) {
+    let rotation_is = |operation: &Matrix4<f64>, sign: f64| {
+        (0..3).all(|i| {
+            (0..3).all(|j| {
+                let expected = if i == j { sign } else { 0.0 };
+                (operation[i][j] - expected).abs() < 1e-6
+            })
+        })
+    };
+
+    let centering = operations
+        .iter()
+        .filter(|operation| rotation_is(operation, 1.0))
+        .map(|operation| operation.w.truncate())
+        .collect::<Vec<_>>();
+
+    let lattice_type = (1..=7).find(|lattice_type| {
+        let translations = centering_translations(*lattice_type);
+
+        translations.len() == centering.len()
+            && translations.iter().all(|t| {
+                centering.iter().any(|c| {
+                    let d = c - t;
+                    (0..3).all(|i| (d[i] - d[i].round()).abs() < 1e-6)
+                })
+            })
+    });
+
+    let Some(lattice_type) = lattice_type else {
+        let symmetry_operations = operations
+            .iter()
+            .filter(|operation| !is_same_operation(operation, &Matrix4::identity()))
+            .copied()
+            .collect();
+
+        return (-1, symmetry_operations);
+    };
+
+    let is_centrosymmetric = operations.iter().any(|operation| {
+        rotation_is(operation, -1.0)
+            && centering.iter().any(|c| {
+                let d = operation.w.truncate() - c;
+                (0..3).all(|i| (d[i] - d[i].round()).abs() < 1e-6)
+            })
+    });
+
+    let lattice_type = match is_centrosymmetric {
+        true => lattice_type,
+        false => -lattice_type,
+    };
+
+    let mut symmetry_operations: Vec<Matrix4<f64>> = Vec::new();
+
+    for operation in operations {
+        let generated = expand_operations(lattice_type, &symmetry_operations);
+
+        if !generated.iter().any(|g| is_same_operation(g, operation)) {
+            symmetry_operations.push(*operation);
+        }
+    }
+
+    (lattice_type, symmetry_operations)
+}
+
+/// Encodes a value as fixed, `10 + p`.
+fn fixed(value: f64) -> f64 {
+    10.0 + value
+}
+
+/// Formats a parameter with up to eight decimals, keeping at least five.
+fn format_parameter(value: f64) -> String {
+    let mut formatted = format!("{:.8}", value);
+
+    while formatted.ends_with('0') && formatted.len() - formatted.find('.').unwrap() > 6 {
+        formatted.pop();
+    }
+
+    formatted
+}
+
+impl Shelx {
+    /// Returns whether an encoded parameter resolves to `value` with the free variables.
+    fn decodes_to(&self, encoded: f64, value: f64) -> bool {
+        decode_free_variable(0, encoded, &self.free_variables)
+            .is_ok_and(|decoded| (decoded - value).abs() < 1e-6)
+    }
+}
+
+impl FromStr for Shelx {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for Shelx {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cell = &self.phase.cell;
+
+        writeln!(f, "TITL {}", self.title)?;
+        writeln!(
+            f,
+            "CELL {:.5} {:.4} {:.4} {:.4} {:.3} {:.3} {:.3}",
+            self.wavelength, cell.a, cell.b, cell.c, cell.alpha, cell.beta, cell.gamma
+        )?;
+
+        if let Some(zerr) = self.zerr {
+            let values = zerr.map(|v| v.to_string());
+            writeln!(f, "ZERR {}", values.join(" "))?;
+        }
+
+        writeln!(f, "LATT {}", self.lattice_type)?;
+
+        for operation in &self.symmetry_operations {
+            let operation = crate::format_symmetry_operation(operation).to_ascii_uppercase();
+            writeln!(f, "SYMM {}", operation.replace(',', ", "))?;
+        }
+
+        writeln!(f, "SFAC {}", self.sfac.join(" "))?;
+
+        let unit = self
+            .unit
+            .iter()
+            .map(|n| format!("{}", n))
+            .collect::<Vec<_>>();
+        writeln!(f, "UNIT {}", unit.join(" "))?;
+
+        for instruction in &self.instructions {
+            writeln!(f, "{}", instruction)?;
+        }
+
+        let free_variables = self
+            .free_variables
+            .iter()
+            .map(|v| format!("{:.5}", v))
+            .collect::<Vec<_>>();
+        writeln!(f, "FVAR {}", free_variables.join(" "))?;
+
+        let (mut part, mut afix) = (0, 0);
+        let mut riding_reference = None;
+
+        for (i, atom) in self.phase.atoms.iter().enumerate() {
+            let info = self.atom_info.get(i).cloned().unwrap_or_default();
+
+            if info.part != part {
+                writeln!(f, "PART {}", info.part)?;
+                part = info.part;
+            }
+
+            if info.afix != afix {
+                writeln!(f, "AFIX {}", info.afix)?;
+                afix = info.afix;
+            }
+
+            let sfac = self
+                .sfac
+                .iter()
+                .position(|s| s.eq_ignore_ascii_case(&atom.type_))
+                .map_or(1, |i| i + 1);
+
+            let stabilizer = atom
+                .site_symmetry(cell, 1e-2)
+                .map_or(1, |s| s.operations.len());
+
+            let sof = atom.occupancy / stabilizer as f64;
+            let sof = info
+                .sof
+                .filter(|encoded| self.decodes_to(*encoded, sof))
+                .unwrap_or_else(|| fixed(sof));
+
+            write!(
+                f,
+                "{:<4} {} {:.6} {:.6} {:.6} {}",
+                atom.label,
+                sfac,
+                atom.x,
+                atom.y,
+                atom.z,
+                format_parameter(sof)
+            )?;
+
+            match (&atom.adp_type, atom.u_cif(cell)) {
+                (AdpType::Uani | AdpType::Bani, Some(u)) => {
+                    writeln!(
+                        f,
+                        " {:.5} {:.5} =\n    {:.5} {:.5} {:.5} {:.5}",
+                        u.x.x, u.y.y, u.z.z, u.y.z, u.x.z, u.x.y
+                    )?;
+                    riding_reference = Some(cell.u_equiv(u));
+                }
+                _ => {
+                    let u = atom.u_equiv(cell).unwrap_or(0.05);
+                    let riding = info.u_iso.filter(|encoded| {
+                        (-5.0..=-0.5).contains(encoded)
+                            && riding_reference
+                                .is_some_and(|r: f64| (-encoded * r - u).abs() < 1e-6)
+                    });
+
+                    match riding {
+                        Some(encoded) => writeln!(f, " {}", format_parameter(encoded))?,
+                        None => {
+                            let u_iso = info
+                                .u_iso
+                                .filter(|encoded| {
+                                    !(-5.0..=-0.5).contains(encoded) && self.decodes_to(*encoded, u)
+                                })
+                                .unwrap_or(u);
+
+                            writeln!(f, " {}", format_parameter(u_iso))?;
+                            riding_reference = Some(u);
+                        }
+                    }
+                }
+            }
+        }
+
+        if afix != 0 {
+            writeln!(f, "AFIX 0")?;
+        }
+
+        if part != 0 {
+            writeln!(f, "PART 0")?;
+        }
+
+        writeln!(f, "HKLF 4")?;
+        writeln!(f, "END")
+    }
+}
+
+#[cfg(test)]
+mod test_shelx {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::Shelx;
+    use crate::AdpType;
+
+    const INS: &str = "\
+TITL test in P2(1)/c
+CELL 0.71073 5.0000 6.0000 7.0000 90.000 100.000 90.000
+ZERR 4 0.001 0.001 0.001 0 0.01 0
+LATT 1
+SYMM -X, 1/2+Y, 1/2-Z
+SFAC C O H
+UNIT 8 4 16
+L.S. 10
+WGHT 0.1 0
+FVAR 1.2 0.7
+C1    1    0.100000    0.200000    0.300000    11.00000    0.02000    0.03000 =
+         0.04000    0.00100    0.00200    0.00300
+AFIX 137
+H1A   3    0.150000    0.250000    0.350000    11.00000   -1.50000
+AFIX 0
+PART 1
+O1    2    0.500000    0.000000    0.000000    20.50000    0.05000
+PART 2
+O2    2    0.400000    0.100000    0.100000   -21.00000    0.05000
+PART 0
+Q1    1    0.1 0.1 0.1 11.0 0.05 1.2
+HKLF 4
+END
+";
+
+    #[test]
+    fn test_parse() {
+        let shelx: Shelx = INS.parse().unwrap();
+        let atoms = &shelx.phase.atoms;
+
+        assert_eq!(shelx.phase.cell.space_group_number, 14);
+        assert_eq!(shelx.sfac, vec!["C", "O", "H"]);
+        assert_eq!(shelx.instructions, vec!["L.S. 10", "WGHT 0.1 0"]);
+        assert_eq!(atoms.len(), 4);
+
+        assert_eq!(atoms[0].adp_type, AdpType::Uani);
+        assert_abs_diff_eq!(atoms[0].u33, 0.04, epsilon = 1e-12);
+        assert_abs_diff_eq!(atoms[0].u12, 0.003, epsilon = 1e-12);
+
+        // riding on C1
+        assert_eq!(atoms[1].type_, "H");
+        assert_abs_diff_eq!(
+            atoms[1].u_iso_or_equiv,
+            1.5 * atoms[0].u_iso_or_equiv,
+            epsilon = 1e-12
+        );
+        assert_eq!(shelx.atom_info[1].afix, 137);
+
+        // on an inversion center, so the site occupation factor is half the occupancy
+        assert_abs_diff_eq!(atoms[2].occupancy, 0.7, epsilon = 1e-12);
+        assert_eq!(atoms[2].multiplicity, Some(2.0));
+        assert_abs_diff_eq!(atoms[3].occupancy, 0.3, epsilon = 1e-12);
+        assert_eq!(shelx.atom_info[3].part, 2);
+    }
+
+    #[test]
+    fn test_non_tabulated_setting() {
+        let shelx: Shelx = INS
+            .replace("SYMM -X, 1/2+Y, 1/2-Z", "SYMM 1/2-X, 1/2+Y, 1/2-Z")
+            .parse()
+            .unwrap();
+        let atoms = &shelx.phase.atoms;
+
+        // P2_1/n
+        assert_eq!(shelx.phase.cell.setting_operations.len(), 4);
+        assert_abs_diff_eq!(atoms[2].occupancy, 0.7, epsilon = 1e-12);
+        assert_eq!(atoms[2].multiplicity, Some(2.0));
+        assert_eq!(shelx.phase.expand_to_p1().atoms.len(), 14);
+    }
+
+    #[test]
+    fn test_residue_suffix() {
+        let shelx: Shelx = INS
+            .replace(
+                "WGHT 0.1 0\n",
+                "WGHT 0.1 0\nDFIX_1 1.5 C1 O1\nSADI_CCF C1 O1 C1 O2\n",
+            )
+            .parse()
+            .unwrap();
+
+        assert_eq!(shelx.phase.atoms.len(), 4);
+        assert_eq!(
+            shelx.instructions[2..],
+            ["DFIX_1 1.5 C1 O1", "SADI_CCF C1 O1 C1 O2"]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = INS
+            .replace("0.300000", "0.3x")
+            .parse::<Shelx>()
+            .unwrap_err();
+        assert_eq!(error.line(), Some(11));
+
+        let error = INS
+            .replace("20.50000", "30.50000")
+            .parse::<Shelx>()
+            .unwrap_err();
+        assert_eq!(error.line(), Some(17));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let shelx: Shelx = INS.parse().unwrap();
+        let written = shelx.to_string();
+        let reread: Shelx = written.parse().unwrap();
+
+        // free variables and riding parameters are kept encoded
+        assert!(written.contains(" 20.50000 "));
+        assert!(written.contains(" -21.00000 "));
+        assert!(written.contains(" 11.00000 -1.50000\n"));
+
+        assert_eq!(reread.phase.cell.space_group_number, 14);
+        assert_eq!(reread.atom_info, shelx.atom_info);
+
+        for (a, b) in reread.phase.atoms.iter().zip(shelx.phase.atoms.iter()) {
+            assert_abs_diff_eq!(a.occupancy, b.occupancy, epsilon = 1e-5);
+            assert_abs_diff_eq!(a.u_iso_or_equiv, b.u_iso_or_equiv, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_from_phase() {
+        use crate::{Atom, Atoms, Cell, Phase};
+
+        for (number, lattice_type, symm) in [(225, 4, 23), (62, 1, 3), (19, -1, 3), (166, 3, 5)] {
+            let phase = Phase {
+                cell: Cell {
+                    a: 5.0,
+                    b: 6.0,
+                    c: 7.0,
+                    alpha: 90.0,
+                    beta: 90.0,
+                    gamma: if number == 166 { 120.0 } else { 90.0 },
+                    space_group_number: number,
+                    ..Default::default()
+                },
+                atoms: Atoms(vec![Atom {
+                    label: "Na1".to_string(),
+                    type_: "Na".to_string(),
+                    occupancy: 1.0,
+                    u_iso_or_equiv: 0.01,
+                    ..Default::default()
+                }]),
+            };
+
+            let shelx = Shelx::from_phase(&phase);
+
+            assert_eq!(shelx.lattice_type, lattice_type);
+            assert_eq!(shelx.symmetry_operations.len(), symm);
+
+            let reread: Shelx = shelx.to_string().parse().unwrap();
+
+            assert_eq!(reread.phase.cell.space_group_number, number);
+
+            // site occupation factors such as 1/48 are written with eight decimals
+            assert_abs_diff_eq!(reread.phase.atoms[0].occupancy, 1.0, epsilon = 1e-6);
+        }
+    }
+}
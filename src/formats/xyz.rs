@@ -0,0 +1,686 @@
+use std::{
+    iter::{Enumerate, Peekable},
+    str::{FromStr, Lines},
+};
+
+use cgmath::{Matrix3, SquareMatrix, Vector3};
+
+use super::{parse_floats, ParseError, StructureFileError};
+use crate::{Atom, Atoms, Cell, Phase};
+
+/// Columns of files without a `Properties` key.
+const DEFAULT_PROPERTIES: &str = "species:S:1:pos:R:3";
+
+/// A frame of an extended XYZ file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XyzFrame {
+    pub phase: Phase,
+    /// Lattice vectors as columns in the Cartesian frame of the file, from the `Lattice` key
+    /// or given to [`XyzFrame::parse_with_lattice`] for plain XYZ files.
+    pub lattice: Matrix3<f64>,
+    /// Remaining key-value pairs of the comment line, e.g. `energy` or `pbc`, in file order.
+    pub info: Vec<(String, String)>,
+    /// Per-atom columns besides species and positions, e.g. forces or charges.
+    pub properties: Vec<XyzProperty>,
+}
+
+/// A per-atom column of an extended XYZ file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct XyzProperty {
+    pub name: String,
+    /// Number of columns of the property, e.g. 3 for forces.
+    pub columns: usize,
+    /// Values per atom, each with as many entries as the property has columns.
+    pub values: XyzValues,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum XyzValues {
+    Real(Vec<Vec<f64>>),
+    Integer(Vec<Vec<i64>>),
+    Logical(Vec<Vec<bool>>),
+    String(Vec<Vec<String>>),
+}
+
+impl XyzValues {
+    fn type_code(&self) -> char {
+        match self {
+            Self::Real(_) => 'R',
+            Self::Integer(_) => 'I',
+            Self::Logical(_) => 'L',
+            Self::String(_) => 'S',
+        }
+    }
+
+    /// Formats the values of an atom, or returns `None` if the atom has no values.
+    fn format(&self, atom: usize) -> Option<Vec<String>> {
+        let values = match self {
+            Self::Real(v) => v.get(atom)?.iter().map(|x| format!("{:16.8}", x)).collect(),
+            Self::Integer(v) => v.get(atom)?.iter().map(|x| format!("{:8}", x)).collect(),
+            Self::Logical(v) => v
+                .get(atom)?
+                .iter()
+                .map(|x| if *x { "T" } else { "F" }.to_string())
+                .collect(),
+            Self::String(v) => v.get(atom)?.clone(),
+        };
+
+        Some(values)
+    }
+}
+
+impl XyzProperty {
+    /// Returns whether every atom has as many values as the property has columns.
+    fn fits(&self, number_of_atoms: usize) -> bool {
+        (0..number_of_atoms).all(|i| {
+            self.values
+                .format(i)
+                .is_some_and(|values| values.len() == self.columns)
+        })
+    }
+}
+
+/// Iterator over the frames of a multi-frame extended XYZ file.
+pub struct XyzFrames<'a> {
+    lines: Peekable<Enumerate<Lines<'a>>>,
+    /// Lattice of frames without a `Lattice` key.
+    lattice: Option<Matrix3<f64>>,
+}
+
+impl<'a> Iterator for XyzFrames<'a> {
+    type Item = Result<XyzFrame, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self
+            .lines
+            .peek()
+            .is_some_and(|(_, line)| line.trim().is_empty())
+        {
+            self.lines.next();
+        }
+
+        self.lines.peek()?;
+
+        let frame = XyzFrame::parse_frame(
+            &mut self.lines.by_ref().map(|(i, line)| (i + 1, line)),
+            self.lattice,
+        );
+
+        // stop after the first error since the frame boundaries are lost
+        if frame.is_err() {
+            self.lines.by_ref().for_each(drop);
+        }
+
+        Some(frame)
+    }
+}
+
+impl XyzFrame {
+    /// Returns an iterator over the frames of a (multi-frame) extended XYZ file.
+    ///
+    /// Frames without a `Lattice` key are errors.
+    pub fn frames(s: &str) -> XyzFrames<'_> {
+        XyzFrames {
+            lines: s.lines().enumerate().peekable(),
+            lattice: None,
+        }
+    }
+
+    /// Returns an iterator over the frames of a (multi-frame) XYZ file whose frames without a
+    /// `Lattice` key get the given lattice vectors as columns.
+    pub fn frames_with_lattice(s: &str, lattice: Matrix3<f64>) -> XyzFrames<'_> {
+        XyzFrames {
+            lines: s.lines().enumerate().peekable(),
+            lattice: Some(lattice),
+        }
+    }
+
+    /// Parses the first frame of an extended XYZ file.
+    ///
+    /// Files without a `Properties` key are read as if their comment line declared
+    /// `Properties=species:S:1:pos:R:3`. Plain XYZ files have no `Lattice` key and need
+    /// [`XyzFrame::parse_with_lattice`].
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        Self::frames(s)
+            .next()
+            .unwrap_or_else(|| Err(ParseError::without_line("File contains no frames")))
+    }
+
+    /// Parses the first frame of an XYZ file, taking the lattice vectors as columns from
+    /// `lattice` if the frame has no `Lattice` key.
+    pub fn parse_with_lattice(s: &str, lattice: Matrix3<f64>) -> Result<Self, ParseError> {
+        Self::frames_with_lattice(s, lattice)
+            .next()
+            .unwrap_or_else(|| Err(ParseError::without_line("File contains no frames")))
+    }
+
+    fn parse_frame<'a>(
+        lines: &mut impl Iterator<Item = (usize, &'a str)>,
+        default_lattice: Option<Matrix3<f64>>,
+    ) -> Result<Self, ParseError> {
+        let mut next_line = |what: &str| {
+            lines.next().ok_or_else(|| {
+                ParseError::without_line(format!("Unexpected end of file before {}", what))
+            })
+        };
+
+        let (line_number, line) = next_line("the number of atoms")?;
+        let number_of_atoms = line
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| ParseError::new(line_number, "Invalid number of atoms"))?;
+
+        let (comment_line_number, comment) = next_line("the comment")?;
+        let mut info = parse_key_values(comment);
+
+        let mut take = |key: &str| {
+            info.iter()
+                .position(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|i| info.remove(i).1)
+        };
+
+        let lattice = match take("Lattice") {
+            Some(value) => {
+                let tokens = value.split_whitespace().collect::<Vec<_>>();
+                let v = parse_floats::<9>(comment_line_number, &tokens, "Lattice")?;
+
+                Matrix3::new(v[0], v[1], v[2], v[3], v[4], v[5], v[6], v[7], v[8])
+            }
+            None => default_lattice.ok_or_else(|| {
+                ParseError::new(
+                    comment_line_number,
+                    "Missing Lattice, the cell of a plain XYZ file must be given",
+                )
+            })?,
+        };
+
+        let columns = parse_properties(
+            comment_line_number,
+            &take("Properties").unwrap_or_else(|| DEFAULT_PROPERTIES.to_string()),
+        )?;
+
+        let inverse_lattice = lattice.invert().ok_or_else(|| {
+            ParseError::new(
+                comment_line_number,
+                "Lattice vectors are linearly dependent",
+            )
+        })?;
+
+        let species_column = columns
+            .iter()
+            .position(|c| c.name == "species" && c.type_code == 'S' && c.count == 1)
+            .ok_or_else(|| ParseError::new(comment_line_number, "Missing species:S:1 property"))?;
+        let position_column = columns
+            .iter()
+            .position(|c| c.name == "pos" && c.type_code == 'R' && c.count == 3)
+            .ok_or_else(|| ParseError::new(comment_line_number, "Missing pos:R:3 property"))?;
+
+        let mut properties = columns
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| *i != species_column && *i != position_column)
+            .map(|(_, c)| XyzProperty {
+                name: c.name.clone(),
+                columns: c.count,
+                values: match c.type_code {
+                    'R' => XyzValues::Real(Vec::with_capacity(number_of_atoms)),
+                    'I' => XyzValues::Integer(Vec::with_capacity(number_of_atoms)),
+                    'L' => XyzValues::Logical(Vec::with_capacity(number_of_atoms)),
+                    _ => XyzValues::String(Vec::with_capacity(number_of_atoms)),
+                },
+            })
+            .collect::<Vec<_>>();
+
+        let number_of_tokens = columns.iter().map(|c| c.count).sum::<usize>();
+        let mut atoms = Vec::with_capacity(number_of_atoms);
+        let mut counts: Vec<(String, usize)> = Vec::new();
+
+        for _ in 0..number_of_atoms {
+            let (line_number, line) = next_line("the atoms")?;
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+
+            if tokens.len() < number_of_tokens {
+                return Err(ParseError::new(
+                    line_number,
+                    format!("Expected {} columns", number_of_tokens),
+                ));
+            }
+
+            let mut offset = 0;
+            let mut type_ = String::new();
+            let mut position = Vector3::new(0.0, 0.0, 0.0);
+            let mut extra = properties.iter_mut();
+
+            for (i, column) in columns.iter().enumerate() {
+                let tokens = &tokens[offset..offset + column.count];
+                offset += column.count;
+
+                if i == species_column {
+                    type_ = tokens[0].to_string();
+                } else if i == position_column {
+                    let [x, y, z] = parse_floats::<3>(line_number, tokens, "position")?;
+                    position = inverse_lattice * Vector3::new(x, y, z);
+                } else if let Some(property) = extra.next() {
+                    parse_values(line_number, &column.name, tokens, &mut property.values)?;
+                }
+            }
+
+            let index = match counts.iter_mut().find(|(t, _)| *t == type_) {
+                Some((_, count)) => {
+                    *count += 1;
+                    *count
+                }
+                None => {
+                    counts.push((type_.clone(), 1));
+                    1
+                }
+            };
+
+            atoms.push(Atom {
+                label: format!("{}{}", type_, index),
+                type_,
+                x: position.x,
+                y: position.y,
+                z: position.z,
+                occupancy: 1.0,
+                ..Default::default()
+            });
+        }
+
+        let cell = Cell::from_lattice_vectors([lattice.x, lattice.y, lattice.z]);
+
+        Ok(Self {
+            phase: Phase {
+                cell,
+                atoms: Atoms(atoms),
+            },
+            lattice,
+            info,
+            properties,
+        })
+    }
+
+    /// Creates a periodic frame from a phase expanded to P1.
+    #[cfg(feature = "symmetry")]
+    pub fn from_phase(phase: &Phase) -> Self {
+        let phase = phase.expand_to_p1();
+
+        Self {
+            lattice: phase.cell.orthogonalization_matrix(),
+            phase,
+            info: Vec::new(),
+            properties: Vec::new(),
+        }
+    }
+
+    /// Returns the content of the frame as an extended XYZ file.
+    ///
+    /// Unlike the [`Display`](std::fmt::Display) implementation, which skips them, fails for
+    /// properties without values for every atom or with a different number of values than
+    /// columns.
+    pub fn to_xyz(&self) -> Result<String, StructureFileError> {
+        let number_of_atoms = self.phase.atoms.len();
+
+        if let Some(property) = self.properties.iter().find(|p| !p.fits(number_of_atoms)) {
+            return Err(StructureFileError::new(format!(
+                "Property {} does not have {} values for each of the {} atoms",
+                property.name, property.columns, number_of_atoms
+            )));
+        }
+
+        Ok(self.to_string())
+    }
+}
+
+struct Column {
+    name: String,
+    type_code: char,
+    count: usize,
+}
+
+/// Splits the comment line into `key=value` pairs. Values may be quoted, keys without a value
+/// are flags set to `T`.
+fn parse_key_values(comment: &str) -> Vec<(String, String)> {
+    let mut pairs = Vec::new();
+    let mut chars = comment.chars().peekable();
+
+    let read_token = |chars: &mut Peekable<std::str::Chars>| {
+        let mut token = String::new();
+
+        match chars.peek() {
+            Some('"') => {
+                chars.next();
+
+                for c in chars.by_ref() {
+                    if c == '"' {
+                        break;
+                    }
+
+                    token.push(c);
+                }
+            }
+            _ => {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace() && *c != '=') {
+                    token.push(c);
+                }
+            }
+        }
+
+        token
+    };
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let key = read_token(&mut chars);
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let value = match chars.next_if_eq(&'=') {
+            Some(_) => {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                read_token(&mut chars)
+            }
+            None => "T".to_string(),
+        };
+
+        if key.is_empty() {
+            // skip stray characters such as a lone `=`
+            chars.next();
+            continue;
+        }
+
+        pairs.push((key, value));
+    }
+
+    pairs
+}
+
+fn parse_properties(line_number: usize, properties: &str) -> Result<Vec<Column>, ParseError> {
+    let parts = properties.split(':').collect::<Vec<_>>();
+
+    if parts.len() % 3 != 0 {
+        return Err(ParseError::new(line_number, "Invalid Properties"));
+    }
+
+    parts
+        .chunks(3)
+        .map(|chunk| {
+            let type_code = match chunk[1] {
+                "R" | "I" | "L" | "S" => chunk[1].chars().next().unwrap_or('S'),
+                _ => {
+                    return Err(ParseError::new(
+                        line_number,
+                        format!("Invalid property type: {}", chunk[1]),
+                    ))
+                }
+            };
+
+            let count = chunk[2]
+                .parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or_else(|| {
+                    ParseError::new(
+                        line_number,
+                        format!("Invalid number of columns: {}", chunk[2]),
+                    )
+                })?;
+
+            Ok(Column {
+                name: chunk[0].to_string(),
+                type_code,
+                count,
+            })
+        })
+        .collect()
+}
+
+fn parse_values(
+    line_number: usize,
+    name: &str,
+    tokens: &[&str],
+    values: &mut XyzValues,
+) -> Result<(), ParseError> {
+    let error = |token: &str| ParseError::new(line_number, format!("Invalid {}: {}", name, token));
+
+    match values {
+        XyzValues::Real(values) => values.push(
+            tokens
+                .iter()
+                .map(|t| t.parse().map_err(|_| error(t)))
+                .collect::<Result<_, _>>()?,
+        ),
+        XyzValues::Integer(values) => values.push(
+            tokens
+                .iter()
+                .map(|t| t.parse().map_err(|_| error(t)))
+                .collect::<Result<_, _>>()?,
+        ),
+        XyzValues::Logical(values) => values.push(
+            tokens
+                .iter()
+                .map(|t| match *t {
+                    "T" | "t" | "True" | "true" => Ok(true),
+                    "F" | "f" | "False" | "false" => Ok(false),
+                    _ => Err(error(t)),
+                })
+                .collect::<Result<_, _>>()?,
+        ),
+        XyzValues::String(values) => values.push(tokens.iter().map(|t| t.to_string()).collect()),
+    }
+
+    Ok(())
+}
+
+impl FromStr for XyzFrame {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl std::fmt::Display for XyzFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", self.phase.atoms.len())?;
+
+        let mut comment = Vec::new();
+
+        let m = self.lattice;
+        let values = [m.x, m.y, m.z]
+            .iter()
+            .flat_map(|v| [v.x, v.y, v.z])
+            .map(|x| format!("{:.10}", x))
+            .collect::<Vec<_>>();
+
+        comment.push(format!("Lattice=\"{}\"", values.join(" ")));
+
+        // properties without columns or values for every atom cannot be read back
+        let written = self
+            .properties
+            .iter()
+            .filter(|p| p.columns > 0 && p.fits(self.phase.atoms.len()))
+            .collect::<Vec<_>>();
+
+        let mut properties = DEFAULT_PROPERTIES.to_string();
+
+        for property in &written {
+            properties.push_str(&format!(
+                ":{}:{}:{}",
+                property.name,
+                property.values.type_code(),
+                property.columns
+            ));
+        }
+
+        comment.push(format!("Properties={}", properties));
+
+        for (key, value) in &self.info {
+            match value.contains(char::is_whitespace) || value.is_empty() {
+                true => comment.push(format!("{}=\"{}\"", key, value)),
+                false => comment.push(format!("{}={}", key, value)),
+            }
+        }
+
+        writeln!(f, "{}", comment.join(" "))?;
+
+        for (i, atom) in self.phase.atoms.iter().enumerate() {
+            let position = m * Vector3::new(atom.x, atom.y, atom.z);
+
+            write!(
+                f,
+                "{:<4} {:16.8} {:16.8} {:16.8}",
+                atom.type_, position.x, position.y, position.z
+            )?;
+
+            for property in &written {
+                for value in property.values.format(i).unwrap_or_default() {
+                    write!(f, " {}", value)?;
+                }
+            }
+
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_xyz {
+    use cgmath::{assert_abs_diff_eq, Matrix3, SquareMatrix, Vector3};
+
+    use super::{XyzFrame, XyzValues};
+
+    const FRAMES: &str = r#"2
+Lattice="4.0 0.0 0.0 0.0 5.0 0.0 0.0 0.0 6.0" Properties=species:S:1:pos:R:3:forces:R:3:charge:R:1 energy=-10.5 pbc="T T T"
+Na 0.0 0.0 0.0  0.1 0.0 0.0  0.8
+Cl 2.0 2.5 3.0 -0.1 0.0 0.0 -0.8
+2
+Lattice="4.0 0.0 0.0 0.0 5.0 0.0 0.0 0.0 6.0" Properties=species:S:1:pos:R:3:forces:R:3:charge:R:1 energy=-10.4 pbc="T T T"
+Na 0.4 0.0 0.0  0.2 0.0 0.0  0.8
+Cl 2.0 2.5 3.0 -0.2 0.0 0.0 -0.8
+"#;
+
+    #[test]
+    fn test_frames() {
+        let frames = XyzFrame::frames(FRAMES)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(frames.len(), 2);
+
+        let frame = &frames[1];
+
+        assert_abs_diff_eq!(frame.phase.cell.b, 5.0, epsilon = 1e-12);
+        assert_abs_diff_eq!(frame.phase.atoms[0].x, 0.1, epsilon = 1e-12);
+        assert_abs_diff_eq!(frame.phase.atoms[1].z, 0.5, epsilon = 1e-12);
+        assert_eq!(frame.phase.atoms[1].label, "Cl1");
+        assert_eq!(
+            frame.info,
+            vec![
+                ("energy".to_string(), "-10.4".to_string()),
+                ("pbc".to_string(), "T T T".to_string())
+            ]
+        );
+        assert_eq!(frame.properties[0].name, "forces");
+        assert_eq!(
+            frame.properties[1].values,
+            XyzValues::Real(vec![vec![0.8], vec![-0.8]])
+        );
+    }
+
+    #[test]
+    fn test_plain_xyz() {
+        let water = "3\nwater\nO 0.0 0.0 0.1\nH 0.0 0.76 -0.47\nH 0.0 -0.76 -0.47\n";
+
+        // there is no cell without a Lattice key
+        assert_eq!(water.parse::<XyzFrame>().unwrap_err().line(), Some(2));
+
+        let lattice = Matrix3::from_diagonal(Vector3::new(10.0, 10.0, 10.0));
+        let frame = XyzFrame::parse_with_lattice(water, lattice).unwrap();
+
+        assert_eq!(frame.lattice, lattice);
+        assert_eq!(frame.phase.atoms[2].label, "H2");
+        assert_abs_diff_eq!(frame.phase.atoms[1].y, 0.076, epsilon = 1e-12);
+        assert_abs_diff_eq!(frame.phase.cell.a, 10.0, epsilon = 1e-12);
+        assert_eq!(frame.info, vec![("water".to_string(), "T".to_string())]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = "2\nLattice=\"1 0 0 0 1 0 0 0 1\" Properties=species:S:1:pos:R:3\nNa 0.0 0.0\n"
+            .parse::<XyzFrame>()
+            .unwrap_err();
+
+        assert_eq!(error.line(), Some(3));
+        assert!(
+            XyzFrame::frames_with_lattice("1\n\nNa 0 0 0\nx\n", Matrix3::identity())
+                .nth(1)
+                .unwrap()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let frame = XyzFrame::parse(FRAMES).unwrap();
+        let written = frame.to_string();
+
+        assert!(written.contains("Properties=species:S:1:pos:R:3:forces:R:3:charge:R:1"));
+        assert_eq!(XyzFrame::parse(&written).unwrap().info, frame.info);
+
+        let reread = XyzFrame::parse(&written).unwrap();
+
+        assert_eq!(reread.properties, frame.properties);
+        assert_abs_diff_eq!(reread.phase.atoms[1].y, 0.5, epsilon = 1e-8);
+
+        // frames without atoms keep the number of columns of their properties
+        let empty = XyzFrame::parse(
+            "0\nLattice=\"4 0 0 0 5 0 0 0 6\" Properties=species:S:1:pos:R:3:forces:R:3\n",
+        )
+        .unwrap();
+        let reread = XyzFrame::parse(&empty.to_string()).unwrap();
+
+        assert_eq!(reread.properties[0].columns, 3);
+        assert_eq!(reread, empty);
+    }
+
+    #[test]
+    fn test_inconsistent_properties() {
+        let mut frame = XyzFrame::parse(FRAMES).unwrap();
+        frame.properties[1].values = XyzValues::Real(vec![vec![0.8]]);
+
+        assert!(frame.to_xyz().is_err());
+
+        let reread = XyzFrame::parse(&frame.to_string()).unwrap();
+
+        assert_eq!(reread.properties.len(), 1);
+        assert_eq!(reread.properties[0].name, "forces");
+
+        frame.properties[1].values = XyzValues::Real(vec![vec![0.8], vec![-0.8, 0.0]]);
+
+        assert!(frame.to_xyz().is_err());
+
+        frame.properties.truncate(1);
+
+        assert_eq!(frame.to_xyz().unwrap(), frame.to_string());
+    }
+
+    #[cfg(feature = "symmetry")]
+    #[test]
+    fn test_from_phase() {
+        let phase = crate::test_util::rock_salt();
+
+        let frame = XyzFrame::from_phase(&phase);
+        let reread = XyzFrame::parse(&frame.to_string()).unwrap();
+
+        assert_eq!(reread.phase.atoms.len(), 8);
+        assert_abs_diff_eq!(reread.phase.cell.a, 5.64, epsilon = 1e-8);
+    }
+}
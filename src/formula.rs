@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use crate::{DataTableError, Element, Phase, UnknownElementError, ATOMIC_MASSES};
+
+/// Avogadro constant in 1/mol.
+const AVOGADRO_CONSTANT: f64 = 6.022_140_76e23;
+
+/// Counts below this are dropped and counts this close to one are not written.
+const COUNT_TOLERANCE: f64 = 1e-3;
+
+/// A chemical formula with element counts in Hill order: `C` and `H` first if the formula
+/// contains carbon, the other elements alphabetically.
+///
+/// It is displayed like `_chemical_formula_sum`, e.g. `C6 H12 O6` or `Fe0.5 Mn0.5 O`.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ChemicalFormula(Vec<(Element, f64)>);
+
+impl ChemicalFormula {
+    /// Creates a formula from element counts, merging repeated elements and dropping empty ones.
+    pub fn new(counts: impl IntoIterator<Item = (Element, f64)>) -> Self {
+        let mut merged: HashMap<Element, f64> = HashMap::new();
+
+        for (element, count) in counts {
+            *merged.entry(element).or_default() += count;
+        }
+
+        let mut counts = merged
+            .into_iter()
+            .filter(|(_, count)| *count > COUNT_TOLERANCE)
+            .collect::<Vec<_>>();
+
+        let has_carbon = counts.iter().any(|(element, _)| *element == Element::C);
+        let hill_key = |element: &Element| match (has_carbon, element) {
+            (true, Element::C) => (0, ""),
+            (true, Element::H) => (1, ""),
+            _ => (2, element.symbol()),
+        };
+
+        counts.sort_by(|(a, _), (b, _)| hill_key(a).cmp(&hill_key(b)));
+
+        Self(counts)
+    }
+
+    /// Returns the elements and their counts in Hill order.
+    pub fn iter(&self) -> impl Iterator<Item = &(Element, f64)> {
+        self.0.iter()
+    }
+
+    /// Returns the count of an element, `0.0` if it is absent.
+    pub fn count(&self, element: Element) -> f64 {
+        self.0
+            .iter()
+            .find(|(e, _)| *e == element)
+            .map_or(0.0, |(_, count)| *count)
+    }
+
+    /// Returns the formula with all counts divided by `divisor`.
+    pub fn divide(&self, divisor: f64) -> Self {
+        Self(
+            self.0
+                .iter()
+                .map(|(element, count)| (*element, count / divisor))
+                .collect(),
+        )
+    }
+
+    /// Returns the molar mass in g/mol from the standard atomic weights.
+    ///
+    /// Fails if an element has no tabulated mass.
+    pub fn weight(&self) -> Result<f64, DataTableError> {
+        self.0
+            .iter()
+            .map(|(element, count)| {
+                let mass = ATOMIC_MASSES.get(*element).ok_or_else(|| {
+                    DataTableError::new(format!("No atomic mass for element: {element}"))
+                })?;
+
+                Ok(count * f64::from(*mass))
+            })
+            .sum()
+    }
+}
+
+impl std::fmt::Display for ChemicalFormula {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts = self
+            .0
+            .iter()
+            .map(
+                |(element, count)| match (count - 1.0).abs() < COUNT_TOLERANCE {
+                    true => element.to_string(),
+                    false => format!("{element}{}", format_count(*count)),
+                },
+            )
+            .collect::<Vec<_>>();
+
+        f.write_str(&parts.join(" "))
+    }
+}
+
+/// Formats a count with up to four decimals and without trailing zeros.
+fn format_count(count: f64) -> String {
+    let formatted = format!("{count:.4}");
+
+    formatted
+        .trim_end_matches('0')
+        .trim_end_matches('.')
+        .to_string()
+}
+
+impl Phase {
+    /// Returns the contents of the unit cell, i.e. the occupancy-weighted number of atoms of
+    /// each element after expansion by symmetry.
+    pub fn cell_contents(&self) -> Result<ChemicalFormula, UnknownElementError> {
+        Ok(ChemicalFormula::new(self.p1_sites()?))
+    }
+
+    /// Returns the number of formula units per cell, `_cell_formula_units_Z`.
+    ///
+    /// This is the greatest common divisor of the number of positions of each element in the
+    /// cell, so that disordered sites give fractional counts as in `Fe0.5 Mn0.5 O` rather than
+    /// a smaller `Z`.
+    pub fn formula_units(&self) -> Result<u32, UnknownElementError> {
+        let positions = ChemicalFormula::new(self.p1_sites()?.into_iter().map(|(e, _)| (e, 1.0)));
+
+        Ok(positions
+            .iter()
+            .map(|(_, count)| count.round() as u32)
+            .reduce(gcd)
+            .unwrap_or(1)
+            .max(1))
+    }
+
+    /// Returns the formula per formula unit in Hill order, `_chemical_formula_sum`.
+    pub fn chemical_formula(&self) -> Result<ChemicalFormula, UnknownElementError> {
+        let z = self.formula_units()?;
+
+        Ok(self.cell_contents()?.divide(f64::from(z)))
+    }
+
+    /// Returns the molar mass of one formula unit in g/mol, `_chemical_formula_weight`.
+    pub fn formula_weight(&self) -> Result<f64, DataTableError> {
+        self.chemical_formula()
+            .map_err(|e| DataTableError::new(e.to_string()))?
+            .weight()
+    }
+
+    /// Returns the calculated density in g/cm³, `_exptl_crystal_density_diffrn`.
+    ///
+    /// The volume is taken from the cell, or calculated from the lattice parameters if it is
+    /// not set.
+    pub fn density(&self) -> Result<f64, DataTableError> {
+        let volume = match self.cell.volume > 0.0 {
+            true => self.cell.volume,
+            false => self.cell.calculate_volume(),
+        };
+
+        let weight = self
+            .cell_contents()
+            .map_err(|e| DataTableError::new(e.to_string()))?
+            .weight()?;
+
+        // Å³ to cm³
+        Ok(weight / (AVOGADRO_CONSTANT * volume * 1e-24))
+    }
+
+    /// Returns the element and occupancy of every atom of the phase expanded to P1.
+    fn p1_sites(&self) -> Result<Vec<(Element, f64)>, UnknownElementError> {
+        self.expand_to_p1()
+            .atoms
+            .iter()
+            .map(|atom| {
+                let element = atom.element().ok_or_else(|| {
+                    UnknownElementError::new(match atom.type_.trim().is_empty() {
+                        true => &atom.label,
+                        false => &atom.type_,
+                    })
+                })?;
+
+                Ok((element, atom.occupancy))
+            })
+            .collect()
+    }
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+#[cfg(test)]
+mod test_formula {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::ChemicalFormula;
+    use crate::{test_util::phase, Element};
+
+    #[test]
+    fn test_hill_order() {
+        let glucose =
+            ChemicalFormula::new([(Element::O, 6.0), (Element::H, 12.0), (Element::C, 6.0)]);
+        assert_eq!(glucose.to_string(), "C6 H12 O6");
+        assert_abs_diff_eq!(glucose.weight().unwrap(), 180.156, epsilon = 1e-3);
+
+        let formula = ChemicalFormula::new([
+            (Element::O, 1.0),
+            (Element::Na, 1.0),
+            (Element::H, 1.0),
+            (Element::Cl, 0.0),
+        ]);
+        assert_eq!(formula.to_string(), "H Na O");
+        assert_eq!(formula.count(Element::Cl), 0.0);
+    }
+
+    #[test]
+    fn test_rock_salt() {
+        let rock_salt = phase(5.64, "Fm-3m", 225, &[("Na+", [0.0; 3]), ("Cl-", [0.5; 3])]);
+
+        assert_eq!(rock_salt.cell_contents().unwrap().to_string(), "Cl4 Na4");
+        assert_eq!(rock_salt.formula_units().unwrap(), 4);
+        assert_eq!(rock_salt.chemical_formula().unwrap().to_string(), "Cl Na");
+        assert_abs_diff_eq!(rock_salt.formula_weight().unwrap(), 58.44, epsilon = 1e-3);
+        assert_abs_diff_eq!(rock_salt.density().unwrap(), 2.164, epsilon = 1e-3);
+    }
+
+    #[test]
+    fn test_partial_occupancy() {
+        let mut disordered = phase(
+            4.2,
+            "Fm-3m",
+            225,
+            &[("Fe", [0.0; 3]), ("Mn", [0.0; 3]), ("O", [0.5; 3])],
+        );
+        disordered.atoms[0].occupancy = 0.5;
+        disordered.atoms[1].occupancy = 0.5;
+
+        assert_eq!(disordered.formula_units().unwrap(), 4);
+        assert_eq!(
+            disordered.chemical_formula().unwrap().to_string(),
+            "Fe0.5 Mn0.5 O"
+        );
+
+        let vacancies = phase(4.2, "Pm-3m", 221, &[("Xx", [0.0; 3])]);
+        assert!(vacancies.cell_contents().is_err());
+    }
+}
@@ -1,26 +1,134 @@
+mod adp;
 mod atoms;
 mod cell;
+mod element;
+mod ellipsoid;
+mod formats;
 mod phase;
 
 #[cfg(feature = "data")]
 mod data;
 
 #[cfg(feature = "data")]
-pub use data::{COVALENT_RADII, VAN_DER_WAALS_RADII};
+pub use data::{
+    BondValenceParameter, CoordinationGeometry, CoordinationShape, DataTableError, IonicRadius,
+    MissingRadius, RadiusTable, SpinState, ATOMIC_MASSES, ATOMIC_NUMBERS, BOND_VALENCE_PARAMETERS,
+    COVALENT_RADII, ELECTRONEGATIVITIES, JMOL_COLORS, NEUTRON_SCATTERING_LENGTHS, SHANNON_RADII,
+    VAN_DER_WAALS_RADII,
+};
+
+#[cfg(feature = "data")]
+mod scattering;
+
+#[cfg(feature = "data")]
+pub use scattering::{Radiation, UnknownScatteringPowerError};
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+mod bond_valence;
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+pub use bond_valence::{
+    BondValenceAnalysis, BondValenceError, BondValenceSite, BOND_VALENCE_CUTOFF,
+};
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+mod coordination;
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+pub use coordination::{CoordinationEnvironment, Neighbor, NeighborCriterion, Polyhedron};
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+mod formula;
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+pub use formula::ChemicalFormula;
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+mod molecule;
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+pub use molecule::{Molecule, BOND_TOLERANCE};
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+mod pdf;
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+pub use pdf::{PairDistributionFunction, PartialPdf, PdfError, PdfParameters};
+
+#[cfg(feature = "symmetry")]
+mod site_symmetry;
+
+#[cfg(feature = "symmetry")]
+pub use site_symmetry::{AdpConstraints, SiteSymmetry, ADP_COMPONENTS};
+
+#[cfg(feature = "symmetry")]
+mod neighbor_list;
+
+#[cfg(feature = "symmetry")]
+pub use neighbor_list::{NeighborList, NeighborListError, PeriodicNeighbor};
+
+#[cfg(feature = "symmetry")]
+mod primitive;
+
+#[cfg(feature = "symmetry")]
+pub use primitive::PrimitiveSetting;
+
+#[cfg(feature = "symmetry")]
+mod reduction;
+
+#[cfg(feature = "symmetry")]
+pub use reduction::{BravaisLattice, LatticeCharacter, ReducedCell, REDUCTION_TOLERANCE};
+
+#[cfg(feature = "symmetry")]
+mod supercell;
+
+#[cfg(feature = "symmetry")]
+mod transformation;
+
+#[cfg(feature = "symmetry")]
+pub use transformation::{CellTransformation, TransformationError};
+
+#[cfg(feature = "symmetry")]
+mod cluster;
+
+#[cfg(feature = "symmetry")]
+pub use cluster::{Cluster, ClusterAtom, ClusterShape, Facet};
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+mod debye;
+
+#[cfg(all(feature = "data", feature = "symmetry"))]
+pub use debye::{DebyeError, DebyeParameters, DebyeScattering};
 
 #[cfg(feature = "symmetry")]
 mod symmetry;
 
 #[cfg(feature = "symmetry")]
 pub use symmetry::{
-    IntoSpaceGroupNumber, IntoSpaceGroupSymbol, SpaceGroupGenerators, SpaceGroupSymmetryOperations,
-    SPACEGROUP_NUMBERS, SPACEGROUP_SYMBOLS,
+    format_symmetry_operation, parse_symmetry_operation, IntoSpaceGroupNumber,
+    IntoSpaceGroupSymbol, SpaceGroupGenerators, SpaceGroupSymmetryOperations, SPACEGROUP_NUMBERS,
+    SPACEGROUP_SYMBOLS,
 };
 
 pub use atoms::Atom;
 pub use atoms::Atoms;
 
 pub use cell::Cell;
+pub use element::{AtomType, Element, ToElement, UnknownElementError};
+pub use ellipsoid::{probability_scale, ThermalEllipsoid};
+pub use formats::{
+    format_names, read_structure, register_format, write_structure, CastepCell, LammpsAtomType,
+    LammpsData, LammpsDumpFrame, LammpsDumpFrames, Mmcif, ParseError, Pdb, PdbAtomInfo, Poscar,
+    PwInput, PwSpecies, StructureFileError, StructureFormat, XyzFrame, XyzFrames, XyzProperty,
+    XyzValues,
+};
+
+#[cfg(feature = "symmetry")]
+pub use formats::{Shelx, ShelxAtomInfo};
 pub use phase::Phase;
 
+pub use adp::{b_to_u, u_to_b, AdpConversionError, U_TO_B};
 pub use atoms::AdpType;
+
+#[cfg(test)]
+mod test_util;
@@ -0,0 +1,330 @@
+use std::collections::VecDeque;
+
+use cgmath::{EuclideanSpace, Matrix4, Point3, SquareMatrix, Transform, Vector3};
+
+use crate::{
+    phase::{is_same_position, POSITION_TOLERANCE},
+    ChemicalFormula, ClusterAtom, DataTableError, Element, MissingRadius, NeighborList, Phase,
+    UnknownElementError, COVALENT_RADII,
+};
+
+/// The default tolerance in Å added to the sum of covalent radii of bonded atoms.
+pub const BOND_TOLERANCE: f64 = 0.4;
+
+/// A group of bonded atoms of a crystal, e.g. a molecule or an ion.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Molecule {
+    /// The atoms with unwrapped Cartesian positions, i.e. bonded atoms are never split across
+    /// the cell boundaries. The fractional centroid lies in the unit cell.
+    pub atoms: Vec<ClusterAtom>,
+    /// The index of each atom in [`Phase::expand_to_p1`].
+    pub indices: Vec<usize>,
+    /// The formula of the atoms, counted by their occupancies.
+    pub formula: ChemicalFormula,
+    /// Whether the atoms are bonded to their own periodic images, as in chains, layers and
+    /// frameworks. The atoms are then one repeat unit of the infinite fragment.
+    pub is_periodic: bool,
+    /// The index of the first symmetry-equivalent molecule.
+    pub equivalent_to: usize,
+    /// The symmetry operation in fractional coordinates, including a lattice translation,
+    /// that maps the molecule [`Self::equivalent_to`] onto this one.
+    pub operation: Matrix4<f64>,
+}
+
+impl Molecule {
+    /// Returns the mean Cartesian position of the atoms.
+    pub fn centroid(&self) -> Point3<f64> {
+        Point3::centroid(
+            &self
+                .atoms
+                .iter()
+                .map(|atom| atom.position)
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl Phase {
+    /// Returns the molecules of the phase expanded to P1.
+    ///
+    /// Two atoms are bonded if they are closer than the sum of their covalent radii plus
+    /// `tolerance` in Å, see [`BOND_TOLERANCE`]. Every atom of the unit cell belongs to
+    /// exactly one molecule. Molecules are related by the space group operations if they
+    /// are mapped onto each other atom by atom. Fails if a covalent radius or the element of an
+    /// atom type is missing, or if the resulting cutoff is negative.
+    pub fn molecules(&self, tolerance: f64) -> Result<Vec<Molecule>, DataTableError> {
+        let p1 = self.expand_to_p1();
+        let orthogonalization_matrix = p1.cell.orthogonalization_matrix();
+
+        let radii = p1
+            .atoms
+            .iter()
+            .map(|atom| {
+                COVALENT_RADII
+                    .get_or(atom.type_.as_str(), MissingRadius::Error)
+                    .map(f64::from)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let max_radius = radii.iter().copied().fold(0.0, f64::max);
+
+        let list = NeighborList::new(&p1, 2.0 * max_radius + tolerance)
+            .map_err(|e| DataTableError::new(e.to_string()))?;
+        let bonds = (0..p1.atoms.len())
+            .map(|i| {
+                list.neighbors(i)
+                    .iter()
+                    .filter(|n| n.distance <= radii[i] + radii[n.index] + tolerance)
+                    .map(|n| (n.index, Vector3::from(n.offset.map(f64::from))))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // the lattice translation of each visited atom within its molecule
+        let mut offsets: Vec<Option<Vector3<f64>>> = vec![None; p1.atoms.len()];
+        let mut fragments = Vec::new();
+
+        for start in 0..p1.atoms.len() {
+            if offsets[start].is_some() {
+                continue;
+            }
+
+            let mut indices = vec![start];
+            let mut is_periodic = false;
+            let mut queue = VecDeque::from([start]);
+            offsets[start] = Some(Vector3::new(0.0, 0.0, 0.0));
+
+            while let Some(i) = queue.pop_front() {
+                let offset = offsets[i].unwrap_or_else(|| Vector3::new(0.0, 0.0, 0.0));
+
+                for (j, bond_offset) in &bonds[i] {
+                    let neighbor_offset = offset + bond_offset;
+
+                    match offsets[*j] {
+                        Some(existing) => is_periodic |= existing != neighbor_offset,
+                        None => {
+                            offsets[*j] = Some(neighbor_offset);
+                            indices.push(*j);
+                            queue.push_back(*j);
+                        }
+                    }
+                }
+            }
+
+            let mut positions = indices
+                .iter()
+                .map(|i| {
+                    let atom = &p1.atoms[*i];
+                    Point3::new(atom.x, atom.y, atom.z)
+                        + offsets[*i].unwrap_or(Vector3::new(0.0, 0.0, 0.0))
+                })
+                .collect::<Vec<_>>();
+
+            let shift = Point3::centroid(&positions).map(f64::floor).to_vec();
+            positions.iter_mut().for_each(|p| *p -= shift);
+
+            fragments.push((indices, positions, is_periodic));
+        }
+
+        let identity = vec![Matrix4::identity()];
+        let operations = self.cell.symmetry_operations().unwrap_or(&identity);
+        let elements = p1
+            .atoms
+            .iter()
+            .map(|atom| {
+                atom.element().ok_or_else(|| {
+                    DataTableError::new(UnknownElementError::new(&atom.type_).to_string())
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut molecules: Vec<Molecule> = Vec::with_capacity(fragments.len());
+        let mut fractional_positions: Vec<Vec<Point3<f64>>> = Vec::with_capacity(fragments.len());
+
+        for (indices, positions, is_periodic) in fragments {
+            let types = indices.iter().map(|i| elements[*i]).collect::<Vec<_>>();
+
+            let (equivalent_to, operation) = molecules
+                .iter()
+                .enumerate()
+                .filter(|(k, other)| {
+                    other.equivalent_to == *k && other.indices.len() == indices.len()
+                })
+                .find_map(|(k, other)| {
+                    let other_types = other
+                        .indices
+                        .iter()
+                        .map(|i| elements[*i])
+                        .collect::<Vec<_>>();
+
+                    operations.iter().find_map(|operation| {
+                        find_mapping(
+                            operation,
+                            (&fractional_positions[k], &other_types),
+                            (&positions, &types),
+                        )
+                        .map(|operation| (k, operation))
+                    })
+                })
+                .unwrap_or((molecules.len(), Matrix4::identity()));
+
+            let formula = ChemicalFormula::new(
+                indices
+                    .iter()
+                    .map(|i| (elements[*i], p1.atoms[*i].occupancy)),
+            );
+
+            molecules.push(Molecule {
+                atoms: indices
+                    .iter()
+                    .zip(&positions)
+                    .map(|(i, position)| {
+                        let atom = &p1.atoms[*i];
+
+                        ClusterAtom {
+                            label: atom.label.clone(),
+                            type_: atom.type_.clone(),
+                            position: Point3::from_vec(
+                                orthogonalization_matrix * position.to_vec(),
+                            ),
+                            occupancy: atom.occupancy,
+                        }
+                    })
+                    .collect(),
+                indices,
+                formula,
+                is_periodic,
+                equivalent_to,
+                operation,
+            });
+            fractional_positions.push(positions);
+        }
+
+        Ok(molecules)
+    }
+}
+
+/// Returns the operation combined with a lattice translation that maps the atoms of `from`
+/// onto those of `to`, both given as fractional positions and elements.
+fn find_mapping(
+    operation: &Matrix4<f64>,
+    from: (&[Point3<f64>], &[Element]),
+    to: (&[Point3<f64>], &[Element]),
+) -> Option<Matrix4<f64>> {
+    let images = from
+        .0
+        .iter()
+        .map(|p| operation.transform_point(*p))
+        .collect::<Vec<_>>();
+
+    let matches = |translation: Vector3<f64>| {
+        images.iter().zip(from.1).all(|(image, element)| {
+            to.0.iter().zip(to.1).any(|(p, other)| {
+                let d = image + translation - p;
+
+                other == element && [d.x, d.y, d.z].iter().all(|x| x.abs() < POSITION_TOLERANCE)
+            })
+        })
+    };
+
+    to.0.iter()
+        .zip(to.1)
+        .filter(|(_, element)| **element == from.1[0])
+        .map(|(p, _)| p - images[0])
+        .filter(|t| {
+            is_same_position(
+                Point3::from_vec(*t),
+                Point3::new(0.0, 0.0, 0.0),
+                POSITION_TOLERANCE,
+            )
+        })
+        .map(|t| t.map(f64::round))
+        .find(|t| matches(*t))
+        .map(|t| Matrix4::from_translation(t) * operation)
+}
+
+#[cfg(test)]
+mod test_molecule {
+    use cgmath::{assert_abs_diff_eq, InnerSpace, Matrix4, SquareMatrix, Transform};
+
+    use crate::{test_util::phase, BOND_TOLERANCE};
+
+    #[test]
+    fn test_molecules_across_boundaries() {
+        let nitrogen = phase(
+            5.0,
+            "P-1",
+            2,
+            &[("N", [0.95, 0.2, 0.3]), ("N", [0.15, 0.2, 0.3])],
+        );
+        let molecules = nitrogen.molecules(BOND_TOLERANCE).unwrap();
+
+        assert_eq!(molecules.len(), 2);
+
+        for molecule in &molecules {
+            assert_eq!(molecule.formula.to_string(), "N2");
+            assert!(!molecule.is_periodic);
+            assert_abs_diff_eq!(
+                (molecule.atoms[0].position - molecule.atoms[1].position).magnitude(),
+                1.0,
+                epsilon = 1e-8
+            );
+
+            let centroid = molecule.centroid();
+            assert!([centroid.x, centroid.y, centroid.z]
+                .iter()
+                .all(|x| (0.0..5.0).contains(x)));
+        }
+
+        assert_eq!(molecules[0].equivalent_to, 0);
+        assert_eq!(molecules[0].operation, Matrix4::identity());
+        assert_eq!(molecules[1].equivalent_to, 0);
+
+        // the operation is the inversion followed by a lattice translation
+        let operation = molecules[1].operation;
+        assert_abs_diff_eq!(
+            operation.transform_vector(cgmath::vec3(1.0, 2.0, 3.0)),
+            cgmath::vec3(-1.0, -2.0, -3.0),
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn test_periodic_fragments() {
+        let chain = phase(1.5, "P1", 1, &[("C", [0.0; 3])]);
+        let molecules = chain.molecules(BOND_TOLERANCE).unwrap();
+
+        assert_eq!(molecules.len(), 1);
+        assert!(molecules[0].is_periodic);
+
+        let salt = phase(5.64, "Fm-3m", 225, &[("Na", [0.0; 3]), ("Cl", [0.5; 3])]);
+        let ions = salt.molecules(0.0).unwrap();
+
+        assert_eq!(ions.len(), 8);
+        assert!(ions.iter().all(|ion| ion.atoms.len() == 1));
+        assert_eq!(ions.iter().filter(|ion| ion.equivalent_to == 0).count(), 4);
+
+        let unknown = phase(5.0, "P1", 1, &[("Xx", [0.0; 3])]);
+        assert!(unknown.molecules(BOND_TOLERANCE).is_err());
+    }
+
+    #[test]
+    fn test_partial_occupancy() {
+        let mut water = phase(
+            10.0,
+            "P1",
+            1,
+            &[
+                ("O", [0.0; 3]),
+                ("H", [0.096, 0.0, 0.0]),
+                ("H", [-0.024, 0.093, 0.0]),
+            ],
+        );
+        water.atoms.iter_mut().for_each(|atom| atom.occupancy = 0.5);
+
+        let molecules = water.molecules(BOND_TOLERANCE).unwrap();
+
+        assert_eq!(molecules.len(), 1);
+        assert_eq!(molecules[0].formula.to_string(), "H O0.5");
+    }
+}
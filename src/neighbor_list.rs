@@ -0,0 +1,403 @@
+use cgmath::{InnerSpace, Matrix3, Point3, Vector3};
+
+use crate::Phase;
+
+/// Distances below this in Å are considered to be the queried position itself.
+const SAME_POSITION_DISTANCE: f64 = 1e-4;
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct NeighborListError(String);
+
+impl NeighborListError {
+    pub fn new(message: impl AsRef<str>) -> Self {
+        Self(message.as_ref().to_string())
+    }
+}
+
+impl std::fmt::Display for NeighborListError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for NeighborListError {}
+
+/// A periodic image of an atom near an atom or position.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PeriodicNeighbor {
+    /// The index of the atom in the phase of the list.
+    pub index: usize,
+    /// The lattice translation from the atom to its image.
+    pub offset: [i32; 3],
+    /// The Cartesian vector to the image in Å.
+    pub vector: Vector3<f64>,
+    pub distance: f64,
+}
+
+/// The pairs of atoms closer than a cutoff under periodic boundary conditions.
+///
+/// Atoms are sorted into a grid of bins in fractional coordinates whose widths perpendicular
+/// to the lattice planes are at least the cutoff, so that only adjacent bins need to be
+/// searched. Building the list takes `O(N)` time for a fixed density, also for strongly
+/// skewed triclinic cells and cutoffs exceeding the cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeighborList {
+    cutoff: f64,
+    orthogonalization_matrix: Matrix3<f64>,
+    reciprocal_lengths: Vector3<f64>,
+    /// The fractional positions of the atoms as given.
+    positions: Vec<Point3<f64>>,
+    /// The lattice translation moving each atom into the cell.
+    shifts: Vec<Vector3<i64>>,
+    bins: Vec<Vec<usize>>,
+    shape: [usize; 3],
+    /// The neighbors of all atoms, those of atom `i` at `starts[i]..starts[i + 1]`.
+    neighbors: Vec<PeriodicNeighbor>,
+    starts: Vec<usize>,
+}
+
+impl NeighborList {
+    /// Creates the list of the atoms of the phase as given, i.e. without applying the space
+    /// group. See [`Phase::neighbor_list`] for a phase with symmetry.
+    ///
+    /// Fails if the cutoff is negative or not finite.
+    pub fn new(phase: &Phase, cutoff: f64) -> Result<Self, NeighborListError> {
+        validate_cutoff(cutoff)?;
+
+        let cell = &phase.cell;
+        let reciprocal_lengths = cell.reciprocal_lengths();
+
+        // the distance between lattice planes is 1 / |a*|
+        let mut shape = [
+            reciprocal_lengths.x,
+            reciprocal_lengths.y,
+            reciprocal_lengths.z,
+        ]
+        .map(|l| match cutoff > 0.0 {
+            true => ((1.0 / (cutoff * l)).floor() as usize).clamp(1, 1 << 10),
+            false => 1,
+        });
+
+        // fewer and larger bins are still correct, they only hold more atoms
+        while shape.iter().product::<usize>() > 8 * phase.atoms.len().max(1) {
+            let largest = (0..3).max_by_key(|k| shape[*k]).unwrap_or_default();
+            shape[largest] = shape[largest].div_ceil(2);
+        }
+
+        let positions = phase
+            .atoms
+            .iter()
+            .map(|atom| Point3::new(atom.x, atom.y, atom.z))
+            .collect::<Vec<_>>();
+
+        let mut list = Self {
+            cutoff,
+            orthogonalization_matrix: cell.orthogonalization_matrix(),
+            reciprocal_lengths,
+            shifts: Vec::with_capacity(positions.len()),
+            positions,
+            bins: vec![Vec::new(); shape.iter().product()],
+            shape,
+            neighbors: Vec::new(),
+            starts: vec![0],
+        };
+
+        for index in 0..list.positions.len() {
+            let (bin, shift) = list.wrap_bin(list.bin_of(list.positions[index]));
+            let bin = list.bin_index(bin);
+
+            list.bins[bin].push(index);
+            list.shifts.push(shift);
+        }
+
+        for index in 0..list.positions.len() {
+            let neighbors = list.neighbors_of_position(list.positions[index], cutoff)?;
+
+            list.neighbors.extend(neighbors);
+            list.starts.push(list.neighbors.len());
+        }
+
+        Ok(list)
+    }
+
+    pub fn cutoff(&self) -> f64 {
+        self.cutoff
+    }
+
+    /// Returns the number of atoms.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns the neighbors of the atom with the given index sorted by distance. The atom
+    /// itself is excluded, its periodic images are not.
+    pub fn neighbors(&self, index: usize) -> &[PeriodicNeighbor] {
+        &self.neighbors[self.starts[index]..self.starts[index + 1]]
+    }
+
+    /// Returns all pairs of an atom index and a neighbor. Every pair appears in both
+    /// directions.
+    pub fn pairs(&self) -> impl Iterator<Item = (usize, &PeriodicNeighbor)> {
+        (0..self.len()).flat_map(move |index| {
+            self.neighbors(index)
+                .iter()
+                .map(move |neighbor| (index, neighbor))
+        })
+    }
+
+    /// Returns the atoms within `cutoff` of a fractional position sorted by distance. Atoms at
+    /// the position itself are excluded.
+    ///
+    /// The cutoff may differ from the one of the list, larger ones search more bins. Fails
+    /// like [`NeighborList::new`].
+    pub fn neighbors_of_position(
+        &self,
+        position: Point3<f64>,
+        cutoff: f64,
+    ) -> Result<Vec<PeriodicNeighbor>, NeighborListError> {
+        validate_cutoff(cutoff)?;
+
+        let bin = self.bin_of(position);
+        let reach = [0, 1, 2]
+            .map(|k| (cutoff * self.reciprocal_lengths[k] * self.shape[k] as f64).ceil() as i64);
+
+        let mut neighbors = Vec::new();
+
+        for du in -reach[0]..=reach[0] {
+            for dv in -reach[1]..=reach[1] {
+                for dw in -reach[2]..=reach[2] {
+                    let (other, image) = self.wrap_bin([bin[0] + du, bin[1] + dv, bin[2] + dw]);
+
+                    for &index in &self.bins[self.bin_index(other)] {
+                        // the atom moved into the cell and then into the image of the bin
+                        let offset = (image - self.shifts[index]).map(|x| x as f64);
+                        let vector = self.orthogonalization_matrix
+                            * (self.positions[index] + offset - position);
+                        let distance = vector.magnitude();
+
+                        if distance > cutoff || distance < SAME_POSITION_DISTANCE {
+                            continue;
+                        }
+
+                        neighbors.push(PeriodicNeighbor {
+                            index,
+                            offset: [offset.x, offset.y, offset.z].map(|x| x.round() as i32),
+                            vector,
+                            distance,
+                        });
+                    }
+                }
+            }
+        }
+
+        neighbors.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+        Ok(neighbors)
+    }
+
+    /// Returns the unwrapped bin of a fractional position, e.g. `-1` for positions below `0`.
+    fn bin_of(&self, position: Point3<f64>) -> [i64; 3] {
+        [0, 1, 2].map(|k| (position[k] * self.shape[k] as f64).floor() as i64)
+    }
+
+    /// Splits an unwrapped bin into the bin inside the cell and the lattice translation.
+    fn wrap_bin(&self, bin: [i64; 3]) -> ([usize; 3], Vector3<i64>) {
+        let n = self.shape.map(|n| n as i64);
+
+        (
+            [0, 1, 2].map(|k| bin[k].rem_euclid(n[k]) as usize),
+            Vector3::from([0, 1, 2].map(|k| bin[k].div_euclid(n[k]))),
+        )
+    }
+
+    fn bin_index(&self, [u, v, w]: [usize; 3]) -> usize {
+        (u * self.shape[1] + v) * self.shape[2] + w
+    }
+}
+
+fn validate_cutoff(cutoff: f64) -> Result<(), NeighborListError> {
+    match cutoff.is_finite() && cutoff >= 0.0 {
+        true => Ok(()),
+        false => Err(NeighborListError::new(format!(
+            "Cutoff must be finite and non-negative, found {}",
+            cutoff
+        ))),
+    }
+}
+
+impl Phase {
+    /// Returns the neighbor list of the phase expanded to P1. Indices refer to the atoms of
+    /// [`Self::expand_to_p1`].
+    pub fn neighbor_list(&self, cutoff: f64) -> Result<NeighborList, NeighborListError> {
+        NeighborList::new(&self.expand_to_p1(), cutoff)
+    }
+}
+
+#[cfg(test)]
+mod test_neighbor_list {
+    use cgmath::{assert_abs_diff_eq, vec3, InnerSpace, Point3};
+
+    use super::NeighborList;
+    use crate::{test_util::rock_salt, Atom, Atoms, Cell, Phase};
+
+    /// Returns the sorted distances of all pairs by checking every image.
+    fn brute_force(phase: &Phase, cutoff: f64) -> Vec<(usize, usize, [i32; 3])> {
+        let m = phase.cell.orthogonalization_matrix();
+        let ranges = phase
+            .cell
+            .reciprocal_lengths()
+            .map(|l| (cutoff * l).ceil() as i32 + 1);
+        let mut pairs = Vec::new();
+
+        for (i, a) in phase.atoms.iter().enumerate() {
+            for (j, b) in phase.atoms.iter().enumerate() {
+                for u in -ranges.x..=ranges.x {
+                    for v in -ranges.y..=ranges.y {
+                        for w in -ranges.z..=ranges.z {
+                            let d = vec3(
+                                b.x - a.x + u as f64,
+                                b.y - a.y + v as f64,
+                                b.z - a.z + w as f64,
+                            );
+                            let distance = (m * d).magnitude();
+
+                            if distance <= cutoff && distance > 1e-4 {
+                                pairs.push((i, j, [u, v, w]));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        pairs.sort();
+
+        pairs
+    }
+
+    fn skewed_phase() -> Phase {
+        let coordinates = [
+            [0.1, 0.2, 0.3],
+            [0.9, 0.95, 0.05],
+            [-0.4, 0.5, 1.7],
+            [0.5, 0.5, 0.5],
+            [0.33, 0.8, 0.12],
+        ];
+
+        Phase {
+            cell: Cell {
+                a: 3.0,
+                b: 7.0,
+                c: 4.0,
+                alpha: 100.0,
+                beta: 115.0,
+                gamma: 25.0,
+                space_group: "P1".to_string(),
+                space_group_number: 1,
+                ..Default::default()
+            },
+            atoms: Atoms(
+                coordinates
+                    .iter()
+                    .map(|[x, y, z]| Atom {
+                        label: "C".to_string(),
+                        type_: "C".to_string(),
+                        x: *x,
+                        y: *y,
+                        z: *z,
+                        occupancy: 1.0,
+                        ..Default::default()
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    #[test]
+    fn test_skewed_cell() {
+        let phase = skewed_phase();
+        assert!(phase.cell.calculate_volume() > 0.0);
+
+        for cutoff in [0.5, 2.0, 4.5, 9.0] {
+            let list = NeighborList::new(&phase, cutoff).unwrap();
+
+            let mut pairs = list
+                .pairs()
+                .map(|(i, n)| (i, n.index, n.offset))
+                .collect::<Vec<_>>();
+            pairs.sort();
+
+            assert_eq!(pairs, brute_force(&phase, cutoff), "cutoff {cutoff}");
+        }
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let phase = skewed_phase();
+        let list = NeighborList::new(&phase, 3.0).unwrap();
+        let m = phase.cell.orthogonalization_matrix();
+
+        assert_eq!(list.len(), 5);
+        assert_eq!(list.cutoff(), 3.0);
+
+        for index in 0..list.len() {
+            let neighbors = list.neighbors(index);
+            let atom = &phase.atoms[index];
+
+            assert!(neighbors.windows(2).all(|w| w[0].distance <= w[1].distance));
+
+            for neighbor in neighbors {
+                let other = &phase.atoms[neighbor.index];
+                let offset = vec3(neighbor.offset[0], neighbor.offset[1], neighbor.offset[2]);
+                let d = vec3(other.x - atom.x, other.y - atom.y, other.z - atom.z)
+                    + offset.map(f64::from);
+
+                assert_abs_diff_eq!(m * d, neighbor.vector, epsilon = 1e-10);
+                assert_abs_diff_eq!(
+                    neighbor.vector.magnitude(),
+                    neighbor.distance,
+                    epsilon = 1e-10
+                );
+            }
+        }
+
+        // queries of arbitrary positions exclude atoms at the position
+        let around_atom = list
+            .neighbors_of_position(Point3::new(0.1, 0.2, 0.3), 3.0)
+            .unwrap();
+        assert_eq!(around_atom, list.neighbors(0));
+    }
+
+    #[test]
+    fn test_symmetry() {
+        let rock_salt = rock_salt();
+
+        let list = rock_salt.neighbor_list(3.0).unwrap();
+
+        assert_eq!(list.len(), 8);
+        assert!((0..8).all(|i| list.neighbors(i).len() == 6));
+        assert!(NeighborList::new(&rock_salt, 0.0)
+            .unwrap()
+            .pairs()
+            .next()
+            .is_none());
+    }
+
+    #[test]
+    fn test_invalid_cutoff() {
+        let rock_salt = rock_salt();
+        let list = rock_salt.neighbor_list(3.0).unwrap();
+
+        for cutoff in [-1.0, f64::INFINITY, f64::NAN] {
+            assert!(NeighborList::new(&rock_salt, cutoff).is_err());
+            assert!(list
+                .neighbors_of_position(Point3::new(0.0, 0.0, 0.0), cutoff)
+                .is_err());
+        }
+    }
+}
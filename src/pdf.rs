@@ -0,0 +1,529 @@
+use std::{collections::BTreeMap, f64::consts::PI};
+
+use cgmath::InnerSpace;
+
+use crate::{scattering::Radiation, Phase};
+
+/// Parameters of a real-space pair distribution function calculation.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+#[derive(Debug, Clone, PartialEq)]
+pub struct PdfParameters {
+    /// Lower end of the calculated range in Å.
+    pub r_min: f64,
+    /// Upper end of the calculated range in Å.
+    pub r_max: f64,
+    /// Grid spacing in Å.
+    pub r_step: f64,
+    pub radiation: Radiation,
+    /// Instrumental resolution damping `exp(-(q_damp r)² / 2)` applied to `G(r)`.
+    pub q_damp: Option<f64>,
+    /// Maximum momentum transfer in Å⁻¹. `G(r)` is convoluted with the corresponding
+    /// termination function.
+    pub q_max: Option<f64>,
+    /// Gaussian peak width in Å added in quadrature to the displacement parameters.
+    pub peak_width: f64,
+    /// Broadens the peaks by the isotropic displacement parameters of both atoms.
+    pub use_adps: bool,
+}
+
+impl Default for PdfParameters {
+    fn default() -> Self {
+        Self {
+            r_min: 0.5,
+            r_max: 20.0,
+            r_step: 0.01,
+            radiation: Radiation::XRay,
+            q_damp: None,
+            q_max: None,
+            peak_width: 0.0,
+            use_adps: true,
+        }
+    }
+}
+
+impl PdfParameters {
+    /// Checks that the grid is non-empty and that all widths are finite and non-negative.
+    pub fn validate(&self) -> Result<(), PdfError> {
+        if !(self.r_step.is_finite() && self.r_step > 0.0) {
+            return Err(PdfError::new(format!(
+                "Grid spacing must be positive: {}",
+                self.r_step
+            )));
+        }
+
+        if !(self.r_min.is_finite() && self.r_max.is_finite() && 0.0 <= self.r_min) {
+            return Err(PdfError::new(format!(
+                "Range must be finite and non-negative: {} to {}",
+                self.r_min, self.r_max
+            )));
+        }
+
+        if self.r_min > self.r_max {
+            return Err(PdfError::new(format!(
+                "Lower end of the range exceeds the upper end: {} > {}",
+                self.r_min, self.r_max
+            )));
+        }
+
+        if !(self.peak_width.is_finite() && self.peak_width >= 0.0) {
+            return Err(PdfError::new(format!(
+                "Peak width must be non-negative: {}",
+                self.peak_width
+            )));
+        }
+
+        if let Some(q_damp) = self.q_damp.filter(|q| !(q.is_finite() && *q >= 0.0)) {
+            return Err(PdfError::new(format!(
+                "Q_damp must be non-negative: {q_damp}"
+            )));
+        }
+
+        if let Some(q_max) = self.q_max.filter(|q| !(q.is_finite() && *q > 0.0)) {
+            return Err(PdfError::new(format!("Q_max must be positive: {q_max}")));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PdfError(String);
+
+impl PdfError {
+    pub fn new(message: impl AsRef<str>) -> Self {
+        Self(message.as_ref().to_string())
+    }
+}
+
+impl std::fmt::Display for PdfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for PdfError {}
+
+/// Contribution of one pair of atom types to the pair distribution function.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialPdf {
+    /// Unweighted radial distribution function `R_αβ(r)`.
+    pub rdf: Vec<f64>,
+    /// Reduced pair distribution function `G_αβ(r)`.
+    pub g: Vec<f64>,
+    /// Weight `c_α c_β b_α b_β / <b>²` of the pair in the total functions.
+    /// Unlike pairs enter the total functions twice.
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PairDistributionFunction {
+    pub r: Vec<f64>,
+    /// Weighted radial distribution function `R(r)`.
+    pub rdf: Vec<f64>,
+    /// Weighted reduced pair distribution function `G(r) = R(r) / r - 4π r ρ₀`.
+    pub g: Vec<f64>,
+    /// Average number density `ρ₀` in Å⁻³.
+    pub number_density: f64,
+    /// Partial functions keyed by the sorted pair of atom types.
+    pub partials: BTreeMap<(String, String), PartialPdf>,
+}
+
+struct PdfAtom {
+    species: usize,
+    position: cgmath::Vector3<f64>,
+    occupancy: f64,
+    u_iso: f64,
+}
+
+impl PairDistributionFunction {
+    /// Calculates the pair distribution function of a phase with periodic boundary conditions.
+    ///
+    /// The phase is expanded to P1 and every atom in the unit cell is paired with all atoms of
+    /// the periodic images up to `r_max`. Each pair contributes a Gaussian weighted by the
+    /// occupancies and scattering powers of both atoms.
+    ///
+    /// Fails for invalid parameters, see [`PdfParameters::validate`], a degenerate cell or
+    /// atom types without a scattering power.
+    pub fn calculate(phase: &Phase, parameters: &PdfParameters) -> Result<Self, PdfError> {
+        parameters.validate()?;
+
+        if phase.cell.fractionalization_matrix().is_none() {
+            return Err(PdfError::new("Unit cell is degenerate"));
+        }
+
+        let p1 = phase.expand_to_p1();
+        let cell = &p1.cell;
+        let orthogonalization_matrix = cell.orthogonalization_matrix();
+
+        let mut species: Vec<String> = Vec::new();
+        let mut atoms = Vec::with_capacity(p1.atoms.len());
+
+        for atom in p1.atoms.iter() {
+            let index = match species.iter().position(|s| *s == atom.type_) {
+                Some(index) => index,
+                None => {
+                    species.push(atom.type_.clone());
+                    species.len() - 1
+                }
+            };
+
+            atoms.push(PdfAtom {
+                species: index,
+                position: cgmath::vec3(atom.x, atom.y, atom.z),
+                occupancy: atom.occupancy,
+                u_iso: atom.u_equiv(cell).unwrap_or_default(),
+            });
+        }
+
+        let scattering_powers = species
+            .iter()
+            .map(|s| parameters.radiation.scattering_power(s))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| PdfError::new(e.to_string()))?;
+
+        let number_of_atoms: f64 = atoms.iter().map(|a| a.occupancy).sum();
+        let number_density = number_of_atoms / cell.calculate_volume();
+
+        let concentrations = (0..species.len())
+            .map(|s| {
+                atoms
+                    .iter()
+                    .filter(|a| a.species == s)
+                    .map(|a| a.occupancy)
+                    .sum::<f64>()
+                    / number_of_atoms
+            })
+            .collect::<Vec<_>>();
+
+        let mean_scattering_power: f64 = concentrations
+            .iter()
+            .zip(&scattering_powers)
+            .map(|(c, b)| c * b)
+            .sum();
+
+        // the termination ripples reach beyond r_max, so the grid is extended
+        let r_extension = parameters.q_max.map(|q| 20.0 * PI / q).unwrap_or(0.0);
+        let number_of_points =
+            ((parameters.r_max + r_extension) / parameters.r_step).ceil() as usize + 1;
+        let r = (0..number_of_points)
+            .map(|i| i as f64 * parameters.r_step)
+            .collect::<Vec<_>>();
+
+        let peak_width = |a: &PdfAtom, b: &PdfAtom| match parameters.use_adps {
+            true => (parameters.peak_width.powi(2) + a.u_iso + b.u_iso).sqrt(),
+            false => parameters.peak_width,
+        };
+
+        let max_width = atoms.iter().map(|a| peak_width(a, a)).fold(0.0, f64::max);
+        let cutoff = r[number_of_points - 1] + 5.0 * max_width;
+
+        let reciprocal_lengths = cell.reciprocal_lengths();
+        let image_ranges = [
+            reciprocal_lengths.x,
+            reciprocal_lengths.y,
+            reciprocal_lengths.z,
+        ]
+        .map(|l| (cutoff * l).ceil() as i32 + 1);
+
+        let mut rdfs = vec![vec![0.0; number_of_points]; species.len() * species.len()];
+
+        for (i, a) in atoms.iter().enumerate() {
+            for (j, b) in atoms.iter().enumerate() {
+                let sigma = peak_width(a, b);
+                let weight = a.occupancy * b.occupancy / number_of_atoms;
+                let rdf = &mut rdfs[a.species * species.len() + b.species];
+
+                for u in -image_ranges[0]..=image_ranges[0] {
+                    for v in -image_ranges[1]..=image_ranges[1] {
+                        for w in -image_ranges[2]..=image_ranges[2] {
+                            if i == j && u == 0 && v == 0 && w == 0 {
+                                continue;
+                            }
+
+                            let image = cgmath::vec3(u as f64, v as f64, w as f64);
+                            let distance = (orthogonalization_matrix
+                                * (b.position + image - a.position))
+                                .magnitude();
+
+                            if distance > cutoff {
+                                continue;
+                            }
+
+                            add_peak(rdf, parameters.r_step, distance, sigma, weight);
+                        }
+                    }
+                }
+            }
+        }
+
+        let envelope = |g: &mut Vec<f64>| {
+            if let Some(q_damp) = parameters.q_damp {
+                g.iter_mut()
+                    .zip(&r)
+                    .for_each(|(g, r)| *g *= (-0.5 * (q_damp * r).powi(2)).exp());
+            }
+
+            if let Some(q_max) = parameters.q_max {
+                *g = terminate(g, parameters.r_step, q_max);
+            }
+        };
+
+        let reduce = |rdf: &[f64], scale: f64| {
+            rdf.iter()
+                .zip(&r)
+                .map(|(rdf, r)| match *r > 0.0 {
+                    true => rdf / (r * scale) - 4.0 * PI * r * number_density,
+                    false => 0.0,
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let first = (parameters.r_min / parameters.r_step).ceil() as usize;
+        let last = (parameters.r_max / parameters.r_step + 1e-9).floor() as usize;
+        let crop = |values: &[f64]| values[first..=last].to_vec();
+
+        let mut total_rdf = vec![0.0; number_of_points];
+        let mut partials = BTreeMap::new();
+
+        for a in 0..species.len() {
+            for b in 0..species.len() {
+                let rdf = &rdfs[a * species.len() + b];
+                let scale =
+                    scattering_powers[a] * scattering_powers[b] / mean_scattering_power.powi(2);
+
+                total_rdf
+                    .iter_mut()
+                    .zip(rdf)
+                    .for_each(|(total, rdf)| *total += scale * rdf);
+
+                if a > b {
+                    continue;
+                }
+
+                let mut g = reduce(rdf, concentrations[a] * concentrations[b]);
+                envelope(&mut g);
+
+                let key = match species[a] <= species[b] {
+                    true => (species[a].clone(), species[b].clone()),
+                    false => (species[b].clone(), species[a].clone()),
+                };
+
+                partials.insert(
+                    key,
+                    PartialPdf {
+                        rdf: crop(rdf),
+                        g: crop(&g),
+                        weight: concentrations[a] * concentrations[b] * scale,
+                    },
+                );
+            }
+        }
+
+        let mut g = reduce(&total_rdf, 1.0);
+        envelope(&mut g);
+
+        Ok(Self {
+            r: crop(&r),
+            rdf: crop(&total_rdf),
+            g: crop(&g),
+            number_density,
+            partials,
+        })
+    }
+}
+
+/// Adds a normalized Gaussian centered at `distance` to the grid.
+///
+/// Peaks narrower than the grid spacing are added to the nearest point instead.
+fn add_peak(values: &mut [f64], r_step: f64, distance: f64, sigma: f64, weight: f64) {
+    if sigma < 0.5 * r_step {
+        let index = (distance / r_step).round() as usize;
+
+        if let Some(value) = values.get_mut(index) {
+            *value += weight / r_step;
+        }
+
+        return;
+    }
+
+    let first = ((distance - 5.0 * sigma) / r_step).floor().max(0.0) as usize;
+    let last = (((distance + 5.0 * sigma) / r_step).ceil() as usize).min(values.len() - 1);
+    let norm = weight / (sigma * (2.0 * PI).sqrt());
+
+    for (i, value) in values.iter_mut().enumerate().take(last + 1).skip(first) {
+        let x = (i as f64 * r_step - distance) / sigma;
+        *value += norm * (-0.5 * x * x).exp();
+    }
+}
+
+/// Convolutes `G(r)` with the termination function of a finite `Q_max`.
+fn terminate(g: &[f64], r_step: f64, q_max: f64) -> Vec<f64> {
+    let kernel = |x: f64| match x.abs() < 1e-12 {
+        true => q_max / PI,
+        false => (q_max * x).sin() / (PI * x),
+    };
+
+    (0..g.len())
+        .map(|i| {
+            let r = i as f64 * r_step;
+
+            g.iter()
+                .enumerate()
+                .map(|(k, g)| {
+                    let r_k = k as f64 * r_step;
+                    g * (kernel(r - r_k) - kernel(r + r_k)) * r_step
+                })
+                .sum()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test_pdf {
+    use super::{PairDistributionFunction, PdfParameters};
+    use crate::{scattering::Radiation, test_util, Phase};
+
+    fn rock_salt() -> Phase {
+        let mut phase = test_util::rock_salt();
+
+        for atom in phase.atoms.iter_mut() {
+            atom.u_iso_or_equiv = 0.005;
+        }
+
+        phase
+    }
+
+    fn integrate(pdf: &PairDistributionFunction, values: &[f64], from: f64, to: f64) -> f64 {
+        pdf.r
+            .iter()
+            .zip(values)
+            .filter(|(r, _)| (from..to).contains(*r))
+            .map(|(_, v)| v * 0.01)
+            .sum()
+    }
+
+    #[test]
+    fn test_coordination_number() {
+        let parameters = PdfParameters {
+            r_max: 6.0,
+            ..Default::default()
+        };
+
+        let pdf = PairDistributionFunction::calculate(&rock_salt(), &parameters).unwrap();
+
+        let na_cl = &pdf.partials[&("Cl".to_string(), "Na".to_string())];
+
+        // every Na has six Cl neighbours at a / 2, half of the atoms are Na
+        assert!((integrate(&pdf, &na_cl.rdf, 2.2, 3.4) - 3.0).abs() < 1e-3);
+        assert!(integrate(&pdf, &na_cl.rdf, 0.0, 2.2).abs() < 1e-6);
+
+        let na_na = &pdf.partials[&("Na".to_string(), "Na".to_string())];
+
+        assert!((integrate(&pdf, &na_na.rdf, 3.4, 4.6) - 6.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_partials_sum_to_total() {
+        let parameters = PdfParameters {
+            r_max: 8.0,
+            radiation: Radiation::Neutron,
+            q_damp: Some(0.02),
+            ..Default::default()
+        };
+
+        let pdf = PairDistributionFunction::calculate(&rock_salt(), &parameters).unwrap();
+
+        for (i, g) in pdf.g.iter().enumerate() {
+            let sum: f64 = pdf
+                .partials
+                .iter()
+                .map(|((a, b), partial)| match a == b {
+                    true => partial.weight * partial.g[i],
+                    false => 2.0 * partial.weight * partial.g[i],
+                })
+                .sum();
+
+            assert!((g - sum).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_termination() {
+        let parameters = PdfParameters {
+            r_max: 10.0,
+            q_max: Some(25.0),
+            ..Default::default()
+        };
+
+        let unterminated = PdfParameters {
+            q_max: None,
+            ..parameters.clone()
+        };
+
+        let terminated = PairDistributionFunction::calculate(&rock_salt(), &parameters).unwrap();
+        let reference = PairDistributionFunction::calculate(&rock_salt(), &unterminated).unwrap();
+
+        assert_eq!(terminated.r, reference.r);
+
+        // the peaks are broadened by the termination but stay in place
+        let maximum = |g: &[f64]| {
+            g.iter()
+                .enumerate()
+                .fold((0, f64::MIN), |m, (i, g)| match *g > m.1 {
+                    true => (i, *g),
+                    false => m,
+                })
+        };
+
+        let (i, g_terminated) = maximum(&terminated.g);
+        let (j, g_reference) = maximum(&reference.g);
+
+        assert_eq!(i, j);
+        assert!(g_terminated < g_reference);
+    }
+
+    #[test]
+    fn test_unknown_atom_type() {
+        let mut phase = rock_salt();
+        phase.atoms[0].type_ = "Xx".to_string();
+
+        assert!(PairDistributionFunction::calculate(&phase, &PdfParameters::default()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        for parameters in [
+            PdfParameters {
+                r_step: 0.0,
+                ..Default::default()
+            },
+            PdfParameters {
+                r_step: f64::NAN,
+                ..Default::default()
+            },
+            PdfParameters {
+                r_min: 10.0,
+                r_max: 5.0,
+                ..Default::default()
+            },
+            PdfParameters {
+                r_max: f64::INFINITY,
+                ..Default::default()
+            },
+            PdfParameters {
+                q_max: Some(0.0),
+                ..Default::default()
+            },
+        ] {
+            assert!(PairDistributionFunction::calculate(&rock_salt(), &parameters).is_err());
+        }
+
+        let mut degenerate = rock_salt();
+        degenerate.cell.c = 0.0;
+
+        assert!(
+            PairDistributionFunction::calculate(&degenerate, &PdfParameters::default()).is_err()
+        );
+    }
+}
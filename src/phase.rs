@@ -1,4 +1,10 @@
-use crate::{atoms::Atoms, cell::Cell};
+use std::path::Path;
+
+use crate::{
+    atoms::Atoms,
+    cell::Cell,
+    formats::{read_structure, write_structure, StructureFileError},
+};
 
 #[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
 #[cfg_attr(feature = "serde", serde(default))]
@@ -7,3 +13,284 @@ pub struct Phase {
     pub cell: Cell,
     pub atoms: Atoms,
 }
+
+impl Phase {
+    /// Reads a structure file. The format is chosen by file name, e.g. `*.pdb` or `POSCAR`,
+    /// and otherwise by content.
+    ///
+    /// Further formats can be added with [`crate::register_format`].
+    pub fn read(path: impl AsRef<Path>) -> Result<Phase, StructureFileError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| StructureFileError::new(format!("{}: {}", path.display(), e)))?;
+
+        read_structure(&content, &file_name(path))
+    }
+
+    /// Writes a structure file in the format matching the file name.
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), StructureFileError> {
+        let path = path.as_ref();
+        let content = write_structure(self, &file_name(path))?;
+
+        std::fs::write(path, content)
+            .map_err(|e| StructureFileError::new(format!("{}: {}", path.display(), e)))
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Tolerance in fractional coordinates below which two positions are considered identical.
+#[cfg(feature = "symmetry")]
+pub(crate) const POSITION_TOLERANCE: f64 = 1e-4;
+
+#[cfg(feature = "symmetry")]
+impl Phase {
+    /// Applies the symmetry operations of the space group to all atoms and returns the
+    /// resulting phase in P1.
+    ///
+    /// Coordinates are moved into the unit cell and symmetry equivalent positions of the same
+    /// atom are merged. Anisotropic displacement parameters are rotated accordingly.
+    /// If the space group is unknown the atoms are copied unchanged.
+    pub fn expand_to_p1(&self) -> Phase {
+        self.expand_to_p1_with_sites().0
+    }
+
+    /// Expands the phase like [`Self::expand_to_p1`] and returns the index of the atom of
+    /// this phase that each expanded atom was generated from.
+    pub(crate) fn expand_to_p1_with_sites(&self) -> (Phase, Vec<usize>) {
+        use cgmath::{Matrix, SquareMatrix, Transform};
+
+        let identity = vec![cgmath::Matrix4::identity()];
+
+        let symmetry_operations = self.cell.symmetry_operations().unwrap_or_else(|| {
+            log::warn!(
+                "Unknown space group: {}. Treating phase as P1.",
+                self.cell.space_group
+            );
+            &identity
+        });
+
+        let reciprocal_lengths = self.cell.reciprocal_lengths();
+        let n = cgmath::Matrix3::from_diagonal(reciprocal_lengths);
+        let n_inv = cgmath::Matrix3::from_diagonal(reciprocal_lengths.map(|x| 1.0 / x));
+
+        let mut atoms = Vec::with_capacity(self.atoms.len() * symmetry_operations.len());
+        let mut sites = Vec::with_capacity(atoms.capacity());
+
+        for (site, atom) in self.atoms.iter().enumerate() {
+            let point = cgmath::Point3::new(atom.x, atom.y, atom.z);
+            let mut positions: Vec<cgmath::Point3<f64>> = Vec::new();
+
+            for operation in symmetry_operations {
+                let new_point = wrap_point(operation.transform_point(point));
+
+                if positions
+                    .iter()
+                    .any(|p| is_same_position(*p, new_point, POSITION_TOLERANCE))
+                {
+                    continue;
+                }
+
+                positions.push(new_point);
+
+                let mut new_atom = atom.clone();
+                new_atom.x = new_point.x;
+                new_atom.y = new_point.y;
+                new_atom.z = new_point.z;
+                new_atom.multiplicity = Some(1.0);
+
+                let rotation = rotation_part(operation);
+                let rotation = n_inv * rotation * n;
+                new_atom.set_u_ani(rotation * atom.u_ani() * rotation.transpose());
+
+                atoms.push(new_atom);
+                sites.push(site);
+            }
+        }
+
+        let p1 = Phase {
+            cell: Cell {
+                space_group: "P1".to_string(),
+                space_group_number: 1,
+                setting_operations: Vec::new(),
+                ..self.cell.clone()
+            },
+            atoms: Atoms(atoms),
+        };
+
+        (p1, sites)
+    }
+
+    /// Sets the space group of a phase given in P1 and keeps one atom per orbit of the group.
+    pub(crate) fn reduce_to_asymmetric_unit(&mut self, space_group_number: u8) {
+        use cgmath::{SquareMatrix, Transform};
+
+        let identity = vec![cgmath::Matrix4::identity()];
+        let operations =
+            crate::SpaceGroupSymmetryOperations::get(space_group_number).unwrap_or(&identity);
+        let mut asymmetric_unit: Vec<crate::Atom> = Vec::new();
+
+        for atom in self.atoms.iter() {
+            let point = cgmath::Point3::new(atom.x, atom.y, atom.z);
+
+            let is_equivalent = asymmetric_unit.iter().any(|kept| {
+                kept.type_ == atom.type_
+                    && operations.iter().any(|operation| {
+                        let image =
+                            operation.transform_point(cgmath::Point3::new(kept.x, kept.y, kept.z));
+                        is_same_position(image, point, POSITION_TOLERANCE)
+                    })
+            });
+
+            if !is_equivalent {
+                asymmetric_unit.push(crate::Atom {
+                    multiplicity: None,
+                    ..atom.clone()
+                });
+            }
+        }
+
+        self.cell.space_group_number = space_group_number;
+        self.cell.setting_operations.clear();
+        self.cell.space_group = crate::SPACEGROUP_SYMBOLS
+            .get(&space_group_number)
+            .cloned()
+            .unwrap_or_default();
+        self.atoms = Atoms(asymmetric_unit);
+    }
+}
+
+/// Returns the rotational part of an affine symmetry operation.
+#[cfg(feature = "symmetry")]
+pub(crate) fn rotation_part(operation: &cgmath::Matrix4<f64>) -> cgmath::Matrix3<f64> {
+    cgmath::Matrix3::from_cols(
+        operation.x.truncate(),
+        operation.y.truncate(),
+        operation.z.truncate(),
+    )
+}
+
+/// Moves a fractional position into `[0, 1)`.
+#[cfg(feature = "symmetry")]
+pub(crate) fn wrap_point(point: cgmath::Point3<f64>) -> cgmath::Point3<f64> {
+    point.map(|x| {
+        let x = x.rem_euclid(1.0);
+
+        match 1.0 - x < 1e-10 {
+            true => 0.0,
+            false => x,
+        }
+    })
+}
+
+/// Compares two fractional positions modulo lattice translations.
+#[cfg(feature = "symmetry")]
+pub(crate) fn is_same_position(
+    a: cgmath::Point3<f64>,
+    b: cgmath::Point3<f64>,
+    tolerance: f64,
+) -> bool {
+    let d = a - b;
+
+    [d.x, d.y, d.z]
+        .iter()
+        .all(|x| (x - x.round()).abs() < tolerance)
+}
+
+#[cfg(all(test, feature = "symmetry"))]
+mod test_expand_to_p1 {
+    use crate::{Atom, Atoms, Cell, Phase};
+
+    #[test]
+    fn test_rock_salt() {
+        let phase = Phase {
+            cell: Cell {
+                a: 5.64,
+                b: 5.64,
+                c: 5.64,
+                alpha: 90.0,
+                beta: 90.0,
+                gamma: 90.0,
+                space_group: "F m -3 m".to_string(),
+                ..Default::default()
+            },
+            atoms: Atoms(vec![
+                Atom {
+                    label: "Na1".to_string(),
+                    type_: "Na".to_string(),
+                    occupancy: 1.0,
+                    ..Default::default()
+                },
+                Atom {
+                    label: "Cl1".to_string(),
+                    type_: "Cl".to_string(),
+                    x: 0.5,
+                    y: 0.5,
+                    z: 0.5,
+                    occupancy: 1.0,
+                    ..Default::default()
+                },
+            ]),
+        };
+
+        let p1 = phase.expand_to_p1();
+
+        assert_eq!(p1.cell.space_group_number, 1);
+        assert_eq!(p1.atoms.len(), 8);
+        assert_eq!(p1.atoms.iter().filter(|a| a.type_ == "Na").count(), 4);
+        assert!(p1
+            .atoms
+            .iter()
+            .all(|a| (0.0..1.0).contains(&a.x) && (0.0..1.0).contains(&a.y)));
+
+        // the operations of a setting do not carry over to P1
+        let mut setting = phase.clone();
+        setting.cell.setting_operations = phase.cell.symmetry_operations().unwrap().clone();
+
+        assert!(setting.expand_to_p1().cell.setting_operations.is_empty());
+    }
+
+    #[test]
+    fn test_adp_rotation() {
+        let phase = Phase {
+            cell: Cell {
+                a: 4.0,
+                b: 4.0,
+                c: 6.0,
+                alpha: 90.0,
+                beta: 90.0,
+                gamma: 90.0,
+                space_group_number: 75,
+                ..Default::default()
+            },
+            atoms: Atoms(vec![Atom {
+                x: 0.1,
+                y: 0.2,
+                z: 0.3,
+                u11: 0.01,
+                u22: 0.02,
+                u33: 0.03,
+                ..Default::default()
+            }]),
+        };
+
+        let p1 = phase.expand_to_p1();
+
+        assert_eq!(p1.atoms.len(), 4);
+
+        // the fourfold axis swaps U11 and U22
+        let rotated = p1
+            .atoms
+            .iter()
+            .find(|a| (a.x - 0.8).abs() < 1e-8 && (a.y - 0.1).abs() < 1e-8)
+            .unwrap();
+
+        assert!((rotated.u11 - 0.02).abs() < 1e-12);
+        assert!((rotated.u22 - 0.01).abs() < 1e-12);
+        assert!((rotated.u33 - 0.03).abs() < 1e-12);
+    }
+}
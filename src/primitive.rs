@@ -0,0 +1,283 @@
+use cgmath::{Matrix, Matrix3, Matrix4, Point3, SquareMatrix, Transform, Vector3, Zero};
+
+use crate::{
+    phase::{is_same_position, rotation_part, POSITION_TOLERANCE},
+    Cell, CellTransformation, Phase, SpaceGroupSymmetryOperations, TransformationError,
+    REDUCTION_TOLERANCE,
+};
+
+/// The choice of primitive basis for a centred conventional cell.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PrimitiveSetting {
+    /// The primitive cells of ITA Vol. A, table 1.5.1.1, e.g. `(a-b)/2, (a+b)/2, c` for C
+    /// and the obverse rhombohedral cell for R.
+    #[default]
+    Ita,
+    /// The primitive cells of Setyawan & Curtarolo (2010) used for band structure paths.
+    /// They coincide with [`Self::Ita`] except for C-centred monoclinic cells, which use
+    /// `(a+b)/2, (-a+b)/2, c`.
+    SetyawanCurtarolo,
+}
+
+impl Cell {
+    /// Returns the centring letter of the space group symbol, e.g. `F` for `Fm-3m`.
+    pub fn centering(&self) -> Option<char> {
+        let symbol = match self.space_group_number {
+            0 => Some(self.space_group.as_str()),
+            number => crate::SPACEGROUP_SYMBOLS.get(&number).map(String::as_str),
+        };
+
+        symbol?
+            .chars()
+            .next()
+            .map(|c| c.to_ascii_uppercase())
+            .filter(|c| "PABCIFR".contains(*c))
+    }
+
+    /// Returns the transformation from the conventional to the primitive cell, or `None` if
+    /// the centring is unknown. Rhombohedral space groups are expected in hexagonal axes.
+    pub fn primitive_transformation(
+        &self,
+        setting: PrimitiveSetting,
+    ) -> Option<CellTransformation> {
+        let is_monoclinic = (3..=15).contains(&self.space_group_number);
+        let h = 0.5;
+        let t = 1.0 / 3.0;
+
+        // the columns are the new basis vectors
+        let matrix = match self.centering()? {
+            'P' => Matrix3::identity(),
+            'A' => Matrix3::new(1.0, 0.0, 0.0, 0.0, h, -h, 0.0, h, h),
+            'B' => Matrix3::new(h, 0.0, -h, 0.0, 1.0, 0.0, h, 0.0, h),
+            'C' if is_monoclinic && setting == PrimitiveSetting::SetyawanCurtarolo => {
+                Matrix3::new(h, h, 0.0, -h, h, 0.0, 0.0, 0.0, 1.0)
+            }
+            'C' => Matrix3::new(h, -h, 0.0, h, h, 0.0, 0.0, 0.0, 1.0),
+            'I' => Matrix3::new(-h, h, h, h, -h, h, h, h, -h),
+            'F' => Matrix3::new(0.0, h, h, h, 0.0, h, h, h, 0.0),
+            'R' => Matrix3::new(2.0 * t, t, t, -t, t, t, -t, -2.0 * t, t),
+            _ => return None,
+        };
+
+        Some(CellTransformation::new(matrix, Vector3::zero()))
+    }
+
+    /// Returns the symmetry operations of the space group in the primitive cell of
+    /// [`Self::primitive_transformation`], without the centring translations.
+    pub fn primitive_symmetry_operations(
+        &self,
+        setting: PrimitiveSetting,
+    ) -> Option<Vec<Matrix4<f64>>> {
+        let operations = self.symmetry_operations()?;
+
+        Some(
+            self.primitive_transformation(setting)?
+                .transform_operations(operations),
+        )
+    }
+}
+
+impl Phase {
+    /// Returns the primitive cell in P1 with the ITA choice of basis. See
+    /// [`Self::to_primitive_with`].
+    pub fn to_primitive(&self) -> Result<Phase, TransformationError> {
+        self.to_primitive_with(PrimitiveSetting::Ita)
+    }
+
+    /// Returns the primitive cell of a centred phase in P1, containing one copy of each atom
+    /// per primitive cell. The symmetry in the primitive basis is available from
+    /// [`Cell::primitive_symmetry_operations`]. Primitive phases are returned unchanged.
+    pub fn to_primitive_with(
+        &self,
+        setting: PrimitiveSetting,
+    ) -> Result<Phase, TransformationError> {
+        let transformation = self.cell.primitive_transformation(setting).ok_or_else(|| {
+            TransformationError::new(format!(
+                "Unknown centring of space group {}",
+                self.cell.space_group
+            ))
+        })?;
+
+        if self.cell.centering() == Some('P') {
+            return Ok(self.clone());
+        }
+
+        transformation.transform_phase_to_p1(self)
+    }
+
+    /// Returns the conventional cell of the lattice.
+    ///
+    /// Phases with a tabulated space group other than P1 are already conventional and
+    /// returned unchanged. Otherwise the lattice is identified from the Niggli-reduced cell,
+    /// see [`Cell::lattice_character`], and the atoms are expanded into the conventional
+    /// cell. The space group is then recognized if the structure has the operations of a
+    /// tabulated setting at the same origin, in which case the atoms are reduced to an
+    /// asymmetric unit; otherwise the result stays in P1.
+    pub fn to_conventional(&self) -> Result<Phase, TransformationError> {
+        if self.cell.space_group_number > 1 && self.cell.symmetry_operations().is_some() {
+            return Ok(self.clone());
+        }
+
+        let character = self.cell.lattice_character(REDUCTION_TOLERANCE);
+        let mut conventional = character
+            .transformation
+            .transform_phase_to_p1(&self.expand_to_p1())?;
+
+        let operations = structure_symmetry_operations(&conventional);
+
+        match SpaceGroupSymmetryOperations::find_largest_subgroup(&operations) {
+            Some(number) if number > 1 => conventional.reduce_to_asymmetric_unit(number),
+            _ => log::info!("No tabulated space group matches the conventional cell."),
+        }
+
+        Ok(conventional)
+    }
+}
+
+/// Returns the tabulated operations that preserve the metric of the cell and map every atom
+/// onto an atom of the same type.
+fn structure_symmetry_operations(phase: &Phase) -> Vec<Matrix4<f64>> {
+    let g = phase.cell.metric_tensor();
+    let tolerance = 1e-4 * (g.x.x + g.y.y + g.z.z);
+
+    let points = phase
+        .atoms
+        .iter()
+        .map(|atom| (atom.type_.as_str(), Point3::new(atom.x, atom.y, atom.z)))
+        .collect::<Vec<_>>();
+
+    let is_symmetry = |operation: &Matrix4<f64>| {
+        let w = rotation_part(operation);
+        let difference = w.transpose() * g * w - g;
+
+        let preserves_metric = [difference.x, difference.y, difference.z]
+            .iter()
+            .all(|column| column.x.abs() + column.y.abs() + column.z.abs() < tolerance);
+
+        preserves_metric
+            && points.iter().all(|(type_, point)| {
+                let image = operation.transform_point(*point);
+
+                points.iter().any(|(other_type, other)| {
+                    other_type == type_ && is_same_position(image, *other, POSITION_TOLERANCE)
+                })
+            })
+    };
+
+    let mut operations = Vec::new();
+
+    for table in SpaceGroupSymmetryOperations::get_all().values() {
+        if table.iter().all(is_symmetry) {
+            operations.extend(table.iter().copied());
+        }
+    }
+
+    operations
+}
+
+#[cfg(test)]
+mod test_primitive {
+    use cgmath::assert_abs_diff_eq;
+
+    use super::PrimitiveSetting;
+    use crate::{
+        test_util::{phase, rock_salt, uniaxial_phase},
+        Cell,
+    };
+
+    #[test]
+    fn test_rock_salt() {
+        let rock_salt = rock_salt();
+
+        let primitive = rock_salt.to_primitive().unwrap();
+
+        assert_eq!(primitive.cell.space_group_number, 1);
+        assert_eq!(primitive.atoms.len(), 2);
+        assert_abs_diff_eq!(primitive.cell.a, 5.64 / 2f64.sqrt(), epsilon = 1e-8);
+        assert_abs_diff_eq!(primitive.cell.alpha, 60.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(primitive.cell.volume, 5.64f64.powi(3) / 4.0, epsilon = 1e-8);
+
+        let operations = rock_salt
+            .cell
+            .primitive_symmetry_operations(PrimitiveSetting::Ita)
+            .unwrap();
+        assert_eq!(operations.len(), 48);
+
+        let conventional = primitive.to_conventional().unwrap();
+
+        assert_eq!(conventional.cell.space_group_number, 225);
+        assert_eq!(conventional.atoms.len(), 2);
+        assert_abs_diff_eq!(conventional.cell.a, 5.64, epsilon = 1e-8);
+        assert_abs_diff_eq!(conventional.cell.gamma, 90.0, epsilon = 1e-8);
+        assert_eq!(conventional.expand_to_p1().atoms.len(), 8);
+    }
+
+    #[test]
+    fn test_body_centred() {
+        let tungsten = phase(3.16, "Im-3m", 229, &[("W", [0.0; 3])]);
+        let primitive = tungsten.to_primitive().unwrap();
+
+        assert_eq!(primitive.atoms.len(), 1);
+        assert_abs_diff_eq!(primitive.cell.alpha, 109.4712206, epsilon = 1e-6);
+        assert_eq!(
+            primitive.to_conventional().unwrap().cell.space_group,
+            "Im-3m"
+        );
+    }
+
+    #[test]
+    fn test_rhombohedral() {
+        let phase = uniaxial_phase(
+            4.0,
+            10.0,
+            "R-3m",
+            166,
+            &[("Bi", [0.0, 0.0, 0.2]), ("Se", [0.0, 0.0, 0.0])],
+        );
+        let primitive = phase.to_primitive().unwrap();
+
+        assert_eq!(primitive.atoms.len(), 3);
+        assert_abs_diff_eq!(primitive.cell.a, primitive.cell.b, epsilon = 1e-8);
+        assert_abs_diff_eq!(primitive.cell.alpha, primitive.cell.gamma, epsilon = 1e-8);
+        assert_abs_diff_eq!(
+            primitive.cell.volume * 3.0,
+            phase.cell.calculate_volume(),
+            epsilon = 1e-8
+        );
+
+        let conventional = primitive.to_conventional().unwrap();
+        assert_eq!(conventional.cell.space_group_number, 166);
+        assert_eq!(conventional.expand_to_p1().atoms.len(), 9);
+    }
+
+    #[test]
+    fn test_settings() {
+        let monoclinic = Cell {
+            a: 5.0,
+            b: 6.0,
+            c: 7.0,
+            alpha: 90.0,
+            beta: 100.0,
+            gamma: 90.0,
+            space_group: "C12/m1".to_string(),
+            space_group_number: 12,
+            ..Default::default()
+        };
+
+        let ita = monoclinic
+            .primitive_transformation(PrimitiveSetting::Ita)
+            .unwrap();
+        let sc = monoclinic
+            .primitive_transformation(PrimitiveSetting::SetyawanCurtarolo)
+            .unwrap();
+
+        assert_ne!(ita, sc);
+        assert_abs_diff_eq!(ita.determinant(), 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(sc.determinant(), 0.5, epsilon = 1e-12);
+
+        let primitive = phase(4.0, "Pm-3m", 221, &[("Po", [0.0; 3])]);
+        assert_eq!(primitive.to_primitive().unwrap(), primitive);
+        assert_eq!(primitive.to_conventional().unwrap(), primitive);
+    }
+}
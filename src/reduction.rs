@@ -0,0 +1,812 @@
+use cgmath::{InnerSpace, Matrix, Matrix3, SquareMatrix, Vector3, Zero};
+
+use crate::{Cell, CellTransformation};
+
+/// The default relative tolerance of the cell reductions.
+pub const REDUCTION_TOLERANCE: f64 = 1e-5;
+
+const MAX_ITERATIONS: usize = 1000;
+
+/// A reduced cell together with the transformation from the original cell.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReducedCell {
+    /// The reduced cell in P1.
+    pub cell: Cell,
+    /// The integer change of basis with `det(P) = 1` leading from the original to the reduced
+    /// cell.
+    pub transformation: CellTransformation,
+}
+
+/// The 14 Bravais lattice types.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BravaisLattice {
+    TriclinicP,
+    MonoclinicP,
+    MonoclinicC,
+    OrthorhombicP,
+    OrthorhombicC,
+    OrthorhombicI,
+    OrthorhombicF,
+    TetragonalP,
+    TetragonalI,
+    Rhombohedral,
+    HexagonalP,
+    CubicP,
+    CubicI,
+    CubicF,
+}
+
+impl BravaisLattice {
+    /// Returns the Pearson symbol of the lattice, e.g. `mC`.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Self::TriclinicP => "aP",
+            Self::MonoclinicP => "mP",
+            Self::MonoclinicC => "mC",
+            Self::OrthorhombicP => "oP",
+            Self::OrthorhombicC => "oC",
+            Self::OrthorhombicI => "oI",
+            Self::OrthorhombicF => "oF",
+            Self::TetragonalP => "tP",
+            Self::TetragonalI => "tI",
+            Self::Rhombohedral => "hR",
+            Self::HexagonalP => "hP",
+            Self::CubicP => "cP",
+            Self::CubicI => "cI",
+            Self::CubicF => "cF",
+        }
+    }
+}
+
+impl std::fmt::Display for BravaisLattice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.symbol())
+    }
+}
+
+/// One of the 44 lattice characters of ITA Vol. A, table 3.1.3.1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatticeCharacter {
+    /// The number of the character, `1..=44`.
+    pub number: u8,
+    pub bravais_lattice: BravaisLattice,
+    /// The change of basis from the original cell to the conventional cell of the lattice,
+    /// passing through the Niggli-reduced cell. Rhombohedral lattices use hexagonal axes.
+    pub transformation: CellTransformation,
+}
+
+impl Cell {
+    /// Returns the Niggli-reduced cell using the algorithm of Křivý & Gruber (1976) with the
+    /// numerically stable comparisons of Grosse-Kunstleve et al. (2004).
+    ///
+    /// Comparisons of the metric use `tolerance · V^(2/3)`, see [`REDUCTION_TOLERANCE`].
+    pub fn niggli_reduce(&self, tolerance: f64) -> ReducedCell {
+        let g = self.metric_tensor();
+        let epsilon = tolerance * self.calculate_volume().powf(2.0 / 3.0);
+        let mut p = Matrix3::identity();
+
+        let lt = |x: f64, y: f64| x < y - epsilon;
+        let gt = |x: f64, y: f64| lt(y, x);
+        let eq = |x: f64, y: f64| !lt(x, y) && !lt(y, x);
+
+        for iteration in 0.. {
+            if iteration == MAX_ITERATIONS {
+                log::warn!("Niggli reduction did not converge after {MAX_ITERATIONS} steps.");
+                break;
+            }
+
+            let [a, b, _, d, e, _] = niggli_parameters(&g, &p);
+
+            // N1
+            if gt(a, b) || (eq(a, b) && gt(d.abs(), e.abs())) {
+                p = p * rows([[0, -1, 0], [-1, 0, 0], [0, 0, -1]]);
+            }
+
+            // N2
+            let [_, b, c, d, e, f] = niggli_parameters(&g, &p);
+
+            if gt(b, c) || (eq(b, c) && gt(e.abs(), f.abs())) {
+                p = p * rows([[-1, 0, 0], [0, 0, -1], [0, -1, 0]]);
+                continue;
+            }
+
+            // N3 and N4
+            let signs = [d, e, f];
+            let positive = signs.iter().filter(|&&x| lt(0.0, x)).count();
+            let zero = signs
+                .iter()
+                .filter(|&&x| !lt(0.0, x) && !lt(x, 0.0))
+                .count();
+
+            if positive == 3 || (zero == 0 && positive == 1) {
+                let sign = |x: f64| if lt(x, 0.0) { -1 } else { 1 };
+                p = p * rows([[sign(d), 0, 0], [0, sign(e), 0], [0, 0, sign(f)]]);
+            } else {
+                let mut sign = [1, 1, 1];
+                let mut zero = None;
+
+                for (i, &x) in signs.iter().enumerate() {
+                    if lt(0.0, x) {
+                        sign[i] = -1;
+                    } else if !lt(x, 0.0) {
+                        zero = Some(i);
+                    }
+                }
+
+                if sign[0] * sign[1] * sign[2] < 0 {
+                    if let Some(i) = zero {
+                        sign[i] = -1;
+                    }
+                }
+
+                p = p * rows([[sign[0], 0, 0], [0, sign[1], 0], [0, 0, sign[2]]]);
+            }
+
+            let [a, b, _, d, e, f] = niggli_parameters(&g, &p);
+
+            // N5
+            if gt(d.abs(), b) || (eq(d, b) && lt(e + e, f)) || (eq(d, -b) && lt(f, 0.0)) {
+                let s = if d > 0.0 { -1 } else { 1 };
+                p = p * rows([[1, 0, 0], [0, 1, s], [0, 0, 1]]);
+                continue;
+            }
+
+            // N6
+            if gt(e.abs(), a) || (eq(e, a) && lt(d + d, f)) || (eq(e, -a) && lt(f, 0.0)) {
+                let s = if e > 0.0 { -1 } else { 1 };
+                p = p * rows([[1, 0, s], [0, 1, 0], [0, 0, 1]]);
+                continue;
+            }
+
+            // N7
+            if gt(f.abs(), a) || (eq(f, a) && lt(d + d, e)) || (eq(f, -a) && lt(e, 0.0)) {
+                let s = if f > 0.0 { -1 } else { 1 };
+                p = p * rows([[1, s, 0], [0, 1, 0], [0, 0, 1]]);
+                continue;
+            }
+
+            // N8
+            let sum = d + e + f + a + b;
+
+            if lt(sum, 0.0) || (eq(sum, 0.0) && gt(a + a + e + e + f, 0.0)) {
+                p = p * rows([[1, 0, 1], [0, 1, 1], [0, 0, 1]]);
+                continue;
+            }
+
+            break;
+        }
+
+        reduced_cell(self, p)
+    }
+
+    /// Returns the Delaunay-reduced cell obtained by Selling reduction.
+    ///
+    /// The superbase `b1, b2, b3, b4 = -(b1 + b2 + b3)` is reduced until all scalar products
+    /// `bi · bj` are non-positive. The reduced cell uses the three shortest vectors of the
+    /// superbase in order of increasing length, as a right-handed basis.
+    pub fn delaunay_reduce(&self, tolerance: f64) -> ReducedCell {
+        let g = self.metric_tensor();
+        let epsilon = tolerance * self.calculate_volume().powf(2.0 / 3.0);
+
+        let mut superbase = [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.0, 0.0, 1.0),
+            Vector3::new(-1.0, -1.0, -1.0),
+        ];
+        let dot = |u: &Vector3<f64>, v: &Vector3<f64>| u.dot(g * v);
+
+        for iteration in 0.. {
+            if iteration == MAX_ITERATIONS {
+                log::warn!("Selling reduction did not converge after {MAX_ITERATIONS} steps.");
+                break;
+            }
+
+            let mut largest = (epsilon, None);
+
+            for i in 0..4 {
+                for j in i + 1..4 {
+                    let s = dot(&superbase[i], &superbase[j]);
+
+                    if s > largest.0 {
+                        largest = (s, Some((i, j)));
+                    }
+                }
+            }
+
+            let Some((i, j)) = largest.1 else {
+                break;
+            };
+
+            let b_i = superbase[i];
+
+            for (k, b_k) in superbase.iter_mut().enumerate() {
+                if k == i {
+                    *b_k = -b_i;
+                } else if k != j {
+                    *b_k += b_i;
+                }
+            }
+        }
+
+        superbase.sort_by(|u, v| dot(u, u).total_cmp(&dot(v, v)));
+
+        let mut p = Matrix3::from_cols(superbase[0], superbase[1], superbase[2]);
+
+        if p.determinant() < 0.0 {
+            p = -p;
+        }
+
+        reduced_cell(self, p)
+    }
+
+    /// Returns the lattice character of the Niggli-reduced cell, which identifies the Bravais
+    /// lattice and the transformation to its conventional cell.
+    ///
+    /// Among the characters whose conditions hold within the tolerance the most specific one
+    /// is chosen, so e.g. a cubic metric is not reported as tetragonal.
+    pub fn lattice_character(&self, tolerance: f64) -> LatticeCharacter {
+        let reduced = self.niggli_reduce(tolerance);
+        let g = reduced.cell.metric_tensor();
+        let epsilon = tolerance * reduced.cell.calculate_volume().powf(2.0 / 3.0);
+
+        // A, B, C, D, E, F in the notation of ITA
+        let parameters = [g.x.x, g.y.y, g.z.z, g.z.y, g.z.x, g.y.x];
+        let is_type_i = parameters[3..].iter().all(|&x| x > epsilon);
+
+        let character = LATTICE_CHARACTERS
+            .iter()
+            .filter(|character| character.is_type_i == is_type_i)
+            .filter(|character| {
+                character.conditions.iter().all(|condition| {
+                    let value: f64 = condition.iter().zip(parameters).map(|(c, x)| c * x).sum();
+                    let scale: f64 = condition.iter().map(|c| c.abs()).sum();
+
+                    value.abs() <= epsilon * scale
+                })
+            })
+            .fold(
+                None,
+                |best: Option<&LatticeCharacterEntry>, character| match best {
+                    Some(best) if best.conditions.len() >= character.conditions.len() => Some(best),
+                    _ => Some(character),
+                },
+            )
+            .expect("The triclinic characters have no conditions");
+
+        let transformation = reduced.transformation.then(&CellTransformation::new(
+            rows(character.transformation).transpose(),
+            Vector3::zero(),
+        ));
+
+        LatticeCharacter {
+            number: character.number,
+            bravais_lattice: character.bravais_lattice,
+            transformation,
+        }
+    }
+}
+
+/// Returns `A, B, C, ξ, η, ζ` of the cell `(a, b, c) P`, where `ξ = 2 b · c` etc.
+fn niggli_parameters(g: &Matrix3<f64>, p: &Matrix3<f64>) -> [f64; 6] {
+    let g = p.transpose() * g * p;
+
+    [g.x.x, g.y.y, g.z.z, 2.0 * g.z.y, 2.0 * g.z.x, 2.0 * g.y.x]
+}
+
+/// Returns the matrix with the given rows.
+fn rows(m: [[i32; 3]; 3]) -> Matrix3<f64> {
+    let column = |j: usize| Vector3::new(m[0][j], m[1][j], m[2][j]).map(f64::from);
+
+    Matrix3::from_cols(column(0), column(1), column(2))
+}
+
+fn reduced_cell(cell: &Cell, p: Matrix3<f64>) -> ReducedCell {
+    let p = Matrix3::from_cols(
+        p.x.map(f64::round),
+        p.y.map(f64::round),
+        p.z.map(f64::round),
+    );
+    let transformation = CellTransformation::new(p, Vector3::zero());
+
+    ReducedCell {
+        cell: transformation.transform_cell(cell),
+        transformation,
+    }
+}
+
+struct LatticeCharacterEntry {
+    number: u8,
+    is_type_i: bool,
+    /// Linear conditions `c · (A, B, C, D, E, F) = 0` with `D = b · c`, `E = a · c` and
+    /// `F = a · b`.
+    conditions: &'static [[f64; 6]],
+    bravais_lattice: BravaisLattice,
+    /// The conventional basis vectors as rows in terms of the reduced basis.
+    transformation: [[i32; 3]; 3],
+}
+
+const A_EQ_B: [f64; 6] = [1.0, -1.0, 0.0, 0.0, 0.0, 0.0];
+const B_EQ_C: [f64; 6] = [0.0, 1.0, -1.0, 0.0, 0.0, 0.0];
+const D_EQ_E: [f64; 6] = [0.0, 0.0, 0.0, 1.0, -1.0, 0.0];
+const E_EQ_F: [f64; 6] = [0.0, 0.0, 0.0, 0.0, 1.0, -1.0];
+const D_ZERO: [f64; 6] = [0.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+const E_ZERO: [f64; 6] = [0.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+const F_ZERO: [f64; 6] = [0.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+const D_HALF_A: [f64; 6] = [-0.5, 0.0, 0.0, 1.0, 0.0, 0.0];
+const E_HALF_A: [f64; 6] = [-0.5, 0.0, 0.0, 0.0, 1.0, 0.0];
+const F_HALF_A: [f64; 6] = [-0.5, 0.0, 0.0, 0.0, 0.0, 1.0];
+const D_MINUS_HALF_A: [f64; 6] = [0.5, 0.0, 0.0, 1.0, 0.0, 0.0];
+const E_MINUS_HALF_A: [f64; 6] = [0.5, 0.0, 0.0, 0.0, 1.0, 0.0];
+const F_MINUS_HALF_A: [f64; 6] = [0.5, 0.0, 0.0, 0.0, 0.0, 1.0];
+const D_HALF_B: [f64; 6] = [0.0, -0.5, 0.0, 1.0, 0.0, 0.0];
+const D_MINUS_HALF_B: [f64; 6] = [0.0, 0.5, 0.0, 1.0, 0.0, 0.0];
+const D_MINUS_THIRD_A: [f64; 6] = [1.0 / 3.0, 0.0, 0.0, 1.0, 0.0, 0.0];
+const E_MINUS_THIRD_A: [f64; 6] = [1.0 / 3.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+const F_MINUS_THIRD_A: [f64; 6] = [1.0 / 3.0, 0.0, 0.0, 0.0, 0.0, 1.0];
+const D_QUARTER_A: [f64; 6] = [-0.25, 0.0, 0.0, 1.0, 0.0, 0.0];
+/// `2 |D + E + F| = A + B` for type II cells.
+const BODY_DIAGONAL: [f64; 6] = [1.0, 1.0, 0.0, 2.0, 2.0, 2.0];
+
+const LATTICE_CHARACTERS: [LatticeCharacterEntry; 44] = {
+    use BravaisLattice::*;
+
+    const IDENTITY: [[i32; 3]; 3] = [[1, 0, 0], [0, 1, 0], [0, 0, 1]];
+
+    const fn entry(
+        number: u8,
+        is_type_i: bool,
+        conditions: &'static [[f64; 6]],
+        bravais_lattice: BravaisLattice,
+        transformation: [[i32; 3]; 3],
+    ) -> LatticeCharacterEntry {
+        LatticeCharacterEntry {
+            number,
+            is_type_i,
+            conditions,
+            bravais_lattice,
+            transformation,
+        }
+    }
+
+    [
+        // A = B = C
+        entry(
+            1,
+            true,
+            &[A_EQ_B, B_EQ_C, D_HALF_A, E_HALF_A, F_HALF_A],
+            CubicF,
+            [[1, -1, 1], [1, 1, -1], [-1, 1, 1]],
+        ),
+        entry(
+            2,
+            true,
+            &[A_EQ_B, B_EQ_C, D_EQ_E, E_EQ_F],
+            Rhombohedral,
+            [[1, -1, 0], [-1, 0, 1], [-1, -1, -1]],
+        ),
+        entry(
+            3,
+            false,
+            &[A_EQ_B, B_EQ_C, D_ZERO, E_ZERO, F_ZERO],
+            CubicP,
+            IDENTITY,
+        ),
+        entry(
+            4,
+            false,
+            &[A_EQ_B, B_EQ_C, D_EQ_E, E_EQ_F],
+            Rhombohedral,
+            [[1, -1, 0], [-1, 0, 1], [-1, -1, -1]],
+        ),
+        entry(
+            5,
+            false,
+            &[
+                A_EQ_B,
+                B_EQ_C,
+                D_MINUS_THIRD_A,
+                E_MINUS_THIRD_A,
+                F_MINUS_THIRD_A,
+            ],
+            CubicI,
+            [[1, 0, 1], [1, 1, 0], [0, 1, 1]],
+        ),
+        entry(
+            6,
+            false,
+            &[A_EQ_B, B_EQ_C, BODY_DIAGONAL, D_EQ_E],
+            TetragonalI,
+            [[0, 1, 1], [1, 0, 1], [1, 1, 0]],
+        ),
+        entry(
+            7,
+            false,
+            &[A_EQ_B, B_EQ_C, BODY_DIAGONAL, E_EQ_F],
+            TetragonalI,
+            [[1, 0, 1], [1, 1, 0], [0, 1, 1]],
+        ),
+        entry(
+            8,
+            false,
+            &[A_EQ_B, B_EQ_C, BODY_DIAGONAL],
+            OrthorhombicI,
+            [[-1, -1, 0], [-1, 0, -1], [0, -1, -1]],
+        ),
+        // A = B
+        entry(
+            9,
+            true,
+            &[A_EQ_B, D_HALF_A, E_HALF_A, F_HALF_A],
+            Rhombohedral,
+            [[1, 0, 0], [-1, 1, 0], [-1, -1, 3]],
+        ),
+        entry(
+            10,
+            true,
+            &[A_EQ_B, D_EQ_E],
+            MonoclinicC,
+            [[1, 1, 0], [1, -1, 0], [0, 0, -1]],
+        ),
+        entry(
+            11,
+            false,
+            &[A_EQ_B, D_ZERO, E_ZERO, F_ZERO],
+            TetragonalP,
+            IDENTITY,
+        ),
+        entry(
+            12,
+            false,
+            &[A_EQ_B, D_ZERO, E_ZERO, F_MINUS_HALF_A],
+            HexagonalP,
+            IDENTITY,
+        ),
+        entry(
+            13,
+            false,
+            &[A_EQ_B, D_ZERO, E_ZERO],
+            OrthorhombicC,
+            [[1, 1, 0], [-1, 1, 0], [0, 0, 1]],
+        ),
+        entry(
+            14,
+            false,
+            &[A_EQ_B, D_EQ_E],
+            MonoclinicC,
+            [[1, 1, 0], [-1, 1, 0], [0, 0, 1]],
+        ),
+        entry(
+            15,
+            false,
+            &[A_EQ_B, D_MINUS_HALF_A, E_MINUS_HALF_A, F_ZERO],
+            TetragonalI,
+            [[1, 0, 0], [0, 1, 0], [1, 1, 2]],
+        ),
+        entry(
+            16,
+            false,
+            &[A_EQ_B, BODY_DIAGONAL, D_EQ_E],
+            OrthorhombicF,
+            [[-1, -1, 0], [1, -1, 0], [1, 1, 2]],
+        ),
+        entry(
+            17,
+            false,
+            &[A_EQ_B, BODY_DIAGONAL],
+            MonoclinicC,
+            [[1, -1, 0], [1, 1, 0], [1, 0, 1]],
+        ),
+        // B = C
+        entry(
+            18,
+            true,
+            &[B_EQ_C, D_QUARTER_A, E_HALF_A, F_HALF_A],
+            TetragonalI,
+            [[0, -1, 1], [1, -1, -1], [1, 0, 0]],
+        ),
+        entry(
+            19,
+            true,
+            &[B_EQ_C, E_HALF_A, F_HALF_A],
+            OrthorhombicI,
+            [[-1, 0, 0], [0, -1, 1], [-1, 1, 1]],
+        ),
+        entry(
+            20,
+            true,
+            &[B_EQ_C, E_EQ_F],
+            MonoclinicC,
+            [[0, 1, 1], [0, 1, -1], [-1, 0, 0]],
+        ),
+        entry(
+            21,
+            false,
+            &[B_EQ_C, D_ZERO, E_ZERO, F_ZERO],
+            TetragonalP,
+            [[0, 1, 0], [0, 0, 1], [1, 0, 0]],
+        ),
+        entry(
+            22,
+            false,
+            &[B_EQ_C, D_MINUS_HALF_B, E_ZERO, F_ZERO],
+            HexagonalP,
+            [[0, 1, 0], [0, 0, 1], [1, 0, 0]],
+        ),
+        entry(
+            23,
+            false,
+            &[B_EQ_C, E_ZERO, F_ZERO],
+            OrthorhombicC,
+            [[0, 1, 1], [0, -1, 1], [1, 0, 0]],
+        ),
+        entry(
+            24,
+            false,
+            &[B_EQ_C, BODY_DIAGONAL, E_MINUS_THIRD_A, F_MINUS_THIRD_A],
+            Rhombohedral,
+            [[1, 2, 1], [0, -1, 1], [1, 0, 0]],
+        ),
+        entry(
+            25,
+            false,
+            &[B_EQ_C, E_EQ_F],
+            MonoclinicC,
+            [[0, 1, 1], [0, -1, 1], [1, 0, 0]],
+        ),
+        // no conditions on A, B and C
+        entry(
+            26,
+            true,
+            &[D_QUARTER_A, E_HALF_A, F_HALF_A],
+            OrthorhombicF,
+            [[1, 0, 0], [-1, 2, 0], [-1, 0, 2]],
+        ),
+        entry(
+            27,
+            true,
+            &[E_HALF_A, F_HALF_A],
+            MonoclinicC,
+            [[-1, 2, 0], [-1, 0, 0], [0, -1, 1]],
+        ),
+        entry(
+            28,
+            true,
+            &[E_HALF_A, [0.0, 0.0, 0.0, -2.0, 0.0, 1.0]],
+            MonoclinicC,
+            [[-1, 0, 0], [-1, 0, 2], [0, 1, 0]],
+        ),
+        entry(
+            29,
+            true,
+            &[[0.0, 0.0, 0.0, -2.0, 1.0, 0.0], F_HALF_A],
+            MonoclinicC,
+            [[1, 0, 0], [1, -2, 0], [0, 0, -1]],
+        ),
+        entry(
+            30,
+            true,
+            &[D_HALF_B, [0.0, 0.0, 0.0, 0.0, -2.0, 1.0]],
+            MonoclinicC,
+            [[0, 1, 0], [0, 1, -2], [-1, 0, 0]],
+        ),
+        entry(31, true, &[], TriclinicP, IDENTITY),
+        entry(
+            32,
+            false,
+            &[D_ZERO, E_ZERO, F_ZERO],
+            OrthorhombicP,
+            IDENTITY,
+        ),
+        entry(33, false, &[D_ZERO, F_ZERO], MonoclinicP, IDENTITY),
+        entry(
+            34,
+            false,
+            &[D_ZERO, E_ZERO],
+            MonoclinicP,
+            [[-1, 0, 0], [0, 0, -1], [0, -1, 0]],
+        ),
+        entry(
+            35,
+            false,
+            &[E_ZERO, F_ZERO],
+            MonoclinicP,
+            [[0, -1, 0], [-1, 0, 0], [0, 0, -1]],
+        ),
+        entry(
+            36,
+            false,
+            &[D_ZERO, E_MINUS_HALF_A, F_ZERO],
+            OrthorhombicC,
+            [[1, 0, 0], [-1, 0, -2], [0, 1, 0]],
+        ),
+        entry(
+            37,
+            false,
+            &[E_MINUS_HALF_A, F_ZERO],
+            MonoclinicC,
+            [[1, 0, 2], [1, 0, 0], [0, 1, 0]],
+        ),
+        entry(
+            38,
+            false,
+            &[D_ZERO, E_ZERO, F_MINUS_HALF_A],
+            OrthorhombicC,
+            [[-1, 0, 0], [1, 2, 0], [0, 0, -1]],
+        ),
+        entry(
+            39,
+            false,
+            &[E_ZERO, F_MINUS_HALF_A],
+            MonoclinicC,
+            [[-1, -2, 0], [-1, 0, 0], [0, 0, -1]],
+        ),
+        entry(
+            40,
+            false,
+            &[D_MINUS_HALF_B, E_ZERO, F_ZERO],
+            OrthorhombicC,
+            [[0, -1, 0], [0, 1, 2], [-1, 0, 0]],
+        ),
+        entry(
+            41,
+            false,
+            &[D_MINUS_HALF_B, F_ZERO],
+            MonoclinicC,
+            [[0, -1, -2], [0, -1, 0], [-1, 0, 0]],
+        ),
+        entry(
+            42,
+            false,
+            &[D_MINUS_HALF_B, E_MINUS_HALF_A, F_ZERO],
+            OrthorhombicI,
+            [[-1, 0, 0], [0, -1, 0], [1, 1, 2]],
+        ),
+        entry(
+            43,
+            false,
+            &[BODY_DIAGONAL, [0.0, 1.0, 0.0, 2.0, 0.0, 1.0]],
+            MonoclinicC,
+            [[-1, -1, 0], [-1, -1, -2], [1, 0, 0]],
+        ),
+        entry(44, false, &[], TriclinicP, IDENTITY),
+    ]
+};
+
+#[cfg(test)]
+mod test_reduction {
+    use cgmath::{assert_abs_diff_eq, Matrix, Vector3, Zero};
+
+    use crate::{BravaisLattice, Cell, CellTransformation, REDUCTION_TOLERANCE};
+
+    use super::rows;
+
+    fn cubic(a: f64) -> Cell {
+        Cell {
+            a,
+            b: a,
+            c: a,
+            alpha: 90.0,
+            beta: 90.0,
+            gamma: 90.0,
+            ..Default::default()
+        }
+    }
+
+    fn transformed(cell: &Cell, m: [[i32; 3]; 3]) -> Cell {
+        CellTransformation::new(rows(m).transpose(), Vector3::zero()).transform_cell(cell)
+    }
+
+    #[test]
+    fn test_niggli_skewed_basis() {
+        let cell = transformed(&cubic(4.0), [[1, 2, 0], [0, 1, -3], [0, 0, 1]]);
+        let reduced = cell.niggli_reduce(REDUCTION_TOLERANCE);
+
+        assert_abs_diff_eq!(reduced.cell.a, 4.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(reduced.cell.b, 4.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(reduced.cell.c, 4.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(reduced.cell.alpha, 90.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(reduced.transformation.determinant(), 1.0, epsilon = 1e-10);
+
+        // the transformation leads from the input to the reduced cell
+        let cell = reduced.transformation.transform_cell(&cell);
+        assert_abs_diff_eq!(cell.gamma, reduced.cell.gamma, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn test_niggli_fcc_primitive() {
+        let primitive = Cell {
+            a: 4.0,
+            b: 4.0,
+            c: 4.0,
+            alpha: 60.0,
+            beta: 60.0,
+            gamma: 60.0,
+            ..Default::default()
+        };
+        let cell = transformed(&primitive, [[1, 0, 0], [1, 1, 0], [-2, 1, 1]]);
+        let reduced = cell.niggli_reduce(REDUCTION_TOLERANCE);
+
+        assert_abs_diff_eq!(reduced.cell.a, 4.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(reduced.cell.c, 4.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(reduced.cell.alpha, 60.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(reduced.cell.beta, 60.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(reduced.cell.gamma, 60.0, epsilon = 1e-8);
+
+        let character = cell.lattice_character(REDUCTION_TOLERANCE);
+        let conventional = character.transformation.transform_cell(&cell);
+
+        assert_eq!(character.number, 1);
+        assert_eq!(character.bravais_lattice, BravaisLattice::CubicF);
+        assert_abs_diff_eq!(conventional.a, 4.0 * 2f64.sqrt(), epsilon = 1e-8);
+        assert_abs_diff_eq!(conventional.beta, 90.0, epsilon = 1e-8);
+    }
+
+    #[test]
+    fn test_delaunay() {
+        let cell = Cell::from_lattice_vectors([
+            Vector3::new(-1.5, 1.5, 1.5),
+            Vector3::new(1.5, -1.5, 1.5),
+            Vector3::new(1.5, 1.5, -1.5),
+        ]);
+        let cell = transformed(&cell, [[1, 0, 0], [1, 1, 0], [1, 1, 1]]);
+        let reduced = cell.delaunay_reduce(REDUCTION_TOLERANCE);
+        let g = reduced.cell.metric_tensor();
+        let b4 = -(g.x + g.y + g.z);
+
+        assert_abs_diff_eq!(reduced.transformation.determinant(), 1.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(reduced.cell.volume, cell.volume, epsilon = 1e-8);
+        assert!(g.y.x <= 1e-8 && g.z.x <= 1e-8 && g.z.y <= 1e-8);
+        assert!(b4.x <= 1e-8 && b4.y <= 1e-8 && b4.z <= 1e-8);
+    }
+
+    #[test]
+    fn test_lattice_characters() {
+        let character = cubic(5.0).lattice_character(REDUCTION_TOLERANCE);
+        assert_eq!(
+            (character.number, character.bravais_lattice),
+            (3, BravaisLattice::CubicP)
+        );
+
+        let hexagonal = Cell {
+            a: 3.0,
+            b: 3.0,
+            c: 5.0,
+            alpha: 90.0,
+            beta: 90.0,
+            gamma: 120.0,
+            ..Default::default()
+        };
+        let character = hexagonal.lattice_character(REDUCTION_TOLERANCE);
+        assert_eq!(character.number, 12);
+        assert_eq!(character.bravais_lattice.to_string(), "hP");
+
+        // primitive cell of a body-centred tetragonal lattice with a = 3 and c = 7
+        let cell = Cell::from_lattice_vectors([
+            Vector3::new(-1.5, 1.5, 3.5),
+            Vector3::new(1.5, -1.5, 3.5),
+            Vector3::new(1.5, 1.5, -3.5),
+        ]);
+        let character = cell.lattice_character(REDUCTION_TOLERANCE);
+        let conventional = character.transformation.transform_cell(&cell);
+
+        assert_eq!(character.bravais_lattice, BravaisLattice::TetragonalI);
+        assert_abs_diff_eq!(character.transformation.determinant(), 2.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(conventional.volume, 63.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(conventional.a, 3.0, epsilon = 1e-8);
+        assert_abs_diff_eq!(conventional.c, 7.0, epsilon = 1e-8);
+
+        let triclinic = Cell {
+            a: 3.0,
+            b: 4.0,
+            c: 5.0,
+            alpha: 80.0,
+            beta: 85.0,
+            gamma: 95.0,
+            ..Default::default()
+        };
+        let character = triclinic.lattice_character(REDUCTION_TOLERANCE);
+        assert_eq!(character.bravais_lattice, BravaisLattice::TriclinicP);
+    }
+}
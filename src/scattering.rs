@@ -0,0 +1,70 @@
+use crate::data::{ATOMIC_NUMBERS, NEUTRON_SCATTERING_LENGTHS};
+
+/// Radiation used to weight the contributions of the atoms.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Radiation {
+    /// Weights atoms by their atomic number, i.e. the X-ray form factor at `Q = 0`.
+    #[default]
+    XRay,
+    /// Weights atoms by their bound coherent neutron scattering length.
+    Neutron,
+}
+
+impl Radiation {
+    /// Returns the scattering power of an atom type.
+    pub fn scattering_power(&self, atom_type: &str) -> Result<f64, UnknownScatteringPowerError> {
+        let power = match self {
+            Radiation::XRay => ATOMIC_NUMBERS.get(atom_type).map(|z| *z as f64),
+            Radiation::Neutron => NEUTRON_SCATTERING_LENGTHS.get(atom_type).map(|b| *b as f64),
+        };
+
+        power.ok_or_else(|| UnknownScatteringPowerError::new(atom_type, *self))
+    }
+}
+
+impl std::fmt::Display for Radiation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Radiation::XRay => write!(f, "X-ray"),
+            Radiation::Neutron => write!(f, "neutron"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownScatteringPowerError(String);
+
+impl UnknownScatteringPowerError {
+    pub fn new(atom_type: impl AsRef<str>, radiation: Radiation) -> Self {
+        Self(format!(
+            "Unknown {} scattering power for atom type: {}",
+            radiation,
+            atom_type.as_ref()
+        ))
+    }
+}
+
+impl std::fmt::Display for UnknownScatteringPowerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for UnknownScatteringPowerError {}
+
+#[cfg(test)]
+mod test_scattering_power {
+    use super::Radiation;
+
+    #[test]
+    fn test() {
+        assert_eq!(Radiation::XRay.scattering_power("Fe"), Ok(26.0));
+        assert!((Radiation::Neutron.scattering_power("O").unwrap() - 5.803).abs() < 1e-6);
+        assert!(Radiation::XRay.scattering_power("Xx").is_err());
+
+        // atom types and labels resolve to the element
+        assert!((Radiation::Neutron.scattering_power("Fe2+").unwrap() - 9.45).abs() < 1e-6);
+        assert!((Radiation::Neutron.scattering_power("O1").unwrap() - 5.803).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,392 @@
+use cgmath::{
+    EuclideanSpace, InnerSpace, Matrix, Matrix3, Matrix4, Point3, SquareMatrix, Transform,
+};
+
+use crate::{phase::rotation_part, AdpType, Atom, Cell};
+
+/// Names of the components of the displacement parameters in the order used by
+/// [`AdpConstraints`].
+pub const ADP_COMPONENTS: [&str; 6] = ["U11", "U22", "U33", "U12", "U13", "U23"];
+
+/// The symmetry operations of a space group leaving a position invariant.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiteSymmetry {
+    /// Operations with their translations chosen such that they map the position onto itself.
+    pub operations: Vec<Matrix4<f64>>,
+    /// Number of symmetry equivalent positions in the unit cell.
+    pub multiplicity: usize,
+}
+
+impl SiteSymmetry {
+    /// Determines the site symmetry of a fractional position in the space group of the cell.
+    ///
+    /// Operations mapping the position within `tolerance` in Å onto a lattice translation of
+    /// itself are part of the stabilizer. Returns `None` if the space group is unknown or the
+    /// tolerance is not positive.
+    pub fn new(cell: &Cell, position: Point3<f64>, tolerance: f64) -> Option<Self> {
+        if tolerance.is_nan() || tolerance <= 0.0 {
+            return None;
+        }
+
+        let symmetry_operations = cell.symmetry_operations()?;
+        let orthogonalization_matrix = cell.orthogonalization_matrix();
+
+        let operations = symmetry_operations
+            .iter()
+            .filter_map(|operation| {
+                let shift = operation.transform_point(position) - position;
+                let lattice_translation = shift.map(|x| x.round());
+
+                match (orthogonalization_matrix * (shift - lattice_translation)).magnitude()
+                    < tolerance
+                {
+                    true => {
+                        let mut operation = *operation;
+                        operation.w -= lattice_translation.extend(0.0);
+                        Some(operation)
+                    }
+                    false => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // even the identity is missing for positions that are not finite
+        if operations.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            multiplicity: symmetry_operations.len() / operations.len(),
+            operations,
+        })
+    }
+
+    /// Returns the average of the images of the position under the site symmetry operations,
+    /// which lies exactly on the special position.
+    pub fn symmetrize_position(&self, position: Point3<f64>) -> Point3<f64> {
+        let sum = self
+            .operations
+            .iter()
+            .map(|operation| operation.transform_point(position).to_vec())
+            .fold(cgmath::Vector3::new(0.0, 0.0, 0.0), |a, b| a + b);
+
+        Point3::from_vec(sum / self.operations.len() as f64)
+    }
+
+    /// Returns the restrictions the site symmetry imposes on the displacement parameters in the
+    /// CIF convention.
+    pub fn adp_constraints(&self, cell: &Cell) -> AdpConstraints {
+        let reciprocal_lengths = cell.reciprocal_lengths();
+        let n = Matrix3::from_diagonal(reciprocal_lengths);
+        let n_inv = Matrix3::from_diagonal(reciprocal_lengths.map(|x| 1.0 / x));
+
+        let mut projector = [[0.0; 6]; 6];
+
+        for operation in &self.operations {
+            let rotation = n_inv * rotation_part(operation) * n;
+
+            for (k, column) in projector.iter_mut().enumerate() {
+                let mut unit = [0.0; 6];
+                unit[k] = 1.0;
+
+                let u = vector_to_u(unit);
+                let u = vector_from_u(rotation * u * rotation.transpose());
+
+                for (p, u) in column.iter_mut().zip(u) {
+                    *p += u / self.operations.len() as f64;
+                }
+            }
+        }
+
+        // rows of I - P span the constraints, stored column-major as `projector[column][row]`
+        let mut equations = (0..6)
+            .map(|row| {
+                let mut equation = [0.0; 6];
+
+                for (column, coefficient) in equation.iter_mut().enumerate() {
+                    let identity = if row == column { 1.0 } else { 0.0 };
+                    *coefficient = identity - projector[column][row];
+                }
+
+                equation
+            })
+            .collect::<Vec<_>>();
+
+        row_reduce(&mut equations);
+
+        AdpConstraints {
+            equations,
+            projector,
+        }
+    }
+}
+
+/// Linear restrictions `Σ c_k U_k = 0` on the displacement parameters
+/// `(U11, U22, U33, U12, U13, U23)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AdpConstraints {
+    /// Coefficients of the constraint equations in reduced row echelon form.
+    pub equations: Vec<[f64; 6]>,
+    projector: [[f64; 6]; 6],
+}
+
+impl AdpConstraints {
+    /// Returns the number of independent displacement parameters.
+    pub fn number_of_free_parameters(&self) -> usize {
+        6 - self.equations.len()
+    }
+
+    /// Projects the displacement parameters onto the closest ones satisfying the constraints.
+    pub fn apply(&self, u: [f64; 6]) -> [f64; 6] {
+        let mut result = [0.0; 6];
+
+        for (column, u) in self.projector.iter().zip(u) {
+            for (r, p) in result.iter_mut().zip(column) {
+                *r += p * u;
+            }
+        }
+
+        result
+    }
+
+    /// Checks whether the displacement parameters satisfy all constraints.
+    pub fn is_satisfied(&self, u: [f64; 6], tolerance: f64) -> bool {
+        self.equations.iter().all(|equation| {
+            equation
+                .iter()
+                .zip(u)
+                .map(|(c, u)| c * u)
+                .sum::<f64>()
+                .abs()
+                < tolerance
+        })
+    }
+}
+
+impl std::fmt::Display for AdpConstraints {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let equations = self
+            .equations
+            .iter()
+            .map(|equation| {
+                let mut terms = String::new();
+
+                for (coefficient, name) in equation.iter().zip(ADP_COMPONENTS) {
+                    if *coefficient == 0.0 {
+                        continue;
+                    }
+
+                    let sign = match (terms.is_empty(), *coefficient < 0.0) {
+                        (true, true) => "-",
+                        (true, false) => "",
+                        (false, true) => " - ",
+                        (false, false) => " + ",
+                    };
+
+                    let magnitude = match (coefficient.abs() - 1.0).abs() < 1e-12 {
+                        true => String::new(),
+                        false => format!("{} ", coefficient.abs()),
+                    };
+
+                    terms.push_str(&format!("{}{}{}", sign, magnitude, name));
+                }
+
+                format!("{} = 0", terms)
+            })
+            .collect::<Vec<_>>();
+
+        f.write_str(&equations.join(", "))
+    }
+}
+
+impl Atom {
+    /// Returns the site symmetry of the atom, see [`SiteSymmetry::new`].
+    pub fn site_symmetry(&self, cell: &Cell, tolerance: f64) -> Option<SiteSymmetry> {
+        SiteSymmetry::new(cell, Point3::new(self.x, self.y, self.z), tolerance)
+    }
+
+    /// Moves the atom exactly onto its special position, makes its anisotropic displacement
+    /// parameters obey the site symmetry and sets its multiplicity.
+    ///
+    /// Returns `None` and leaves the atom unchanged if the space group is unknown.
+    pub fn symmetrize(&mut self, cell: &Cell, tolerance: f64) -> Option<()> {
+        let site_symmetry = self.site_symmetry(cell, tolerance)?;
+
+        let position = site_symmetry.symmetrize_position(Point3::new(self.x, self.y, self.z));
+        self.x = position.x;
+        self.y = position.y;
+        self.z = position.z;
+
+        if matches!(self.adp_type, AdpType::Uani | AdpType::Bani) {
+            let constraints = site_symmetry.adp_constraints(cell);
+            let u = constraints.apply(vector_from_u(self.u_ani()));
+            self.set_u_ani(vector_to_u(u));
+        }
+
+        self.multiplicity = Some(site_symmetry.multiplicity as f64);
+
+        Some(())
+    }
+}
+
+fn vector_to_u(u: [f64; 6]) -> Matrix3<f64> {
+    Matrix3::new(u[0], u[3], u[4], u[3], u[1], u[5], u[4], u[5], u[2])
+}
+
+fn vector_from_u(u: Matrix3<f64>) -> [f64; 6] {
+    [u.x.x, u.y.y, u.z.z, u.x.y, u.x.z, u.y.z]
+}
+
+/// Brings the equations into reduced row echelon form and removes empty rows.
+fn row_reduce(equations: &mut Vec<[f64; 6]>) {
+    let mut pivot_row = 0;
+
+    for column in 0..6 {
+        let Some(best) = (pivot_row..equations.len()).max_by(|a, b| {
+            equations[*a][column]
+                .abs()
+                .total_cmp(&equations[*b][column].abs())
+        }) else {
+            break;
+        };
+
+        if equations[best][column].abs() < 1e-8 {
+            continue;
+        }
+
+        equations.swap(pivot_row, best);
+
+        let pivot = equations[pivot_row][column];
+        equations[pivot_row].iter_mut().for_each(|x| *x /= pivot);
+
+        for row in 0..equations.len() {
+            if row == pivot_row {
+                continue;
+            }
+
+            let factor = equations[row][column];
+            let pivot_equation = equations[pivot_row];
+
+            for (x, p) in equations[row].iter_mut().zip(pivot_equation) {
+                *x -= factor * p;
+            }
+        }
+
+        pivot_row += 1;
+    }
+
+    equations.truncate(pivot_row);
+
+    for x in equations.iter_mut().flatten() {
+        *x = match x.abs() < 1e-8 {
+            true => 0.0,
+            false => (*x * 1e8).round() / 1e8,
+        };
+    }
+}
+
+#[cfg(test)]
+mod test_site_symmetry {
+    use cgmath::{assert_abs_diff_eq, Point3};
+
+    use super::SiteSymmetry;
+    use crate::{AdpType, Atom, Cell};
+
+    fn cell(a: f64, c: f64, gamma: f64, space_group_number: u8) -> Cell {
+        Cell {
+            a,
+            b: a,
+            c,
+            alpha: 90.0,
+            beta: 90.0,
+            gamma,
+            space_group_number,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_tetragonal() {
+        let cell = cell(4.0, 6.0, 90.0, 123);
+        let site_symmetry = SiteSymmetry::new(&cell, Point3::new(0.0, 0.0, 0.0), 1e-3).unwrap();
+
+        assert_eq!(site_symmetry.operations.len(), 16);
+        assert_eq!(site_symmetry.multiplicity, 1);
+
+        let constraints = site_symmetry.adp_constraints(&cell);
+
+        assert_eq!(constraints.number_of_free_parameters(), 2);
+        assert_eq!(
+            constraints.to_string(),
+            "U11 - U22 = 0, U12 = 0, U13 = 0, U23 = 0"
+        );
+    }
+
+    #[test]
+    fn test_hexagonal() {
+        let cell = cell(3.0, 5.0, 120.0, 191);
+        let site_symmetry = SiteSymmetry::new(&cell, Point3::new(0.0, 0.0, 0.0), 1e-3).unwrap();
+        let constraints = site_symmetry.adp_constraints(&cell);
+
+        assert_eq!(constraints.number_of_free_parameters(), 2);
+        assert!(constraints.is_satisfied([0.02, 0.02, 0.03, 0.01, 0.0, 0.0], 1e-12));
+        assert!(!constraints.is_satisfied([0.02, 0.02, 0.03, 0.0, 0.0, 0.0], 1e-12));
+    }
+
+    #[test]
+    fn test_general_position() {
+        let cell = cell(4.0, 6.0, 90.0, 123);
+        let site_symmetry = SiteSymmetry::new(&cell, Point3::new(0.1, 0.2, 0.3), 1e-3).unwrap();
+
+        assert_eq!(site_symmetry.multiplicity, 16);
+        assert_eq!(
+            site_symmetry
+                .adp_constraints(&cell)
+                .number_of_free_parameters(),
+            6
+        );
+    }
+
+    #[test]
+    fn test_invalid_tolerance() {
+        let cell = cell(4.0, 6.0, 90.0, 123);
+
+        for tolerance in [0.0, -1e-3, f64::NAN] {
+            assert_eq!(
+                SiteSymmetry::new(&cell, Point3::new(0.0, 0.0, 0.0), tolerance),
+                None
+            );
+        }
+
+        assert_eq!(
+            SiteSymmetry::new(&cell, Point3::new(f64::NAN, 0.0, 0.0), 1e-3),
+            None
+        );
+    }
+
+    #[test]
+    fn test_symmetrize() {
+        let cell = cell(4.0, 4.0, 90.0, 221);
+
+        let mut atom = Atom {
+            x: 0.5001,
+            y: 0.4999,
+            z: 0.5,
+            adp_type: AdpType::Uani,
+            u11: 0.010,
+            u22: 0.012,
+            u33: 0.014,
+            u12: 0.001,
+            ..Default::default()
+        };
+
+        atom.symmetrize(&cell, 1e-2).unwrap();
+
+        assert_abs_diff_eq!(atom.x, 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(atom.y, 0.5, epsilon = 1e-12);
+        assert_abs_diff_eq!(atom.u11, 0.012, epsilon = 1e-12);
+        assert_abs_diff_eq!(atom.u22, 0.012, epsilon = 1e-12);
+        assert_abs_diff_eq!(atom.u12, 0.0, epsilon = 1e-12);
+        assert_eq!(atom.multiplicity, Some(1.0));
+    }
+}
@@ -0,0 +1,183 @@
+use cgmath::{Matrix3, Matrix4, SquareMatrix, Vector3};
+
+use crate::{CellTransformation, Phase, SpaceGroupSymmetryOperations, TransformationError};
+
+impl Phase {
+    /// Returns the supercell in P1 whose basis is `(a', b', c') = (a, b, c) P`, i.e. the columns
+    /// of `p` are the new basis vectors in terms of the old ones. `p[row][column]` is indexed
+    /// like the matrix.
+    ///
+    /// The phase is expanded to P1 and its atoms are repeated at the `det(P)` lattice points
+    /// inside the new cell. For `det(P) > 1` labels get the number of the lattice point
+    /// appended, e.g. `Na1_2`. Non-diagonal matrices are supported, e.g.
+    /// `[[1, -1, 0], [1, 1, 0], [0, 0, 1]]` for a √2 × √2 × 1 cell.
+    pub fn supercell(&self, p: [[i32; 3]; 3]) -> Result<Phase, TransformationError> {
+        supercell_transformation(p)?.transform_phase_to_p1(self)
+    }
+
+    /// Like [`Self::supercell`], but describes the supercell with the largest tabulated space
+    /// group contained in the symmetry of the parent.
+    ///
+    /// The candidate operations are those of the parent space group that map the supercell
+    /// lattice onto itself, combined with the parent lattice translations inside the new cell.
+    /// Atoms are reduced to one per orbit of the new group, so expanding the result to P1
+    /// gives the same atoms as [`Self::supercell`]. Without a match the supercell stays in P1.
+    pub fn supercell_with_symmetry(&self, p: [[i32; 3]; 3]) -> Result<Phase, TransformationError> {
+        let transformation = supercell_transformation(p)?;
+        let mut supercell = transformation.transform_phase_to_p1(self)?;
+
+        let identity = vec![Matrix4::identity()];
+        let parent_operations = self.cell.symmetry_operations().unwrap_or(&identity);
+        let operations = transformation.transform_operations(parent_operations);
+
+        let Some(number) = SpaceGroupSymmetryOperations::find_largest_subgroup(&operations) else {
+            log::info!("No tabulated space group matches the supercell. Using P1.");
+            return Ok(supercell);
+        };
+
+        supercell.reduce_to_asymmetric_unit(number);
+
+        Ok(supercell)
+    }
+}
+
+/// Returns the transformation of an integer matrix given by rows, rejecting singular
+/// matrices and changes of handedness.
+fn supercell_transformation(p: [[i32; 3]; 3]) -> Result<CellTransformation, TransformationError> {
+    let matrix = Matrix3::from_cols(
+        Vector3::new(p[0][0], p[1][0], p[2][0]).map(f64::from),
+        Vector3::new(p[0][1], p[1][1], p[2][1]).map(f64::from),
+        Vector3::new(p[0][2], p[1][2], p[2][2]).map(f64::from),
+    );
+
+    if matrix.determinant() < 0.5 {
+        return Err(TransformationError::new(format!(
+            "Supercell matrix must have a positive determinant, found {}",
+            matrix.determinant()
+        )));
+    }
+
+    Ok(CellTransformation::new(matrix, Vector3::new(0.0, 0.0, 0.0)))
+}
+
+#[cfg(test)]
+mod test_supercell {
+    use cgmath::assert_abs_diff_eq;
+
+    use crate::{test_util::rock_salt, Atom, Atoms, Cell, Phase};
+
+    #[test]
+    fn test_diagonal() {
+        let supercell = rock_salt()
+            .supercell([[2, 0, 0], [0, 2, 0], [0, 0, 1]])
+            .unwrap();
+
+        assert_eq!(supercell.cell.space_group_number, 1);
+        assert_eq!(supercell.atoms.len(), 32);
+        assert_abs_diff_eq!(supercell.cell.a, 11.28, epsilon = 1e-10);
+        assert_abs_diff_eq!(supercell.cell.c, 5.64, epsilon = 1e-10);
+        assert_eq!(supercell.atoms[0].label, "Na1_1");
+        assert_eq!(supercell.atoms[3].label, "Na1_4");
+
+        // positions are wrapped into the new cell and halved along the doubled axes
+        let on_grid = |x: f64, step: f64| ((x / step) - (x / step).round()).abs() < 1e-8;
+        assert!(supercell.atoms.iter().all(|a| [a.x, a.y, a.z]
+            .iter()
+            .all(|x| (0.0..1.0).contains(x))
+            && on_grid(a.x, 0.25)
+            && on_grid(a.y, 0.25)
+            && on_grid(a.z, 0.5)));
+        assert!(supercell.atoms.iter().any(|a| a.y > 0.5));
+    }
+
+    #[test]
+    fn test_non_diagonal() {
+        let supercell = rock_salt()
+            .supercell([[1, -1, 0], [1, 1, 0], [0, 0, 1]])
+            .unwrap();
+
+        assert_eq!(supercell.atoms.len(), 16);
+        assert_abs_diff_eq!(supercell.cell.a, 5.64 * 2f64.sqrt(), epsilon = 1e-10);
+        assert_abs_diff_eq!(supercell.cell.gamma, 90.0, epsilon = 1e-10);
+        assert_abs_diff_eq!(supercell.cell.volume, 2.0 * 5.64f64.powi(3), epsilon = 1e-8);
+
+        // no two atoms on the same site
+        for (i, a) in supercell.atoms.iter().enumerate() {
+            for b in supercell.atoms.iter().skip(i + 1) {
+                let d = [a.x - b.x, a.y - b.y, a.z - b.z];
+                assert!(d.iter().any(|x| (x - x.round()).abs() > 1e-6));
+            }
+        }
+    }
+
+    #[test]
+    fn test_adp() {
+        let phase = Phase {
+            cell: Cell {
+                a: 4.0,
+                b: 4.0,
+                c: 4.0,
+                alpha: 90.0,
+                beta: 90.0,
+                gamma: 90.0,
+                space_group_number: 1,
+                ..Default::default()
+            },
+            atoms: Atoms(vec![Atom {
+                u11: 0.01,
+                u22: 0.02,
+                u33: 0.03,
+                ..Default::default()
+            }]),
+        };
+
+        // swapping a and b swaps U11 and U22
+        let supercell = phase.supercell([[0, 1, 0], [1, 0, 0], [0, 0, -1]]).unwrap();
+
+        assert_abs_diff_eq!(supercell.atoms[0].u11, 0.02, epsilon = 1e-12);
+        assert_abs_diff_eq!(supercell.atoms[0].u22, 0.01, epsilon = 1e-12);
+        assert_abs_diff_eq!(supercell.atoms[0].u33, 0.03, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let phase = rock_salt();
+
+        assert!(phase.supercell([[1, 0, 0], [0, 1, 0], [0, 0, 0]]).is_err());
+        assert!(phase.supercell([[-1, 0, 0], [0, 1, 0], [0, 0, 1]]).is_err());
+    }
+
+    #[test]
+    fn test_with_symmetry() {
+        let phase = rock_salt();
+
+        // the conventional cell doubled along c is body-centred tetragonal
+        let supercell = phase
+            .supercell_with_symmetry([[1, 0, 0], [0, 1, 0], [0, 0, 2]])
+            .unwrap();
+
+        assert_eq!(supercell.cell.space_group_number, 139);
+        assert_eq!(supercell.atoms.len(), 5);
+        assert_eq!(supercell.expand_to_p1().atoms.len(), 16);
+
+        // a cubic supercell keeps the full symmetry
+        let supercell = phase
+            .supercell_with_symmetry([[2, 0, 0], [0, 2, 0], [0, 0, 2]])
+            .unwrap();
+
+        assert_eq!(supercell.cell.space_group_number, 225);
+        assert_eq!(supercell.cell.space_group, "Fm-3m");
+        assert_eq!(supercell.expand_to_p1().atoms.len(), 64);
+
+        // a triclinic parent stays triclinic
+        let mut triclinic = phase.expand_to_p1();
+        triclinic.cell.alpha = 80.0;
+
+        let supercell = triclinic
+            .supercell_with_symmetry([[2, 0, 0], [0, 1, 0], [0, 0, 1]])
+            .unwrap();
+
+        assert_eq!(supercell.cell.space_group_number, 1);
+        assert_eq!(supercell.atoms.len(), 16);
+    }
+}
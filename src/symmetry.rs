@@ -186,6 +186,286 @@ impl SpaceGroupSymmetryOperations {
     }
 }
 
+impl SpaceGroupSymmetryOperations {
+    /// Returns the number of the space group whose tabulated operations equal the given ones,
+    /// comparing translations modulo lattice translations.
+    ///
+    /// Only the settings of the tables are recognized.
+    pub fn find(operations: &[cgmath::Matrix4<f64>]) -> Option<u8> {
+        let mut operations = operations.to_vec();
+        deduplicate_operations(&mut operations);
+
+        SPACEGROUP_SYMMETRY_OPERATIONS
+            .iter()
+            .filter(|(_, table)| table.len() == operations.len())
+            .find(|(_, table)| {
+                operations
+                    .iter()
+                    .all(|a| table.iter().any(|b| is_same_operation(a, b)))
+            })
+            .map(|(number, _)| *number)
+    }
+
+    /// Returns the number of the largest space group whose tabulated operations are all
+    /// contained in the given ones, comparing translations modulo lattice translations.
+    ///
+    /// Groups of the same order are resolved towards more operations without translation, e.g.
+    /// I4/mmm over I4_1/acd, and then towards the higher number.
+    pub fn find_largest_subgroup(operations: &[cgmath::Matrix4<f64>]) -> Option<u8> {
+        let keys = operations
+            .iter()
+            .map(operation_key)
+            .collect::<std::collections::HashSet<_>>();
+
+        let symmorphic_operations = |table: &[cgmath::Matrix4<f64>]| {
+            table
+                .iter()
+                .filter(|op| operation_key(op)[9..] == [0, 0, 0])
+                .count()
+        };
+
+        SPACEGROUP_SYMMETRY_OPERATIONS
+            .iter()
+            .filter(|(_, table)| table.iter().all(|op| keys.contains(&operation_key(op))))
+            .max_by_key(|(number, table)| (table.len(), symmorphic_operations(table), **number))
+            .map(|(number, _)| *number)
+    }
+}
+
+/// Returns a hashable form of an operation with the translation reduced into `[0, 1)`.
+fn operation_key(operation: &cgmath::Matrix4<f64>) -> [i64; 12] {
+    let rotation = (0..3).flat_map(|i| (0..3).map(move |j| operation[i][j].round() as i64));
+    let translation = (0..3).map(|i| ((operation.w[i] * 1e6).round() as i64).rem_euclid(1_000_000));
+
+    let mut key = [0; 12];
+    key.iter_mut()
+        .zip(rotation.chain(translation))
+        .for_each(|(k, v)| *k = v);
+
+    key
+}
+
+/// Checks whether two operations differ at most by a lattice translation.
+pub(crate) fn is_same_operation(a: &cgmath::Matrix4<f64>, b: &cgmath::Matrix4<f64>) -> bool {
+    let rotation_equal = (0..3).all(|i| (0..3).all(|j| (a[i][j] - b[i][j]).abs() < 1e-6));
+    let translation_equal = (0..3).all(|i| {
+        let d = a.w[i] - b.w[i];
+
+        (d - d.round()).abs() < 1e-6
+    });
+
+    rotation_equal && translation_equal
+}
+
+/// Removes operations that only differ by a lattice translation.
+pub(crate) fn deduplicate_operations(operations: &mut Vec<cgmath::Matrix4<f64>>) {
+    let mut unique: Vec<cgmath::Matrix4<f64>> = Vec::with_capacity(operations.len());
+
+    for operation in operations.drain(..) {
+        if !unique.iter().any(|u| is_same_operation(u, &operation)) {
+            unique.push(operation);
+        }
+    }
+
+    *operations = unique;
+}
+
+/// Parses a symmetry operation in coordinate triplet notation, e.g. `-x+1/2, y, z+0.5`.
+///
+/// Case and whitespace are ignored. Returns `None` if the string is not a valid operation.
+pub fn parse_symmetry_operation(s: &str) -> Option<cgmath::Matrix4<f64>> {
+    let rows = s.split(',').collect::<Vec<_>>();
+
+    if rows.len() != 3 {
+        return None;
+    }
+
+    let mut operation = cgmath::Matrix4::from_scale(0.0);
+    operation.w.w = 1.0;
+
+    for (row, expression) in rows.iter().enumerate() {
+        let expression = expression
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect::<String>()
+            .to_ascii_lowercase();
+
+        if expression.is_empty() {
+            return None;
+        }
+
+        let mut rest = expression.as_str();
+
+        while !rest.is_empty() {
+            let (sign, unsigned) = match rest.as_bytes()[0] {
+                b'-' => (-1.0, &rest[1..]),
+                b'+' => (1.0, &rest[1..]),
+                _ => (1.0, rest),
+            };
+
+            let end = unsigned[1.min(unsigned.len())..]
+                .find(['+', '-'])
+                .map_or(unsigned.len(), |i| i + 1);
+            let term = &unsigned[..end];
+            rest = &unsigned[end..];
+
+            // terms are either a coordinate with an optional factor, e.g. `2x` or `2*x`, or a
+            // fraction or decimal number
+            match term.strip_suffix(['x', 'y', 'z']) {
+                Some(factor) => {
+                    let column = match term.as_bytes()[term.len() - 1] {
+                        b'x' => 0,
+                        b'y' => 1,
+                        _ => 2,
+                    };
+
+                    let factor = match factor.trim_end_matches('*') {
+                        "" => 1.0,
+                        factor => parse_fraction(factor)?,
+                    };
+
+                    operation[column][row] += sign * factor;
+                }
+                None => operation.w[row] += sign * parse_fraction(term)?,
+            }
+        }
+    }
+
+    Some(operation)
+}
+
+fn parse_fraction(s: &str) -> Option<f64> {
+    match s.split_once('/') {
+        Some((numerator, denominator)) => {
+            let denominator = denominator.parse::<f64>().ok()?;
+
+            match denominator == 0.0 {
+                true => None,
+                false => Some(numerator.parse::<f64>().ok()? / denominator),
+            }
+        }
+        None => s.parse().ok(),
+    }
+}
+
+/// Formats a symmetry operation in coordinate triplet notation, e.g. `-x+1/2,y,z+1/2`, with
+/// translations as fractions with denominators up to 12.
+pub fn format_symmetry_operation(operation: &cgmath::Matrix4<f64>) -> String {
+    let rows = (0..3)
+        .map(|row| {
+            let mut expression = String::new();
+
+            for (column, name) in ["x", "y", "z"].iter().enumerate() {
+                let factor = operation[column][row];
+
+                if factor.abs() < 1e-6 {
+                    continue;
+                }
+
+                if factor < 0.0 {
+                    expression.push('-');
+                } else if !expression.is_empty() {
+                    expression.push('+');
+                }
+
+                if (factor.abs() - 1.0).abs() > 1e-6 {
+                    expression.push_str(&format_fraction(factor.abs()));
+                    expression.push('*');
+                }
+
+                expression.push_str(name);
+            }
+
+            let translation = operation.w[row];
+
+            if translation.abs() > 1e-6 {
+                if translation > 0.0 && !expression.is_empty() {
+                    expression.push('+');
+                } else if translation < 0.0 {
+                    expression.push('-');
+                }
+
+                expression.push_str(&format_fraction(translation.abs()));
+            }
+
+            match expression.is_empty() {
+                true => "0".to_string(),
+                false => expression,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    rows.join(",")
+}
+
+fn format_fraction(x: f64) -> String {
+    for denominator in 1..=12 {
+        let numerator = x * denominator as f64;
+
+        if (numerator - numerator.round()).abs() < 1e-6 {
+            return match denominator {
+                1 => format!("{}", numerator.round()),
+                _ => format!("{}/{}", numerator.round(), denominator),
+            };
+        }
+    }
+
+    format!("{}", x)
+}
+
+#[cfg(test)]
+mod test_symmetry_operation_strings {
+    use cgmath::{Matrix4, Vector4};
+
+    #[test]
+    fn test_parse() {
+        let operation = super::parse_symmetry_operation("-X+1/2, y, Z + 0.5").unwrap();
+
+        assert_eq!(operation.x, Vector4::new(-1.0, 0.0, 0.0, 0.0));
+        assert_eq!(operation.w, Vector4::new(0.5, 0.0, 0.5, 1.0));
+
+        let operation = super::parse_symmetry_operation("x-y,x,z+1/6").unwrap();
+
+        assert_eq!(operation.x, Vector4::new(1.0, 1.0, 0.0, 0.0));
+        assert_eq!(operation.y, Vector4::new(-1.0, 0.0, 0.0, 0.0));
+
+        assert!(super::parse_symmetry_operation("x,y").is_none());
+        assert!(super::parse_symmetry_operation("x,y,q").is_none());
+        assert!(super::parse_symmetry_operation("x,,z").is_none());
+    }
+
+    #[test]
+    fn test_format() {
+        for s in [
+            "-x+1/2,y,z+1/2",
+            "x-y,x,z+1/6",
+            "-x,-y,-z",
+            "y+3/4,x+1/4,-z+3/4",
+        ] {
+            let operation = super::parse_symmetry_operation(s).unwrap();
+
+            assert_eq!(super::format_symmetry_operation(&operation), s);
+        }
+
+        assert_eq!(
+            super::format_symmetry_operation(&Matrix4::from_scale(1.0)),
+            "x,y,z"
+        );
+    }
+
+    #[test]
+    fn test_find() {
+        for number in [1, 14, 62, 148, 166, 225, 227] {
+            let operations = super::SpaceGroupSymmetryOperations::get(number).unwrap();
+
+            assert_eq!(
+                super::SpaceGroupSymmetryOperations::find(operations),
+                Some(number)
+            );
+        }
+    }
+}
+
 pub trait IntoSpaceGroupNumber {
     fn into_space_group_number(&self) -> Option<u8>;
 }
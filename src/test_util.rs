@@ -0,0 +1,71 @@
+//! Structures shared by the unit tests.
+
+use crate::{Atom, Atoms, Cell, Phase};
+
+/// Returns a phase with a cubic cell and one fully occupied atom per entry, labelled by type
+/// and count, e.g. `Na1`.
+pub(crate) fn phase(a: f64, space_group: &str, number: u8, atoms: &[(&str, [f64; 3])]) -> Phase {
+    uniaxial_phase(a, a, space_group, number, atoms)
+}
+
+/// Returns a phase like [`phase`] with `c` differing from `a`, i.e. a tetragonal cell, or a
+/// hexagonal one for trigonal and hexagonal space groups.
+pub(crate) fn uniaxial_phase(
+    a: f64,
+    c: f64,
+    space_group: &str,
+    number: u8,
+    atoms: &[(&str, [f64; 3])],
+) -> Phase {
+    let mut counts: Vec<(&str, usize)> = Vec::new();
+
+    let atoms = atoms
+        .iter()
+        .map(|(type_, [x, y, z])| {
+            let count = match counts.iter_mut().find(|(t, _)| t == type_) {
+                Some((_, count)) => {
+                    *count += 1;
+                    *count
+                }
+                None => {
+                    counts.push((type_, 1));
+                    1
+                }
+            };
+
+            Atom {
+                label: format!("{type_}{count}"),
+                type_: type_.to_string(),
+                x: *x,
+                y: *y,
+                z: *z,
+                occupancy: 1.0,
+                ..Default::default()
+            }
+        })
+        .collect();
+
+    Phase {
+        cell: Cell {
+            a,
+            b: a,
+            c,
+            alpha: 90.0,
+            beta: 90.0,
+            gamma: if (143..=194).contains(&number) {
+                120.0
+            } else {
+                90.0
+            },
+            space_group: space_group.to_string(),
+            space_group_number: number,
+            ..Default::default()
+        },
+        atoms: Atoms(atoms),
+    }
+}
+
+/// Returns rock salt, NaCl, with Na at the origin.
+pub(crate) fn rock_salt() -> Phase {
+    phase(5.64, "Fm-3m", 225, &[("Na", [0.0; 3]), ("Cl", [0.5; 3])])
+}
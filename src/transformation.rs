@@ -0,0 +1,630 @@
+use cgmath::{EuclideanSpace, Matrix, Matrix3, Matrix4, Point3, SquareMatrix, Vector3, Zero};
+
+use crate::{
+    parse_symmetry_operation,
+    phase::{is_same_position, rotation_part, wrap_point, POSITION_TOLERANCE},
+    symmetry::deduplicate_operations,
+    Atom, Atoms, Cell, Phase, SpaceGroupSymmetryOperations, SPACEGROUP_SYMBOLS,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TransformationError(String);
+
+impl TransformationError {
+    pub fn new(s: impl AsRef<str>) -> Self {
+        Self(s.as_ref().to_string())
+    }
+}
+
+impl std::fmt::Display for TransformationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for TransformationError {}
+
+/// A change of basis and origin `(P, p)` following ITA Vol. A, section 1.5.
+///
+/// The new basis is `(a', b', c') = (a, b, c) P` and the new origin is `O' = O + p`, so
+/// fractional coordinates transform as `x' = P⁻¹ (x - p)`.
+#[cfg_attr(feature = "serde", derive(serde::Deserialize, serde::Serialize))]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CellTransformation {
+    /// `P`, with the new basis vectors as columns in terms of the old ones.
+    pub matrix: Matrix3<f64>,
+    /// `p`, the new origin in fractional coordinates of the old cell.
+    pub origin_shift: Vector3<f64>,
+}
+
+impl Default for CellTransformation {
+    fn default() -> Self {
+        Self {
+            matrix: Matrix3::identity(),
+            origin_shift: Vector3::zero(),
+        }
+    }
+}
+
+impl CellTransformation {
+    pub fn new(matrix: Matrix3<f64>, origin_shift: Vector3<f64>) -> Self {
+        Self {
+            matrix,
+            origin_shift,
+        }
+    }
+
+    /// Parses the new basis in ITA notation with an optional origin shift, e.g. `a-b,a+b,c` or
+    /// `-a-c,b,a;0,1/2,0`.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (basis, origin_shift) = match s.split_once(';') {
+            Some((basis, origin_shift)) => (basis, Some(origin_shift)),
+            None => (s, None),
+        };
+
+        let basis = basis
+            .chars()
+            .map(|c| match c.to_ascii_lowercase() {
+                'a' => 'x',
+                'b' => 'y',
+                'c' => 'z',
+                c => c,
+            })
+            .collect::<String>();
+
+        // the rows of the parsed operation are the new basis vectors
+        let basis = parse_symmetry_operation(&basis)?;
+
+        if !basis.w.truncate().is_zero() {
+            return None;
+        }
+
+        let origin_shift = match origin_shift {
+            Some(origin_shift) => {
+                let shift = parse_symmetry_operation(origin_shift)?;
+
+                if !rotation_part(&shift).is_zero() {
+                    return None;
+                }
+
+                shift.w.truncate()
+            }
+            None => Vector3::zero(),
+        };
+
+        Some(Self {
+            matrix: rotation_part(&basis).transpose(),
+            origin_shift,
+        })
+    }
+
+    /// Returns `det(P)`, the ratio of the new to the old cell volume.
+    pub fn determinant(&self) -> f64 {
+        self.matrix.determinant()
+    }
+
+    /// Returns `(P, p)⁻¹ = (P⁻¹, -P⁻¹ p)`, or `None` if `P` is singular.
+    pub fn inverse(&self) -> Option<Self> {
+        let q = self.matrix.invert()?;
+
+        Some(Self {
+            matrix: q,
+            origin_shift: -(q * self.origin_shift),
+        })
+    }
+
+    /// Returns the transformation applying `self` and then `next`, where `next` is given
+    /// relative to the cell obtained from `self`.
+    pub fn then(&self, next: &Self) -> Self {
+        Self {
+            matrix: self.matrix * next.matrix,
+            origin_shift: self.origin_shift + self.matrix * next.origin_shift,
+        }
+    }
+
+    /// Returns the transformed cell in P1, since the setting of the space group changes. See
+    /// [`Self::transform_phase`] for identifying the new space group.
+    pub fn transform_cell(&self, cell: &Cell) -> Cell {
+        let m = cell.orthogonalization_matrix() * self.matrix;
+
+        Cell::from_lattice_vectors([m.x, m.y, m.z])
+    }
+
+    /// Returns the fractional coordinates of a point in the new cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `P` is singular.
+    pub fn transform_point(&self, point: Point3<f64>) -> Point3<f64> {
+        Point3::from_vec(self.inverse_matrix() * (point.to_vec() - self.origin_shift))
+    }
+
+    /// Returns `U` in the CIF convention of an atom of `cell` in the new cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `P` is singular.
+    pub fn transform_u_cif(&self, cell: &Cell, u_cif: Matrix3<f64>) -> Matrix3<f64> {
+        let t = self.adp_transformation(cell, &self.transform_cell(cell));
+
+        t * u_cif * t.transpose()
+    }
+
+    /// Returns the operation `(P, p)⁻¹ (W, w) (P, p)` in the new cell.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `P` is singular.
+    pub fn transform_operation(&self, operation: &Matrix4<f64>) -> Matrix4<f64> {
+        let q = self.inverse_matrix();
+        let w = rotation_part(operation);
+
+        let mut new_operation = Matrix4::from(q * w * self.matrix);
+        new_operation.w =
+            (q * (operation.w.truncate() + w * self.origin_shift - self.origin_shift)).extend(1.0);
+
+        new_operation
+    }
+
+    /// Transforms a set of operations including its lattice translations, so that the result
+    /// is complete in the new cell, e.g. gains the centring translations of a conventional
+    /// cell. Operations that do not map the new lattice onto itself are dropped.
+    /// Translations are moved into `[0, 1)`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `P` is singular.
+    pub fn transform_operations(&self, operations: &[Matrix4<f64>]) -> Vec<Matrix4<f64>> {
+        self.transform_operations_checked(operations).0
+    }
+
+    /// Like [`Self::transform_operations`], also returning whether all operations were kept.
+    fn transform_operations_checked(
+        &self,
+        operations: &[Matrix4<f64>],
+    ) -> (Vec<Matrix4<f64>>, bool) {
+        let lattice_points = self.lattice_points();
+        let mut is_complete = true;
+        let mut new_operations = Vec::with_capacity(operations.len() * lattice_points.len());
+
+        for operation in operations {
+            let transformed = self.transform_operation(operation);
+            let rotation = rotation_part(&transformed);
+
+            let is_integral = (0..3)
+                .all(|i| (0..3).all(|j| (rotation[i][j] - rotation[i][j].round()).abs() < 1e-6));
+
+            if !is_integral {
+                is_complete = false;
+                continue;
+            }
+
+            let rotation = Matrix3::from_cols(
+                rotation.x.map(f64::round),
+                rotation.y.map(f64::round),
+                rotation.z.map(f64::round),
+            );
+
+            for point in &lattice_points {
+                let translation = wrap_point(Point3::from_vec(transformed.w.truncate() + point));
+
+                let mut new_operation = Matrix4::from(rotation);
+                new_operation.w = translation.to_vec().extend(1.0);
+                new_operations.push(new_operation);
+            }
+        }
+
+        deduplicate_operations(&mut new_operations);
+
+        (new_operations, is_complete)
+    }
+
+    /// Transforms the cell, atoms, ADPs and space group of a phase.
+    ///
+    /// The atoms stay an asymmetric unit of the transformed operations. If these match a
+    /// tabulated setting the cell gets its space group. Otherwise, e.g. for `P2_1/c` to
+    /// `P2_1/n` or to a primitive cell of a centred lattice, the space group number is kept
+    /// and the operations are stored as [`Cell::setting_operations`]. Only if an operation
+    /// does not map the new lattice onto itself the result is the transformed phase in P1 as
+    /// returned by [`Self::transform_phase_to_p1`].
+    pub fn transform_phase(&self, phase: &Phase) -> Result<Phase, TransformationError> {
+        self.check_handedness()?;
+
+        let identity = vec![Matrix4::identity()];
+        let operations = phase.cell.symmetry_operations().unwrap_or(&identity);
+        let (operations, is_complete) = self.transform_operations_checked(operations);
+
+        if !is_complete {
+            return self.transform_phase_to_p1(phase);
+        }
+
+        let mut cell = self.transform_cell(&phase.cell);
+
+        match SpaceGroupSymmetryOperations::find(&operations) {
+            Some(number) => {
+                cell.space_group_number = number;
+                cell.space_group = SPACEGROUP_SYMBOLS.get(&number).cloned().unwrap_or_default();
+            }
+            None => {
+                cell.space_group_number = phase.cell.space_group_number;
+                cell.space_group = String::new();
+                cell.setting_operations = operations;
+            }
+        }
+
+        let t = self.adp_transformation(&phase.cell, &cell);
+
+        let atoms = phase
+            .atoms
+            .iter()
+            .map(|atom| {
+                let position =
+                    wrap_point(self.transform_point(Point3::new(atom.x, atom.y, atom.z)));
+
+                let mut new_atom = Atom {
+                    x: position.x,
+                    y: position.y,
+                    z: position.z,
+                    ..atom.clone()
+                };
+                new_atom.set_u_ani(t * atom.u_ani() * t.transpose());
+
+                new_atom
+            })
+            .collect();
+
+        Ok(Phase {
+            cell,
+            atoms: Atoms(atoms),
+        })
+    }
+
+    /// Expands a phase to P1 and transforms it.
+    ///
+    /// Atoms are repeated at the lattice points of the old lattice inside the new cell and,
+    /// for `det(P) > 1`, labelled with the number of the lattice point, e.g. `Na1_2`. Atoms
+    /// that coincide in a smaller cell are merged.
+    pub fn transform_phase_to_p1(&self, phase: &Phase) -> Result<Phase, TransformationError> {
+        self.check_handedness()?;
+
+        let p1 = phase.expand_to_p1();
+        let cell = self.transform_cell(&p1.cell);
+        let t = self.adp_transformation(&p1.cell, &cell);
+
+        let lattice_points = self.lattice_points();
+        let is_smaller = self.determinant() < 1.0 - 1e-8;
+
+        let mut atoms: Vec<Atom> = Vec::with_capacity(p1.atoms.len() * lattice_points.len());
+
+        for atom in p1.atoms.iter() {
+            let position = self.transform_point(Point3::new(atom.x, atom.y, atom.z));
+            let u_ani = t * atom.u_ani() * t.transpose();
+
+            for (i, point) in lattice_points.iter().enumerate() {
+                let position = wrap_point(position + point);
+
+                if is_smaller
+                    && atoms.iter().any(|a| {
+                        a.type_ == atom.type_
+                            && is_same_position(
+                                Point3::new(a.x, a.y, a.z),
+                                position,
+                                POSITION_TOLERANCE,
+                            )
+                    })
+                {
+                    continue;
+                }
+
+                let mut new_atom = Atom {
+                    x: position.x,
+                    y: position.y,
+                    z: position.z,
+                    ..atom.clone()
+                };
+                new_atom.set_u_ani(u_ani);
+
+                if lattice_points.len() > 1 {
+                    new_atom.label = format!("{}_{}", atom.label, i + 1);
+                }
+
+                atoms.push(new_atom);
+            }
+        }
+
+        Ok(Phase {
+            cell,
+            atoms: Atoms(atoms),
+        })
+    }
+
+    fn check_handedness(&self) -> Result<(), TransformationError> {
+        match self.determinant() > 1e-8 {
+            true => Ok(()),
+            false => Err(TransformationError::new(format!(
+                "Transformation matrix must have a positive determinant, found {}",
+                self.determinant()
+            ))),
+        }
+    }
+
+    fn inverse_matrix(&self) -> Matrix3<f64> {
+        self.matrix
+            .invert()
+            .expect("Transformation matrix is singular")
+    }
+
+    /// Returns `T` with `U' = T U Tᵀ` for `U` in the CIF convention. `U*` transforms like a
+    /// product of coordinates and `U = N⁻¹ U* N⁻¹` with the reciprocal lengths in `N`.
+    fn adp_transformation(&self, cell: &Cell, new_cell: &Cell) -> Matrix3<f64> {
+        let n = Matrix3::from_diagonal(cell.reciprocal_lengths());
+        let n_new_inv = Matrix3::from_diagonal(new_cell.reciprocal_lengths().map(|x| 1.0 / x));
+
+        n_new_inv * self.inverse_matrix() * n
+    }
+
+    /// Returns the points of the old lattice inside the new cell in new fractional
+    /// coordinates, starting with the origin.
+    fn lattice_points(&self) -> Vec<Vector3<f64>> {
+        let q = self.inverse_matrix();
+
+        let corners = (0..8)
+            .map(|i| {
+                self.matrix
+                    * Vector3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64)
+            })
+            .collect::<Vec<_>>();
+
+        let range = |axis: usize| {
+            let min = corners
+                .iter()
+                .map(|c| c[axis])
+                .fold(f64::INFINITY, f64::min);
+            let max = corners
+                .iter()
+                .map(|c| c[axis])
+                .fold(f64::NEG_INFINITY, f64::max);
+
+            min.floor() as i32..=max.ceil() as i32
+        };
+
+        let mut points = Vec::new();
+
+        for i in range(0) {
+            for j in range(1) {
+                for k in range(2) {
+                    let point = q * Vector3::new(i, j, k).map(f64::from);
+
+                    if [point.x, point.y, point.z]
+                        .iter()
+                        .all(|x| (-1e-8..1.0 - 1e-8).contains(x))
+                    {
+                        points.push(point.map(|x| x.max(0.0)));
+                    }
+                }
+            }
+        }
+
+        points.sort_by(|a, b| {
+            (a.x + a.y + a.z)
+                .total_cmp(&(b.x + b.y + b.z))
+                .then(a.z.total_cmp(&b.z))
+                .then(a.y.total_cmp(&b.y))
+        });
+
+        points
+    }
+}
+
+#[cfg(test)]
+mod test_cell_transformation {
+    use cgmath::{assert_abs_diff_eq, Matrix3, Point3, SquareMatrix, Vector3};
+
+    use super::CellTransformation;
+    use crate::{
+        parse_symmetry_operation, symmetry::is_same_operation, test_util::rock_salt, Atom, Atoms,
+        Cell, Phase, SpaceGroupSymmetryOperations,
+    };
+
+    #[test]
+    fn test_parse() {
+        let transformation = CellTransformation::parse("a-b, a+b, c; 0,1/2,0").unwrap();
+
+        assert_eq!(transformation.matrix.x, Vector3::new(1.0, -1.0, 0.0));
+        assert_eq!(transformation.matrix.y, Vector3::new(1.0, 1.0, 0.0));
+        assert_eq!(transformation.matrix.z, Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(transformation.origin_shift, Vector3::new(0.0, 0.5, 0.0));
+        assert_eq!(transformation.determinant(), 2.0);
+
+        assert!(CellTransformation::parse("a+1/2,b,c").is_none());
+        assert!(CellTransformation::parse("a,b,c;x,0,0").is_none());
+        assert!(CellTransformation::parse("a,b").is_none());
+    }
+
+    #[test]
+    fn test_inverse() {
+        let transformation = CellTransformation::parse("-a-c,b,a;1/4,0,1/2").unwrap();
+        let inverse = transformation.inverse().unwrap();
+
+        let identity = transformation.then(&inverse);
+        assert_abs_diff_eq!(identity.matrix, Matrix3::identity(), epsilon = 1e-12);
+        assert_abs_diff_eq!(
+            identity.origin_shift,
+            Vector3::new(0.0, 0.0, 0.0),
+            epsilon = 1e-12
+        );
+
+        let point = Point3::new(0.1, 0.2, 0.3);
+        let back = inverse.transform_point(transformation.transform_point(point));
+        assert_abs_diff_eq!(back, point, epsilon = 1e-12);
+
+        let cell = Cell {
+            a: 5.0,
+            b: 6.0,
+            c: 7.0,
+            alpha: 90.0,
+            beta: 110.0,
+            gamma: 90.0,
+            ..Default::default()
+        };
+        let u = Matrix3::new(0.01, 0.0, 0.002, 0.0, 0.02, 0.0, 0.002, 0.0, 0.03);
+
+        let new_cell = transformation.transform_cell(&cell);
+        let new_u = transformation.transform_u_cif(&cell, u);
+
+        assert_abs_diff_eq!(new_cell.volume, cell.calculate_volume(), epsilon = 1e-8);
+        assert_abs_diff_eq!(
+            inverse.transform_u_cif(&new_cell, new_u),
+            u,
+            epsilon = 1e-12
+        );
+        assert_abs_diff_eq!(
+            inverse.transform_cell(&new_cell).beta,
+            110.0,
+            epsilon = 1e-8
+        );
+
+        let x = Vector3::new(1.0, 2.0, 3.0);
+        assert!(
+            CellTransformation::new(Matrix3::from_cols(x, x, x), Vector3::new(0.0, 0.0, 0.0))
+                .inverse()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_p21c_to_p21n() {
+        let transformation = CellTransformation::parse("-a-c,b,a").unwrap();
+        let operations =
+            transformation.transform_operations(SpaceGroupSymmetryOperations::get(14).unwrap());
+
+        assert_eq!(operations.len(), 4);
+
+        for expected in ["-x+1/2,y+1/2,-z+1/2", "x+1/2,-y+1/2,z+1/2", "-x,-y,-z"] {
+            let expected = parse_symmetry_operation(expected).unwrap();
+            assert!(operations.iter().any(|op| is_same_operation(op, &expected)));
+        }
+
+        // P2_1/n is not tabulated, its operations are kept with the cell
+        let phase = Phase {
+            cell: Cell {
+                a: 5.0,
+                b: 6.0,
+                c: 7.0,
+                alpha: 90.0,
+                beta: 110.0,
+                gamma: 90.0,
+                space_group_number: 14,
+                ..Default::default()
+            },
+            atoms: Atoms(vec![Atom {
+                x: 0.1,
+                y: 0.2,
+                z: 0.3,
+                ..Default::default()
+            }]),
+        };
+
+        let transformed = transformation.transform_phase(&phase).unwrap();
+
+        assert_eq!(transformed.cell.space_group_number, 14);
+        assert_eq!(transformed.cell.symmetry_operations().unwrap().len(), 4);
+        assert_eq!(transformed.cell.setting_operations, operations);
+        assert_eq!(transformed.atoms.len(), 1);
+        assert_eq!(transformed.expand_to_p1().atoms.len(), 4);
+
+        // and transforming back restores the tabulated setting
+        let back = transformation
+            .inverse()
+            .unwrap()
+            .transform_phase(&transformed)
+            .unwrap();
+
+        assert_eq!(back.cell.space_group_number, 14);
+        assert!(back.cell.setting_operations.is_empty());
+        assert_abs_diff_eq!(back.atoms[0].x, 0.1, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn test_setting_preserved() {
+        // swapping a and b keeps the tetragonal setting
+        let phase = Phase {
+            cell: Cell {
+                a: 4.0,
+                b: 4.0,
+                c: 6.0,
+                alpha: 90.0,
+                beta: 90.0,
+                gamma: 90.0,
+                space_group_number: 123,
+                ..Default::default()
+            },
+            atoms: Atoms(vec![Atom {
+                x: 0.1,
+                y: 0.2,
+                z: 0.3,
+                u11: 0.01,
+                u22: 0.02,
+                u33: 0.03,
+                ..Default::default()
+            }]),
+        };
+
+        let transformed = CellTransformation::parse("b,a,-c")
+            .unwrap()
+            .transform_phase(&phase)
+            .unwrap();
+
+        assert_eq!(transformed.cell.space_group_number, 123);
+        assert_eq!(transformed.atoms.len(), 1);
+        assert_abs_diff_eq!(transformed.atoms[0].x, 0.2, epsilon = 1e-12);
+        assert_abs_diff_eq!(transformed.atoms[0].z, 0.7, epsilon = 1e-12);
+        assert_abs_diff_eq!(transformed.atoms[0].u11, 0.02, epsilon = 1e-12);
+
+        // moving the origin to the body centre of Pm-3m
+        let mut cubic = rock_salt();
+        cubic.cell.space_group_number = 221;
+
+        let shifted = CellTransformation::parse("a,b,c;1/2,1/2,1/2")
+            .unwrap()
+            .transform_phase(&cubic)
+            .unwrap();
+
+        assert_eq!(shifted.cell.space_group_number, 221);
+        assert_eq!(shifted.atoms[0].x, 0.5);
+        assert_eq!(shifted.atoms[1].x, 0.0);
+    }
+
+    #[test]
+    fn test_primitive_and_back() {
+        let to_primitive = CellTransformation::parse("1/2b+1/2c,1/2a+1/2c,1/2a+1/2b").unwrap();
+        let primitive = to_primitive.transform_phase(&rock_salt()).unwrap();
+
+        assert_eq!(primitive.cell.space_group_number, 225);
+        assert_eq!(primitive.cell.setting_operations.len(), 48);
+        assert_eq!(primitive.atoms.len(), 2);
+        assert_eq!(primitive.expand_to_p1().atoms.len(), 2);
+        assert_abs_diff_eq!(primitive.cell.a, 5.64 / 2f64.sqrt(), epsilon = 1e-10);
+        assert_abs_diff_eq!(primitive.cell.alpha, 60.0, epsilon = 1e-10);
+
+        let conventional = to_primitive
+            .inverse()
+            .unwrap()
+            .transform_phase(&primitive)
+            .unwrap();
+
+        assert_eq!(conventional.cell.space_group_number, 225);
+        assert!(conventional.cell.setting_operations.is_empty());
+        assert_eq!(conventional.expand_to_p1().atoms.len(), 8);
+        assert_abs_diff_eq!(conventional.cell.a, 5.64, epsilon = 1e-10);
+        assert_abs_diff_eq!(conventional.cell.gamma, 90.0, epsilon = 1e-10);
+    }
+
+    #[test]
+    fn test_invalid() {
+        let mirror = CellTransformation::parse("-a,b,c").unwrap();
+
+        assert!(mirror.transform_phase(&rock_salt()).is_err());
+        assert!(mirror.transform_phase_to_p1(&rock_salt()).is_err());
+    }
+}